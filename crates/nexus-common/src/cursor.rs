@@ -0,0 +1,112 @@
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, SecondsFormat, Utc};
+use uuid::Uuid;
+
+use crate::error::NexusError;
+
+/// Keyset pagination position: the `(created_at, id)` of the last item on a
+/// page, newest first. Clients see it as an opaque URL-safe string; paging
+/// stays stable when items are inserted between requests, unlike offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl Cursor {
+    pub fn new(created_at: DateTime<Utc>, id: Uuid) -> Self {
+        Self { created_at, id }
+    }
+
+    /// The opaque form handed to clients.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}", self.created_at_param(), self.id))
+    }
+
+    /// Parse a cursor previously returned by [`Cursor::encode`].
+    pub fn decode(encoded: &str) -> Result<Self, NexusError> {
+        let invalid = || NexusError::Validation("Invalid pagination cursor".into());
+
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).map_err(|_| invalid())?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (created_at, id) = raw.split_once('|').ok_or_else(invalid)?;
+        Ok(Self {
+            created_at: DateTime::parse_from_rfc3339(created_at)
+                .map_err(|_| invalid())?
+                .with_timezone(&Utc),
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+
+    /// `created_at` as bound to queries: RFC 3339 with full precision.
+    pub fn created_at_param(&self) -> String {
+        self.created_at.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    /// Postgres condition selecting rows after this cursor in
+    /// `ORDER BY created_at DESC, id DESC` order. Bind `created_at` and `id`
+    /// as parameters `$first_param` and `$first_param + 1`.
+    pub fn sql_predicate(created_at_column: &str, id_column: &str, first_param: usize) -> String {
+        format!(
+            "({created_at_column}, {id_column}) < (${first_param}, ${})",
+            first_param + 1
+        )
+    }
+
+    /// Cypher condition selecting nodes after this cursor, for nodes storing
+    /// `created_at` as an RFC 3339 string and `id` as a UUID string. Bind
+    /// `$cursor_created_at` ([`Cursor::created_at_param`]) and `$cursor_id`.
+    pub fn cypher_predicate(node: &str) -> String {
+        format!(
+            "(datetime({node}.created_at) < datetime($cursor_created_at)
+              OR (datetime({node}.created_at) = datetime($cursor_created_at)
+                  AND {node}.id < $cursor_id))"
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn cursor() -> Cursor {
+        let created_at = Utc.timestamp_opt(1_700_000_000, 123_456_789).unwrap();
+        Cursor::new(created_at, Uuid::new_v4())
+    }
+
+    #[test]
+    fn round_trips_with_full_precision() {
+        let cursor = cursor();
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+    }
+
+    #[test]
+    fn encoding_is_url_safe() {
+        let encoded = cursor().encode();
+        assert!(
+            encoded
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_cursors() {
+        let id = Uuid::new_v4();
+        for encoded in [
+            String::new(),
+            "not a cursor!".to_string(),
+            URL_SAFE_NO_PAD.encode("no separator"),
+            URL_SAFE_NO_PAD.encode(format!("yesterday|{id}")),
+            URL_SAFE_NO_PAD.encode("2024-01-01T00:00:00Z|not-a-uuid"),
+            URL_SAFE_NO_PAD.encode([0xff, 0xfe, b'|']),
+        ] {
+            assert!(
+                matches!(Cursor::decode(&encoded), Err(NexusError::Validation(_))),
+                "accepted {encoded:?}"
+            );
+        }
+    }
+}
@@ -36,6 +36,18 @@ pub enum NexusError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    #[error("Unprocessable: {0}")]
+    Unprocessable(String),
+
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
@@ -45,3 +57,34 @@ pub enum NexusError {
     #[error("Internal error: {0}")]
     Internal(String),
 }
+
+// Driver errors map to the variant for their backend, so callers can use
+// `.map_err(NexusError::from)` and keep the backend-specific HTTP status.
+
+#[cfg(feature = "neo4rs")]
+impl From<neo4rs::Error> for NexusError {
+    fn from(e: neo4rs::Error) -> Self {
+        NexusError::Neo4j(e.to_string())
+    }
+}
+
+#[cfg(feature = "qdrant-client")]
+impl From<qdrant_client::QdrantError> for NexusError {
+    fn from(e: qdrant_client::QdrantError) -> Self {
+        NexusError::VectorStore(e.to_string())
+    }
+}
+
+#[cfg(feature = "influxdb2")]
+impl From<influxdb2::RequestError> for NexusError {
+    fn from(e: influxdb2::RequestError) -> Self {
+        NexusError::TimeSeries(e.to_string())
+    }
+}
+
+#[cfg(feature = "redis")]
+impl From<redis::RedisError> for NexusError {
+    fn from(e: redis::RedisError) -> Self {
+        NexusError::Cache(e.to_string())
+    }
+}
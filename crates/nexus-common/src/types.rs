@@ -1,9 +1,16 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
 
+use crate::error::NexusError;
+
 /// Chat mode determines which engine processes the message.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Deserializes through [`FromStr`], so an unknown mode is an error naming the
+/// valid ones rather than a silent fallback.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ChatMode {
     /// Pure River: epistemic dialogue with belief tracking.
@@ -15,6 +22,54 @@ pub enum ChatMode {
     Integrated,
 }
 
+impl ChatMode {
+    pub const ALL: [ChatMode; 3] = [Self::Conversation, Self::Analysis, Self::Integrated];
+
+    /// The mode's name as used in requests and stored on sessions.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Conversation => "conversation",
+            Self::Analysis => "analysis",
+            Self::Integrated => "integrated",
+        }
+    }
+}
+
+impl fmt::Display for ChatMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A mode name that isn't one of [`ChatMode::ALL`].
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown mode '{0}' (expected conversation, analysis or integrated)")]
+pub struct ParseChatModeError(pub String);
+
+impl From<ParseChatModeError> for NexusError {
+    fn from(e: ParseChatModeError) -> Self {
+        NexusError::Validation(e.to_string())
+    }
+}
+
+impl FromStr for ChatMode {
+    type Err = ParseChatModeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.as_str() == s)
+            .ok_or_else(|| ParseChatModeError(s.to_string()))
+    }
+}
+
+impl<'de> Deserialize<'de> for ChatMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// A single message in a conversation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -28,6 +83,25 @@ pub struct Message {
     pub created_at: DateTime<Utc>,
 }
 
+/// Diagnostics recorded with an assistant message, stored as its `metadata`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageMetadata {
+    pub model: String,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub latency_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memories_recalled: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contradictions: Option<usize>,
+    /// The assistant message this one was regenerated from.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regenerates: Option<Uuid>,
+    /// How the reply's questions were asked, e.g. `layered`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question_style: Option<String>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MessageRole {
@@ -44,10 +118,72 @@ pub struct Belief {
     pub claim: String,
     pub confidence: f64,
     pub source_message_id: Uuid,
+    /// Topic the belief falls under, e.g. "politics", "self", "ethics".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// What happened to a belief, as recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BeliefAuditAction {
+    Created,
+    Revised,
+    Deleted,
+}
+
+impl BeliefAuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Revised => "revised",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+/// One row of a user's belief audit log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeliefAuditEntry {
+    pub id: i64,
+    pub belief_id: Uuid,
+    pub user_id: Uuid,
+    pub action: String,
+    pub old_confidence: Option<f64>,
+    pub new_confidence: Option<f64>,
+    pub source_message_id: Option<Uuid>,
+    /// Who made the change; `None` for changes the system made.
+    pub actor_id: Option<Uuid>,
+    pub reason: String,
+    /// SHA-256 over this entry and `prev_hash`, chaining the user's log.
+    pub prev_hash: Option<String>,
+    pub hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A stored contradiction between two of a user's beliefs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContradictionRecord {
+    pub belief_a_id: Uuid,
+    pub belief_b_id: Uuid,
+    pub user_id: Uuid,
+    pub explanation: String,
+    pub severity: f64,
+    pub detected_at: DateTime<Utc>,
+    /// When the user marked it resolved; `None` while it stands.
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// A belief matched by semantic search, with its similarity score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredBelief {
+    #[serde(flatten)]
+    pub belief: Belief,
+    pub score: f32,
+}
+
 /// A contradiction detected between two beliefs.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Contradiction {
@@ -55,6 +191,96 @@ pub struct Contradiction {
     pub belief_b: Belief,
     pub explanation: String,
     pub severity: f64,
+    /// Severity is under the configured threshold: not linked in the graph or
+    /// raised in dialogue, but returned so clients can choose to show it.
+    #[serde(default)]
+    pub below_threshold: bool,
+}
+
+/// The kind of relationship between two belief nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BeliefEdgeKind {
+    Contradicts,
+    Revised,
+}
+
+/// A directed relationship between two beliefs in the graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeliefEdge {
+    pub source: Uuid,
+    pub target: Uuid,
+    pub kind: BeliefEdgeKind,
+    pub explanation: Option<String>,
+    pub severity: Option<f64>,
+}
+
+/// A user's belief network: belief nodes plus the edges between them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeliefGraph {
+    pub nodes: Vec<Belief>,
+    pub edges: Vec<BeliefEdge>,
+}
+
+/// Format version of [`BeliefSnapshot`] written by this build. Snapshots with
+/// any other version are rejected on import.
+pub const BELIEF_SNAPSHOT_VERSION: u32 = 1;
+
+/// A portable copy of a user's belief graph, for moving an account between
+/// instances. Ids are those of the exporting instance; an import assigns new ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeliefSnapshot {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub beliefs: Vec<Belief>,
+    pub edges: Vec<BeliefEdge>,
+}
+
+/// Beliefs whose confidence falls in `[min, max)` (the last bucket includes 1.0).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfidenceBucket {
+    pub min: f64,
+    pub max: f64,
+    pub count: usize,
+}
+
+/// Overview of a user's belief network.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeliefSummary {
+    pub total: usize,
+    pub confidence_histogram: Vec<ConfidenceBucket>,
+    pub categories: Vec<BeliefCategoryCount>,
+    /// Number of contradictions between the user's beliefs.
+    pub tensions: usize,
+    /// LLM-written description of the worldview and its tensions.
+    pub narrative: String,
+}
+
+/// A user's activity for one (UTC) day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Digest {
+    pub user_id: Uuid,
+    pub date: NaiveDate,
+    pub new_beliefs: Vec<Belief>,
+    pub contradictions: Vec<DigestContradiction>,
+    /// LLM-written reflection on the day.
+    pub reflection: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A contradiction surfaced during a digest's day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestContradiction {
+    pub claim_a: String,
+    pub claim_b: String,
+    pub explanation: Option<String>,
+}
+
+/// Number of beliefs a user holds in one category.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BeliefCategoryCount {
+    pub category: String,
+    pub count: i64,
 }
 
 /// Consciousness metrics snapshot.
@@ -70,6 +296,10 @@ pub struct ConsciousnessState {
 }
 
 // ── Perspective Analysis Types ──
+//
+// Layer entries carry a `confidence` from 0 to 1 (entries stored before it
+// was recorded read as 1), and a `chunk_index` when the input was long enough
+// to be analysed in chunks, identifying the passage each finding came from.
 
 /// Complete 4-layer analysis result.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -81,15 +311,72 @@ pub struct AnalysisResult {
     pub discourse: DiscourseAnalysis,
     pub critical_synthesis: CriticalSynthesis,
     pub created_at: DateTime<Utc>,
+    /// Layers that failed and were replaced with empty results.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
 }
 
-/// Layer 1: Syntactic analysis.
+/// A past analysis matched by full-text search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisMatch {
+    pub id: Uuid,
+    /// Matching fragments of the input, search terms wrapped in `<mark>`.
+    pub snippet: String,
+    pub rank: f32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One feature's count summed, averaged and maximised over a set of analyses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureStats {
+    pub total: i64,
+    pub mean: f64,
+    pub max: i64,
+}
+
+/// Feature counts aggregated over a user's stored analyses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnalysisStats {
+    pub analyses: i64,
+    pub passive_voice: FeatureStats,
+    pub nominalisations: FeatureStats,
+    pub presuppositions: FeatureStats,
+    pub implicatures: FeatureStats,
+    pub framings: FeatureStats,
+    pub naturalised_claims: FeatureStats,
+}
+
+/// Items found in only one of two compared analyses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeatureDiff {
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+}
+
+/// Structural differences between the analyses of two texts.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnalysisDiff {
+    pub analysis_a: Uuid,
+    pub analysis_b: Uuid,
+    pub nominalisations: FeatureDiff,
+    pub presuppositions: FeatureDiff,
+    pub framings: FeatureDiff,
+    pub passive_count_a: usize,
+    pub passive_count_b: usize,
+    /// `passive_count_b - passive_count_a`.
+    pub passive_delta: i64,
+}
+
+/// Layer 1: Syntactic analysis.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SyntacticAnalysis {
     pub voice_analysis: Vec<VoiceInstance>,
     pub sentence_complexity: Vec<SentenceComplexity>,
     pub nominalisations: Vec<Nominalisation>,
     pub transitivity: Vec<TransitivityInstance>,
+    /// Hedges and strong modals; absent from analyses stored before it existed.
+    #[serde(default)]
+    pub modality: Vec<ModalityInstance>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -97,6 +384,15 @@ pub struct VoiceInstance {
     pub sentence: String,
     pub voice: VoiceType,
     pub significance: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    /// Character offsets of `sentence` in the analysed text, when it could be located.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -112,6 +408,15 @@ pub struct SentenceComplexity {
     pub score: f64,
     pub clause_count: u32,
     pub note: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    /// Character offsets of `sentence` in the analysed text, when it could be located.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,6 +424,53 @@ pub struct Nominalisation {
     pub original: String,
     pub verb_form: String,
     pub effect: String,
+    /// Number of times the word occurs in the analysed text.
+    #[serde(default = "default_frequency")]
+    pub frequency: u32,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    /// Character offsets of `original` in the analysed text, at its first occurrence.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+}
+
+/// A hedge or strong modal found in a sentence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModalityInstance {
+    /// The marker as written, e.g. "might", "I think" or "never".
+    pub marker: String,
+    pub strength: ModalityStrength,
+    pub sentence: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    /// Character offsets of `marker` in the analysed text, when it could be located.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModalityStrength {
+    /// An epistemic hedge softening the claim: "might", "perhaps", "I think".
+    Hedge,
+    /// A deontic or absolute claim: "must", "always", "never".
+    Strong,
+}
+
+fn default_frequency() -> u32 {
+    1
+}
+
+fn default_confidence() -> f64 {
+    1.0
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -128,10 +480,19 @@ pub struct TransitivityInstance {
     pub process: String,
     pub affected: String,
     pub analysis: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+    /// Character offsets of `sentence` in the analysed text, when it could be located.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
 }
 
 /// Layer 2: Semantic analysis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SemanticAnalysis {
     pub presuppositions: Vec<Presupposition>,
     pub implicatures: Vec<Implicature>,
@@ -144,6 +505,10 @@ pub struct Presupposition {
     pub trigger: String,
     pub presupposed_content: String,
     pub significance: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -151,6 +516,10 @@ pub struct Implicature {
     pub statement: String,
     pub implied_meaning: String,
     pub mechanism: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,6 +528,10 @@ pub struct PowerHierarchy {
     pub subordinate: String,
     pub linguistic_markers: Vec<String>,
     pub analysis: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -166,10 +539,14 @@ pub struct LexicalField {
     pub field_name: String,
     pub terms: Vec<String>,
     pub connotation: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 /// Layer 3: Discourse analysis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DiscourseAnalysis {
     pub framing: Vec<FramingInstance>,
     pub strategic_omissions: Vec<StrategicOmission>,
@@ -182,6 +559,10 @@ pub struct FramingInstance {
     pub frame_name: String,
     pub evidence: String,
     pub effect: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -189,6 +570,10 @@ pub struct StrategicOmission {
     pub what_is_missing: String,
     pub why_it_matters: String,
     pub who_benefits: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -196,6 +581,10 @@ pub struct CollocationPattern {
     pub pattern: String,
     pub frequency_note: String,
     pub ideological_loading: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -203,10 +592,14 @@ pub struct IntertextualityMarker {
     pub reference: String,
     pub source_discourse: String,
     pub function: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 /// Layer 4: Critical synthesis.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CriticalSynthesis {
     pub naturalised_claims: Vec<NaturalisedClaim>,
     pub beneficiary_analysis: Vec<BeneficiaryAnalysis>,
@@ -219,6 +612,10 @@ pub struct NaturalisedClaim {
     pub claim: String,
     pub how_naturalised: String,
     pub counter_evidence: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -226,6 +623,10 @@ pub struct BeneficiaryAnalysis {
     pub who_benefits: String,
     pub how: String,
     pub who_is_disadvantaged: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -233,6 +634,10 @@ pub struct HiddenContext {
     pub context: String,
     pub relevance: String,
     pub why_hidden: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -240,4 +645,36 @@ pub struct AlternativeFraming {
     pub original_frame: String,
     pub alternative: String,
     pub same_facts_used: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chunk_index: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chat_mode_round_trips_through_strings() {
+        for mode in ChatMode::ALL {
+            assert_eq!(mode.to_string().parse::<ChatMode>(), Ok(mode));
+        }
+        assert_eq!(ChatMode::Conversation.to_string(), "conversation");
+        assert_eq!(ChatMode::Analysis.to_string(), "analysis");
+        assert_eq!(ChatMode::Integrated.to_string(), "integrated");
+    }
+
+    #[test]
+    fn chat_mode_rejects_unknown_names() {
+        assert_eq!(
+            "Analysis".parse::<ChatMode>(),
+            Err(ParseChatModeError("Analysis".into()))
+        );
+        assert!(serde_json::from_str::<ChatMode>("\"debate\"").is_err());
+        assert_eq!(
+            serde_json::from_str::<ChatMode>("\"analysis\"").unwrap(),
+            ChatMode::Analysis
+        );
+    }
 }
@@ -0,0 +1,174 @@
+//! Filtered recall latency in Qdrant, with and without the payload indexes and
+//! quantization `episodic::ensure_collection` sets up. Each variant gets a
+//! scratch collection of synthetic points spread over many users, created
+//! with the same `db::qdrant` helpers, searched the way recall does (top-k
+//! within one user) and then dropped.
+//!
+//! Needs `QDRANT_URL`:
+//!
+//! ```text
+//! cargo bench -p nexus-server --bench recall
+//! ```
+//!
+//! `RECALL_BENCH_POINTS` (5000) and `RECALL_BENCH_USERS` (50) set the size.
+
+use std::time::{Duration, Instant};
+
+use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{
+    Condition, DeleteCollectionBuilder, Distance, Filter, NamedVectors, PointStruct,
+    SearchPointsBuilder, UpsertPointsBuilder,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+// The binary crate's modules can't be imported from a bench; this one only
+// depends on the Qdrant client. Its other helpers go unused here.
+#[allow(dead_code)]
+#[path = "../src/db/qdrant.rs"]
+mod qdrant;
+
+/// Dimension of the default embedding model.
+const DIM: usize = 384;
+/// Named vector recall searches, as in `episodic`.
+const DENSE_VECTOR: &str = "dense";
+const QUERIES: usize = 200;
+const LIMIT: u64 = 5;
+const UPSERT_BATCH: usize = 500;
+
+struct Variant {
+    name: &'static str,
+    indexed: bool,
+    quantized: bool,
+}
+
+const VARIANTS: [Variant; 3] = [
+    Variant {
+        name: "unindexed",
+        indexed: false,
+        quantized: false,
+    },
+    Variant {
+        name: "indexed",
+        indexed: true,
+        quantized: false,
+    },
+    Variant {
+        name: "indexed+quantized",
+        indexed: true,
+        quantized: true,
+    },
+];
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    let client = Qdrant::from_url(&std::env::var("QDRANT_URL")?).build()?;
+    let points = env_or("RECALL_BENCH_POINTS", 5000);
+    let users: Vec<String> = (0..env_or("RECALL_BENCH_USERS", 50))
+        .map(|_| Uuid::new_v4().to_string())
+        .collect();
+
+    println!(
+        "{points} points over {} users, {QUERIES} queries, top {LIMIT}",
+        users.len()
+    );
+    for variant in &VARIANTS {
+        let collection = format!("recall_bench_{}", Uuid::new_v4().simple());
+        let outcome = bench(&client, &collection, variant, &users, points).await;
+        client
+            .delete_collection(DeleteCollectionBuilder::new(&collection))
+            .await?;
+        report(variant.name, outcome?);
+    }
+    Ok(())
+}
+
+async fn bench(
+    client: &Qdrant,
+    collection: &str,
+    variant: &Variant,
+    users: &[String],
+    points: usize,
+) -> anyhow::Result<Vec<Duration>> {
+    qdrant::create_collection(
+        client,
+        collection,
+        DENSE_VECTOR,
+        DIM as u64,
+        Distance::Cosine,
+        variant.quantized,
+    )
+    .await?;
+    if variant.indexed {
+        qdrant::ensure_keyword_indexes(client, collection, &["user_id", "session_id"]).await?;
+        qdrant::ensure_text_index(client, collection, "content").await?;
+    }
+
+    let mut seed = 1;
+    for start in (0..points).step_by(UPSERT_BATCH) {
+        let batch: Vec<PointStruct> = (start..points.min(start + UPSERT_BATCH))
+            .map(|i| {
+                let payload: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_value(json!({
+                        "user_id": users[i % users.len()],
+                        "session_id": Uuid::new_v4().to_string(),
+                        "content": format!("synthetic memory {i}"),
+                    }))
+                    .expect("payload is an object");
+                let vectors = NamedVectors::default().add_vector(DENSE_VECTOR, vector(&mut seed));
+                PointStruct::new(Uuid::new_v4().to_string(), vectors, payload)
+            })
+            .collect();
+        client
+            .upsert_points(UpsertPointsBuilder::new(collection, batch).wait(true))
+            .await?;
+    }
+
+    let mut latencies = Vec::with_capacity(QUERIES);
+    for i in 0..QUERIES {
+        let filter = Filter::must([Condition::matches(
+            "user_id",
+            users[i % users.len()].clone(),
+        )]);
+        let search = SearchPointsBuilder::new(collection, vector(&mut seed), LIMIT)
+            .vector_name(DENSE_VECTOR)
+            .filter(filter);
+        let started = Instant::now();
+        client.search_points(search).await?;
+        latencies.push(started.elapsed());
+    }
+    Ok(latencies)
+}
+
+fn report(name: &str, mut latencies: Vec<Duration>) {
+    latencies.sort();
+    let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+    let mean = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+    println!(
+        "{name:>18}: mean {mean:>9.2?}  p50 {:>9.2?}  p95 {:>9.2?}  p99 {:>9.2?}",
+        percentile(50),
+        percentile(95),
+        percentile(99),
+    );
+}
+
+/// A pseudo-random vector from a xorshift generator, so runs are repeatable
+/// without a dependency on `rand`.
+fn vector(seed: &mut u64) -> Vec<f32> {
+    (0..DIM)
+        .map(|_| {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            (*seed >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+        })
+        .collect()
+}
+
+fn env_or(key: &str, default: usize) -> usize {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
@@ -0,0 +1,146 @@
+use std::future::Future;
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use serde::Serialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use uuid::Uuid;
+
+/// Messages buffered per session for subscribers that fall behind.
+const CHANNEL_CAPACITY: usize = 64;
+
+tokio::task_local! {
+    /// The connection whose work is running, set by [`from_connection`].
+    static ORIGIN: Uuid;
+}
+
+/// Run `work` on behalf of a connection: messages it publishes go to every
+/// other socket on the session but not back to this one, which gets its reply
+/// directly.
+pub async fn from_connection<F: Future>(connection_id: Uuid, work: F) -> F::Output {
+    ORIGIN.scope(connection_id, work).await
+}
+
+/// A message saved to a session, as pushed to every socket open on it.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionMessage {
+    pub id: Uuid,
+    pub session_id: Uuid,
+    pub role: String,
+    pub content: String,
+    pub mode: String,
+}
+
+/// A published message with the connection it came from, if any.
+type Envelope = (Option<Uuid>, SessionMessage);
+
+/// One broadcast channel per session with connected WebSockets, so every tab
+/// sees the messages produced from any of them. Channels exist only while
+/// someone is subscribed.
+#[derive(Clone, Default)]
+pub struct SessionHub {
+    channels: Arc<DashMap<Uuid, broadcast::Sender<Envelope>>>,
+}
+
+impl SessionHub {
+    /// Subscribe `connection_id` to a session, creating its channel if this is
+    /// the first socket.
+    pub fn subscribe(&self, session_id: Uuid, connection_id: Uuid) -> Subscription {
+        let receiver = self
+            .channels
+            .entry(session_id)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe();
+        Subscription {
+            hub: self.clone(),
+            session_id,
+            connection_id,
+            receiver: Some(receiver),
+        }
+    }
+
+    /// Send a message to the session's sockets, except the connection it was
+    /// published from. A no-op if none are connected.
+    pub fn publish(&self, message: SessionMessage) {
+        if let Some(sender) = self.channels.get(&message.session_id) {
+            let origin = ORIGIN.try_with(|id| *id).ok();
+            let _ = sender.send((origin, message));
+        }
+    }
+}
+
+/// A socket's subscription to a session. The channel is removed when the last
+/// subscription is dropped.
+pub struct Subscription {
+    hub: SessionHub,
+    session_id: Uuid,
+    connection_id: Uuid,
+    receiver: Option<broadcast::Receiver<Envelope>>,
+}
+
+impl Subscription {
+    /// The next message for the session from another connection. Messages
+    /// missed by falling more than the channel capacity behind are skipped.
+    pub async fn recv(&mut self) -> SessionMessage {
+        while let Some(receiver) = self.receiver.as_mut() {
+            match receiver.recv().await {
+                Ok((Some(origin), _)) if origin == self.connection_id => {}
+                Ok((_, message)) => return message,
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!(session_id = %self.session_id, skipped, "Subscriber lagged");
+                }
+                // The hub keeps the sender while we're subscribed, so this
+                // shouldn't happen; wait forever rather than spin.
+                Err(RecvError::Closed) => self.receiver = None,
+            }
+        }
+        std::future::pending().await
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        // Release our receiver first so the count below doesn't include it.
+        drop(self.receiver.take());
+        self.hub
+            .channels
+            .remove_if(&self.session_id, |_, sender| sender.receiver_count() == 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn message(session_id: Uuid, content: &str) -> SessionMessage {
+        SessionMessage {
+            id: Uuid::new_v4(),
+            session_id,
+            role: "user".into(),
+            content: content.into(),
+            mode: "dialogue".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn messages_skip_the_connection_that_sent_them() {
+        let hub = SessionHub::default();
+        let session_id = Uuid::new_v4();
+        let (sender, other) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut sender_sub = hub.subscribe(session_id, sender);
+        let mut other_sub = hub.subscribe(session_id, other);
+
+        from_connection(sender, async { hub.publish(message(session_id, "mine")) }).await;
+        hub.publish(message(session_id, "http"));
+
+        assert_eq!(other_sub.recv().await.content, "mine");
+        assert_eq!(other_sub.recv().await.content, "http");
+        assert_eq!(sender_sub.recv().await.content, "http");
+        assert!(
+            tokio::time::timeout(Duration::from_millis(20), sender_sub.recv())
+                .await
+                .is_err()
+        );
+    }
+}
@@ -3,30 +3,70 @@ use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
 use nexus_common::error::NexusError;
 
+use crate::api::middleware::current_request_id;
 use crate::models::responses::ErrorResponse;
 
 /// Wrapper so we can implement IntoResponse for anyhow::Error.
 pub struct AppError(pub anyhow::Error);
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, message) = match self.0.downcast_ref::<NexusError>() {
+impl AppError {
+    /// The status and message to report for this error. Internal details are
+    /// logged here and replaced with a generic message.
+    pub fn status_and_message(&self) -> (StatusCode, String) {
+        let request_id = current_request_id();
+
+        match self.0.downcast_ref::<NexusError>() {
             Some(NexusError::NotFound(msg)) => (StatusCode::NOT_FOUND, msg.clone()),
             Some(NexusError::Auth(msg)) => (StatusCode::UNAUTHORIZED, msg.clone()),
             Some(NexusError::Validation(msg)) => (StatusCode::BAD_REQUEST, msg.clone()),
+            Some(NexusError::Conflict(msg)) => (StatusCode::CONFLICT, msg.clone()),
+            Some(NexusError::Unprocessable(msg)) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
+            Some(NexusError::UnsupportedMediaType(msg)) => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, msg.clone())
+            }
+            Some(NexusError::Analysis(msg)) if msg == "timeout" => (
+                StatusCode::GATEWAY_TIMEOUT,
+                "Analysis timed out".to_string(),
+            ),
             Some(NexusError::Llm(msg)) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+            Some(NexusError::Unavailable(msg)) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+            // Upstream stores and models being down is not a server bug: report
+            // it as retryable without leaking driver details to the client.
+            Some(
+                err @ (NexusError::Embedding(_)
+                | NexusError::VectorStore(_)
+                | NexusError::Neo4j(_)
+                | NexusError::TimeSeries(_)),
+            ) => {
+                tracing::warn!(request_id = ?request_id, "Upstream unavailable: {:?}", self.0);
+                let service = match err {
+                    NexusError::Embedding(_) => "Embedding service",
+                    NexusError::VectorStore(_) => "Vector store",
+                    NexusError::Neo4j(_) => "Graph database",
+                    _ => "Metrics store",
+                };
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    format!("{service} is temporarily unavailable"),
+                )
+            }
             _ => {
-                tracing::error!("Internal error: {:?}", self.0);
+                tracing::error!(request_id = ?request_id, "Internal error: {:?}", self.0);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
                     "Internal server error".to_string(),
                 )
             }
-        };
+        }
+    }
+}
 
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, message) = self.status_and_message();
         let body = Json(ErrorResponse {
             error: message,
-            details: None,
+            details: current_request_id(),
         });
 
         (status, body).into_response()
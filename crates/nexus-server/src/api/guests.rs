@@ -0,0 +1,92 @@
+use anyhow::Result;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::river::{beliefs, consciousness, episodic};
+use nexus_common::error::NexusError;
+
+/// Expired guests removed per pruning pass.
+const PRUNE_BATCH: i64 = 100;
+
+/// Create a guest account, returning its id and username. Guests have no
+/// password, so they can't log in; the token issued at creation is their
+/// only credential.
+pub async fn create_guest(state: &AppState) -> Result<(Uuid, String)> {
+    let user_id = Uuid::new_v4();
+    let username = format!("guest-{}", &user_id.simple().to_string()[..12]);
+
+    sqlx::query(
+        "INSERT INTO users (id, username, email, password_hash, is_guest)
+         VALUES ($1, $2, $3, '!', TRUE)",
+    )
+    .bind(user_id)
+    .bind(&username)
+    .bind(format!("{user_id}@guest.invalid"))
+    .execute(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to create guest: {e}")))?;
+
+    tracing::info!(%user_id, "Created guest account");
+    Ok((user_id, username))
+}
+
+/// Delete guest accounts older than `GUEST_RETENTION_HOURS`: their beliefs,
+/// memories and consciousness metrics first, then the user row, which cascades
+/// to sessions, messages, analyses and digests. A guest whose data can't be
+/// deleted is kept for the next pass. Returns how many were removed.
+///
+/// `belief_audit` rows are kept: the ledger is append-only and hash-chained,
+/// and they hold ids and confidences but no message or belief text.
+pub async fn prune_guests(state: &AppState) -> Result<usize> {
+    let cutoff =
+        chrono::Utc::now() - chrono::Duration::hours(state.config.guest.retention_hours as i64);
+    let mut removed = 0;
+
+    loop {
+        let expired: Vec<(Uuid,)> = sqlx::query_as(
+            "SELECT id FROM users WHERE is_guest AND created_at < $1 ORDER BY created_at LIMIT $2",
+        )
+        .bind(cutoff)
+        .bind(PRUNE_BATCH)
+        .fetch_all(&state.db.pg)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to find expired guests: {e}")))?;
+        if expired.is_empty() {
+            break;
+        }
+        let ids: Vec<Uuid> = expired.into_iter().map(|(id,)| id).collect();
+
+        beliefs::delete_user_beliefs(state, &ids).await?;
+        episodic::delete_user_memories(state, &ids).await?;
+        consciousness::delete_user_metrics(state, &ids).await?;
+        sqlx::query("DELETE FROM users WHERE id = ANY($1) AND is_guest")
+            .bind(&ids)
+            .execute(&state.db.pg)
+            .await
+            .map_err(|e| NexusError::Database(format!("Failed to delete guests: {e}")))?;
+
+        removed += ids.len();
+        if (ids.len() as i64) < PRUNE_BATCH {
+            break;
+        }
+    }
+
+    if removed > 0 {
+        tracing::info!(removed, "Pruned expired guest accounts");
+    }
+    Ok(removed)
+}
+
+/// Periodically prune expired guests. Runs until the process exits.
+pub async fn run_pruning(state: AppState) {
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(state.config.guest.prune_interval_secs));
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = prune_guests(&state).await {
+            tracing::warn!("Guest pruning failed: {e:#}");
+        }
+    }
+}
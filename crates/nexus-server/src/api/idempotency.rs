@@ -0,0 +1,160 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::models::requests::ChatRequest;
+use crate::models::responses::ChatResponse;
+use nexus_common::error::NexusError;
+
+/// Header clients use to mark a retried request.
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+/// Keep idempotent responses for 15 minutes — long enough to cover client retries.
+const IDEMPOTENCY_TTL_SECS: u64 = 900;
+
+/// How long a key stays reserved for a request that never finishes, such as
+/// one whose server died mid-way.
+const RESERVATION_TTL_MS: u64 = 300_000;
+
+#[derive(Deserialize)]
+struct IdempotencyRecord {
+    fingerprint: String,
+    /// `None` while the request holding the key is still being processed.
+    response: Option<ChatResponse>,
+}
+
+fn redis_key(user_id: Uuid, key: &str) -> String {
+    format!("idem:{user_id}:{key}")
+}
+
+/// Hash the parts of a chat request that determine its outcome. The message
+/// goes last so a `|` in it can't shift the other fields.
+pub fn fingerprint(req: &ChatRequest) -> String {
+    let fields = [
+        format!("{:?}", req.mode),
+        format!("{:?}", req.session_id),
+        req.explain.to_string(),
+        format!("{:?}", req.recall_scope),
+        format!("{:?}", req.question_style),
+        req.message.clone(),
+    ];
+    let digest = Sha256::digest(fields.join("|").as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Reserve the key for this request, or return the response already stored
+/// under it. Only the caller that gets `Ok(None)` processes the request.
+///
+/// Returns `Unprocessable` if the key was already used with a different
+/// request body, and `Conflict` if a request with it is still in flight.
+pub async fn reserve(
+    state: &AppState,
+    user_id: Uuid,
+    key: &str,
+    fingerprint: &str,
+) -> Result<Option<ChatResponse>> {
+    let mut conn = state.db.redis.clone();
+    let pending = serde_json::to_string(&serde_json::json!({
+        "fingerprint": fingerprint,
+        "response": null,
+    }))?;
+
+    let reserved: Option<String> = match redis::cmd("SET")
+        .arg(redis_key(user_id, key))
+        .arg(&pending)
+        .arg("NX")
+        .arg("PX")
+        .arg(RESERVATION_TTL_MS)
+        .query_async(&mut conn)
+        .await
+    {
+        Ok(reserved) => reserved,
+        Err(e) => {
+            tracing::warn!("Idempotency reservation failed, processing request normally: {e}");
+            return Ok(None);
+        }
+    };
+    if reserved.is_some() {
+        return Ok(None);
+    }
+
+    let raw: Option<String> = match redis::cmd("GET")
+        .arg(redis_key(user_id, key))
+        .query_async(&mut conn)
+        .await
+    {
+        Ok(raw) => raw,
+        Err(e) => {
+            tracing::warn!("Idempotency lookup failed, processing request normally: {e}");
+            return Ok(None);
+        }
+    };
+
+    // Released or expired since the SET: ask the client to try again rather
+    // than race another retry for it.
+    let Some(json) = raw else {
+        return Err(NexusError::Conflict(format!(
+            "Request with idempotency key '{key}' is still being processed"
+        ))
+        .into());
+    };
+
+    let record: IdempotencyRecord =
+        serde_json::from_str(&json).context("Failed to deserialize idempotent response")?;
+
+    if record.fingerprint != fingerprint {
+        return Err(NexusError::Unprocessable(format!(
+            "Idempotency key '{key}' was already used with a different request body"
+        ))
+        .into());
+    }
+
+    let Some(response) = record.response else {
+        return Err(NexusError::Conflict(format!(
+            "Request with idempotency key '{key}' is still being processed"
+        ))
+        .into());
+    };
+
+    tracing::debug!(%user_id, "Replaying idempotent chat response");
+    Ok(Some(response))
+}
+
+/// Drop the reservation of a request that failed, so the client can retry it.
+pub async fn release(state: &AppState, user_id: Uuid, key: &str) -> Result<()> {
+    let mut conn = state.db.redis.clone();
+    redis::cmd("DEL")
+        .arg(redis_key(user_id, key))
+        .query_async::<()>(&mut conn)
+        .await
+        .context("Failed to release idempotency key")?;
+    Ok(())
+}
+
+/// Store a response under the idempotency key, replacing its reservation.
+pub async fn store(
+    state: &AppState,
+    user_id: Uuid,
+    key: &str,
+    fingerprint: &str,
+    response: &ChatResponse,
+) -> Result<()> {
+    let mut conn = state.db.redis.clone();
+    let json = serde_json::to_string(&serde_json::json!({
+        "fingerprint": fingerprint,
+        "response": response,
+    }))?;
+
+    redis::cmd("SET")
+        .arg(redis_key(user_id, key))
+        .arg(&json)
+        .arg("EX")
+        .arg(IDEMPOTENCY_TTL_SECS)
+        .query_async::<()>(&mut conn)
+        .await
+        .context("Failed to store idempotent response")?;
+
+    Ok(())
+}
@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::{Mutex, mpsc};
+use uuid::Uuid;
+
+use crate::api::routes::run_chat;
+use crate::api::state::AppState;
+use crate::db::redis::record_backend_error;
+use crate::models::requests::ChatRequest;
+use crate::models::responses::{ChatResponse, JobResponse, JobStatus};
+use nexus_common::error::NexusError;
+
+/// Keep job results for a day so clients can poll at their leisure.
+const JOB_TTL_SECS: u64 = 86400;
+
+/// A chat job's state as stored in Redis.
+#[derive(Debug, Serialize, Deserialize)]
+struct JobRecord {
+    job_id: Uuid,
+    user_id: Uuid,
+    status: JobStatus,
+    response: Option<ChatResponse>,
+    error: Option<String>,
+}
+
+/// A queued chat request.
+pub struct ChatJob {
+    job_id: Uuid,
+    user_id: Uuid,
+    request: ChatRequest,
+}
+
+/// Handle for submitting chat jobs to the worker pool.
+#[derive(Clone)]
+pub struct JobQueue {
+    tx: mpsc::Sender<ChatJob>,
+}
+
+/// Receiving end of the job queue, consumed by [`spawn_workers`].
+pub struct JobReceiver(mpsc::Receiver<ChatJob>);
+
+/// Create a bounded job queue.
+pub fn channel(capacity: usize) -> (JobQueue, JobReceiver) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (JobQueue { tx }, JobReceiver(rx))
+}
+
+fn redis_key(job_id: Uuid) -> String {
+    format!("job:{job_id}")
+}
+
+/// Record a new pending job and hand it to the worker pool.
+///
+/// Returns `Unavailable` if the queue is full.
+pub async fn enqueue(state: &AppState, user_id: Uuid, request: ChatRequest) -> Result<Uuid> {
+    let job_id = Uuid::new_v4();
+    let record = JobRecord {
+        job_id,
+        user_id,
+        status: JobStatus::Pending,
+        response: None,
+        error: None,
+    };
+    save(state, &record).await?;
+
+    let job = ChatJob {
+        job_id,
+        user_id,
+        request,
+    };
+    if state.jobs.tx.try_send(job).is_err() {
+        let failed = JobRecord {
+            status: JobStatus::Failed,
+            error: Some("Job queue is full".into()),
+            ..record
+        };
+        let _ = save(state, &failed).await;
+        return Err(NexusError::Unavailable("Job queue is full, try again later".into()).into());
+    }
+
+    Ok(job_id)
+}
+
+/// Load a job, returning `NotFound` if it doesn't exist or belongs to another user.
+pub async fn get(state: &AppState, user_id: Uuid, job_id: Uuid) -> Result<JobResponse> {
+    let mut conn = state.db.redis.clone();
+
+    let raw: Option<String> = redis::cmd("GET")
+        .arg(redis_key(job_id))
+        .query_async(&mut conn)
+        .await
+        .map_err(|e| {
+            record_backend_error("job_get", &e);
+            NexusError::Unavailable("Job store is temporarily unavailable".into())
+        })?;
+
+    let record: Option<JobRecord> = raw.map(|json| serde_json::from_str(&json)).transpose()?;
+    let Some(record) = record.filter(|r| r.user_id == user_id) else {
+        return Err(NexusError::NotFound(format!("Job {job_id} not found")).into());
+    };
+
+    Ok(JobResponse {
+        job_id: record.job_id,
+        status: record.status,
+        response: record.response,
+        error: record.error,
+    })
+}
+
+async fn save(state: &AppState, record: &JobRecord) -> Result<()> {
+    let mut conn = state.db.redis.clone();
+    let json = serde_json::to_string(record)?;
+
+    redis::cmd("SET")
+        .arg(redis_key(record.job_id))
+        .arg(&json)
+        .arg("EX")
+        .arg(JOB_TTL_SECS)
+        .query_async::<()>(&mut conn)
+        .await
+        .context("Failed to save job")?;
+
+    Ok(())
+}
+
+/// Spawn `workers` tasks that drain the job queue.
+pub fn spawn_workers(state: AppState, rx: JobReceiver, workers: usize) {
+    let rx = Arc::new(Mutex::new(rx.0));
+
+    for worker in 0..workers.max(1) {
+        let state = state.clone();
+        let rx = rx.clone();
+        tokio::spawn(async move {
+            loop {
+                // Hold the lock only while waiting for the next job.
+                let Some(job) = rx.lock().await.recv().await else {
+                    break;
+                };
+                run_job(&state, job).await;
+            }
+            tracing::debug!(worker, "Job worker stopped");
+        });
+    }
+}
+
+async fn run_job(state: &AppState, job: ChatJob) {
+    let mut record = JobRecord {
+        job_id: job.job_id,
+        user_id: job.user_id,
+        status: JobStatus::Running,
+        response: None,
+        error: None,
+    };
+    if let Err(e) = save(state, &record).await {
+        tracing::warn!(job_id = %job.job_id, "Failed to mark job running: {e}");
+    }
+
+    match run_chat(state, job.user_id, &job.request).await {
+        Ok(response) => {
+            record.status = JobStatus::Done;
+            record.response = Some(response);
+        }
+        Err(e) => {
+            tracing::error!(job_id = %job.job_id, "Chat job failed: {:?}", e.0);
+            record.status = JobStatus::Failed;
+            record.error = Some(e.status_and_message().1);
+        }
+    }
+
+    if let Err(e) = save(state, &record).await {
+        tracing::error!(job_id = %job.job_id, "Failed to save job result: {e}");
+    }
+}
@@ -1,10 +1,13 @@
 use axum::{
-    extract::FromRequestParts,
-    http::{StatusCode, request::Parts},
+    extract::{FromRequestParts, Request},
+    http::{HeaderValue, StatusCode, request::Parts},
+    middleware::Next,
+    response::Response,
 };
 
 use crate::api::state::AppState;
-use crate::models::auth::{self, Claims};
+use crate::models::auth::{self, Claims, Role};
+use crate::shared::llm;
 
 /// Extractor that validates the JWT and provides Claims.
 pub struct AuthUser(pub Claims);
@@ -26,9 +29,82 @@ impl FromRequestParts<AppState> for AuthUser {
             .strip_prefix("Bearer ")
             .ok_or(StatusCode::UNAUTHORIZED)?;
 
-        let claims = auth::verify_token(token, &state.config.jwt_secret)
-            .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        let claims =
+            auth::verify_token(token, &state.config.jwt).map_err(|_| StatusCode::UNAUTHORIZED)?;
 
         Ok(AuthUser(claims))
     }
 }
+
+/// Extractor that requires an authenticated user with the admin role.
+///
+/// Rejects missing or invalid tokens with 401 and non-admins with 403.
+pub struct AdminUser(pub Claims);
+
+impl FromRequestParts<AppState> for AdminUser {
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthUser(claims) = AuthUser::from_request_parts(parts, state).await?;
+
+        if claims.role != Role::Admin {
+            return Err(StatusCode::FORBIDDEN);
+        }
+
+        Ok(AdminUser(claims))
+    }
+}
+
+/// Header carrying the per-request correlation ID.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Middleware that exposes the request's `x-request-id` (set by `SetRequestIdLayer`)
+/// to the rest of the request, so error responses can include it.
+pub async fn scope_request_id(req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+
+    REQUEST_ID.scope(request_id, next.run(req)).await
+}
+
+/// The request ID of the request currently being handled, if any.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID
+        .try_with(|id| id.clone())
+        .ok()
+        .filter(|id| !id.is_empty())
+}
+
+/// Header reporting the total LLM tokens (prompt + completion) a request consumed.
+pub const LLM_TOKENS_HEADER: &str = "x-llm-tokens";
+
+/// Middleware that totals LLM token usage across the request and reports it
+/// in the `x-llm-tokens` response header.
+pub async fn track_llm_usage(req: Request, next: Next) -> Response {
+    let (mut response, usage) = llm::track_usage(next.run(req)).await;
+
+    if usage.total_tokens() > 0 {
+        tracing::debug!(
+            prompt_tokens = usage.prompt_tokens,
+            completion_tokens = usage.completion_tokens,
+            llm_duration_ms = usage.total_duration / 1_000_000,
+            "LLM usage for request"
+        );
+        response
+            .headers_mut()
+            .insert(LLM_TOKENS_HEADER, HeaderValue::from(usage.total_tokens()));
+    }
+
+    response
+}
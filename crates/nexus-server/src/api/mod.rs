@@ -1,5 +1,11 @@
+pub mod broadcast;
 pub mod error;
+pub mod guests;
+pub mod idempotency;
+pub mod jobs;
 pub mod middleware;
+pub mod outbox;
+pub mod rate_limit;
 pub mod routes;
 pub mod state;
 pub mod websocket;
@@ -0,0 +1,147 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::river::{beliefs, episodic};
+use nexus_common::types::Belief;
+
+/// Entries that fail this many times are left in the table for inspection.
+const MAX_ATTEMPTS: i32 = 10;
+
+/// Entries applied per poll.
+const BATCH_SIZE: i64 = 50;
+
+/// How long a claimed entry is reserved for the instance applying it. One
+/// whose instance died is picked up again after this.
+const CLAIM_LEASE_SECS: f64 = 300.0;
+
+/// A write to Neo4j or Qdrant that must eventually be applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OutboxOp {
+    /// Embed a message and store it as an episodic memory.
+    StoreMemory {
+        user_id: Uuid,
+        session_id: Uuid,
+        message_id: Uuid,
+        content: String,
+        role: String,
+    },
+    /// Store a belief's embedding for similarity search.
+    IndexBelief { belief: Belief },
+    /// Record a CONTRADICTS relationship between two beliefs.
+    LinkContradiction {
+        belief_a: Uuid,
+        belief_b: Uuid,
+        explanation: String,
+        severity: f64,
+    },
+}
+
+/// Record `op` for the processor. Pass a transaction to make it atomic with
+/// the writes it belongs to.
+pub async fn enqueue<'e>(executor: impl sqlx::PgExecutor<'e>, op: &OutboxOp) -> Result<()> {
+    sqlx::query("INSERT INTO outbox (id, op) VALUES ($1, $2)")
+        .bind(Uuid::new_v4())
+        .bind(serde_json::to_value(op)?)
+        .execute(executor)
+        .await
+        .context("Failed to write outbox entry")?;
+    Ok(())
+}
+
+/// Apply pending outbox entries every `interval`, forever.
+pub async fn run_processor(state: AppState, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        match process_batch(&state).await {
+            Ok(0) => {}
+            Ok(applied) => tracing::debug!(applied, "Applied outbox entries"),
+            Err(e) => tracing::warn!("Outbox processing failed: {e:#}"),
+        }
+    }
+}
+
+/// Apply one batch of pending entries, returning how many succeeded.
+///
+/// Entries are claimed for [`CLAIM_LEASE_SECS`] in one statement, so no
+/// transaction or row lock is held while the writes go to Neo4j, Qdrant and
+/// the embedder, and several instances can share the table. Applied entries
+/// are deleted.
+async fn process_batch(state: &AppState) -> Result<usize> {
+    let rows: Vec<(Uuid, serde_json::Value, i32)> = sqlx::query_as(
+        "UPDATE outbox SET claimed_until = NOW() + make_interval(secs => $3)
+         WHERE id IN (
+             SELECT id FROM outbox
+             WHERE processed_at IS NULL AND attempts < $1
+               AND (claimed_until IS NULL OR claimed_until < NOW())
+             ORDER BY created_at
+             LIMIT $2
+             FOR UPDATE SKIP LOCKED
+         )
+         RETURNING id, op, attempts",
+    )
+    .bind(MAX_ATTEMPTS)
+    .bind(BATCH_SIZE)
+    .bind(CLAIM_LEASE_SECS)
+    .fetch_all(&state.db.pg)
+    .await
+    .context("Failed to claim outbox entries")?;
+
+    let mut applied = 0;
+    for (id, op, attempts) in rows {
+        let outcome = match serde_json::from_value::<OutboxOp>(op) {
+            Ok(op) => apply(state, &op).await,
+            Err(e) => Err(anyhow::Error::new(e).context("Unreadable outbox entry")),
+        };
+
+        match outcome {
+            Ok(()) => {
+                sqlx::query("DELETE FROM outbox WHERE id = $1")
+                    .bind(id)
+                    .execute(&state.db.pg)
+                    .await?;
+                applied += 1;
+            }
+            Err(e) => {
+                if attempts + 1 >= MAX_ATTEMPTS {
+                    tracing::error!(%id, "Giving up on outbox entry: {e:#}");
+                } else {
+                    tracing::warn!(%id, attempt = attempts + 1, "Outbox entry failed: {e:#}");
+                }
+                sqlx::query(
+                    "UPDATE outbox SET attempts = attempts + 1, last_error = $2, claimed_until = NULL
+                     WHERE id = $1",
+                )
+                .bind(id)
+                .bind(format!("{e:#}"))
+                .execute(&state.db.pg)
+                .await?;
+            }
+        }
+    }
+
+    Ok(applied)
+}
+
+async fn apply(state: &AppState, op: &OutboxOp) -> Result<()> {
+    match op {
+        OutboxOp::StoreMemory {
+            user_id,
+            session_id,
+            message_id,
+            content,
+            role,
+        } => episodic::store_memory(state, *user_id, *session_id, *message_id, content, role).await,
+        OutboxOp::IndexBelief { belief } => beliefs::index_belief(state, belief).await,
+        OutboxOp::LinkContradiction {
+            belief_a,
+            belief_b,
+            explanation,
+            severity,
+        } => beliefs::link_contradiction(state, *belief_a, *belief_b, explanation, *severity).await,
+    }
+}
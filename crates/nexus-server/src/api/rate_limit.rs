@@ -0,0 +1,203 @@
+use axum::{
+    Json,
+    body::Body,
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+
+use crate::api::middleware::{AuthUser, current_request_id};
+use crate::api::state::AppState;
+use crate::db::redis::record_backend_error;
+use crate::models::responses::ErrorResponse;
+use nexus_common::types::ChatMode;
+
+/// Per-user quotas on LLM-backed requests. A limit of 0 disables that quota.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimitConfig {
+    /// Conversation-mode turns and single-call endpoints, per minute.
+    pub conversation_per_minute: u32,
+    /// Analysis and Integrated requests, which fan out to 4+ LLM calls, per minute.
+    pub expensive_per_minute: u32,
+    /// The same quotas for guest accounts.
+    pub guest_conversation_per_minute: u32,
+    pub guest_expensive_per_minute: u32,
+}
+
+/// Which quota a request draws from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+    Conversation,
+    Expensive,
+}
+
+impl Tier {
+    pub fn for_mode(mode: ChatMode) -> Self {
+        match mode {
+            ChatMode::Conversation => Tier::Conversation,
+            ChatMode::Analysis | ChatMode::Integrated => Tier::Expensive,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Tier::Conversation => "conversation",
+            Tier::Expensive => "expensive",
+        }
+    }
+
+    fn per_minute(self, config: &RateLimitConfig, guest: bool) -> u32 {
+        match (self, guest) {
+            (Tier::Conversation, false) => config.conversation_per_minute,
+            (Tier::Expensive, false) => config.expensive_per_minute,
+            (Tier::Conversation, true) => config.guest_conversation_per_minute,
+            (Tier::Expensive, true) => config.guest_expensive_per_minute,
+        }
+    }
+}
+
+/// Token bucket refilled continuously at `rate` tokens per millisecond up to
+/// `capacity`. Returns `{allowed, retry_after_ms}`.
+const TOKEN_BUCKET_SCRIPT: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'ts')
+local tokens = tonumber(bucket[1]) or capacity
+local ts = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - ts) * rate)
+local allowed = 0
+local retry_after = 0
+if tokens >= 1 then
+  tokens = tokens - 1
+  allowed = 1
+else
+  retry_after = math.ceil((1 - tokens) / rate)
+end
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'ts', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity / rate))
+return {allowed, retry_after}
+"#;
+
+/// Take one request from the user's quota for `tier`, the guest quota if
+/// `guest` is set.
+///
+/// Returns how long to wait when the quota is exhausted. Fails open if Redis
+/// is unreachable, so an outage there does not take chat down with it.
+pub async fn acquire(state: &AppState, user_id: Uuid, guest: bool, tier: Tier) -> Option<Duration> {
+    let per_minute = tier.per_minute(&state.config.rate_limit, guest);
+    let key = format!("ratelimit:{}:{user_id}", tier.as_str());
+    let retry_after = take(state, &key, per_minute, Duration::from_secs(60)).await;
+    if retry_after.is_some() {
+        tracing::info!(%user_id, tier = tier.as_str(), "Rate limit exceeded");
+    }
+    retry_after
+}
+
+/// Take one guest account from the hourly quota of the client at `ip`, since
+/// guest signup needs no credentials. Fails open like [`acquire`].
+pub async fn acquire_guest_signup(state: &AppState, ip: IpAddr) -> Option<Duration> {
+    let per_hour = state.config.guest.signups_per_hour;
+    let key = format!("ratelimit:guest_signup:{ip}");
+    let retry_after = take(state, &key, per_hour, Duration::from_secs(3600)).await;
+    if retry_after.is_some() {
+        tracing::info!(%ip, "Guest signup rate limit exceeded");
+    }
+    retry_after
+}
+
+/// Take a token from the bucket at `key`, which holds `capacity` tokens and
+/// refills fully over `window`. A capacity of 0 disables the limit.
+async fn take(state: &AppState, key: &str, capacity: u32, window: Duration) -> Option<Duration> {
+    if capacity == 0 {
+        return None;
+    }
+
+    let now_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let rate = f64::from(capacity) / window.as_millis() as f64;
+
+    let mut conn = state.db.redis.clone();
+    let result: Result<(i64, i64), _> = redis::Script::new(TOKEN_BUCKET_SCRIPT)
+        .key(key)
+        .arg(capacity)
+        .arg(rate)
+        .arg(now_ms)
+        .invoke_async(&mut conn)
+        .await;
+
+    match result {
+        Ok((1, _)) => None,
+        Ok((_, retry_after_ms)) => Some(Duration::from_millis(retry_after_ms.max(0) as u64)),
+        Err(e) => {
+            record_backend_error("rate_limit", &e);
+            None
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ModeProbe {
+    mode: Option<ChatMode>,
+}
+
+/// Middleware enforcing per-user LLM quotas on the routes it wraps.
+///
+/// `/api/v1/chat` is charged by the requested mode and belief extraction, a
+/// single LLM call, by the conversation quota; every other wrapped route draws
+/// from the expensive quota. Unauthenticated requests pass through for the
+/// handler to reject.
+pub async fn limit_llm_requests(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let (mut parts, body) = req.into_parts();
+
+    let Ok(AuthUser(claims)) = AuthUser::from_request_parts(&mut parts, &state).await else {
+        return next.run(Request::from_parts(parts, body)).await;
+    };
+
+    let (tier, body) = match parts.uri.path() {
+        "/api/v1/chat" => {
+            let bytes = match axum::body::to_bytes(body, state.config.max_body_bytes).await {
+                Ok(bytes) => bytes,
+                Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+            };
+            // Malformed bodies are rejected by the handler; charge them as the default mode.
+            let mode = serde_json::from_slice::<ModeProbe>(&bytes)
+                .ok()
+                .and_then(|probe| probe.mode)
+                .unwrap_or(state.config.default_chat_mode);
+            (Tier::for_mode(mode), Body::from(bytes))
+        }
+        "/api/v1/beliefs/extract" => (Tier::Conversation, body),
+        _ => (Tier::Expensive, body),
+    };
+
+    if let Some(retry_after) = acquire(&state, claims.sub, claims.guest, tier).await {
+        return too_many_requests(retry_after);
+    }
+
+    next.run(Request::from_parts(parts, body)).await
+}
+
+pub(crate) fn too_many_requests(retry_after: Duration) -> Response {
+    let secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+    let body = Json(ErrorResponse {
+        error: format!("Rate limit exceeded; retry in {secs}s"),
+        details: current_request_id(),
+    });
+    let mut response = (StatusCode::TOO_MANY_REQUESTS, body).into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+    response
+}
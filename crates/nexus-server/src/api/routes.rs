@@ -1,102 +1,359 @@
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    routing::{get, post},
+    extract::{
+        ConnectInfo, Multipart, Path, Query, State, multipart::MultipartRejection,
+        rejection::JsonRejection,
+    },
+    http::{HeaderMap, HeaderValue, Method, StatusCode, header},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, KeepAlive, Sse},
+    },
+    routing::{get, patch, post},
 };
-use tower_http::cors::{Any, CorsLayer};
+use futures::{Stream, StreamExt};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tower_http::limit::RequestBodyLimitLayer;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 use uuid::Uuid;
 
+use crate::api::broadcast::SessionMessage;
 use crate::api::error::AppError;
-use crate::api::middleware::AuthUser;
+use crate::api::idempotency;
+use crate::api::jobs;
+use crate::api::middleware::{self, AdminUser, AuthUser, REQUEST_ID_HEADER};
+use crate::api::outbox::{self, OutboxOp};
+use crate::api::rate_limit;
 use crate::api::state::AppState;
 use crate::api::websocket::ws_handler;
+use crate::config::CorsConfig;
 use crate::models::auth as jwt;
+use crate::models::password::{self, PasswordCheck};
 use crate::models::requests::*;
 use crate::models::responses::*;
+use crate::river::dialogue::QuestionStyle;
+use crate::shared::chat_engine;
+use crate::shared::llm::{self, TokenUsage};
+use crate::shared::metrics;
+use crate::shared::redaction::Redactor;
+use crate::shared::text_util;
+use nexus_common::types::{BeliefSnapshot, ChatMode, Message, MessageMetadata, MessageRole};
 
 pub fn create_router(state: AppState) -> Router {
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = build_cors(&state.config.cors);
+
+    let body_limit = RequestBodyLimitLayer::new(state.config.max_body_bytes);
 
     Router::new()
         // Public routes.
         .route("/health", get(health_handler))
+        .route("/health/live", get(liveness_handler))
+        .route("/health/ready", get(readiness_handler))
+        .route("/metrics", get(metrics_handler))
         .route("/api/v1/auth/register", post(register_handler))
         .route("/api/v1/auth/login", post(login_handler))
+        .route("/api/v1/auth/guest", post(guest_handler))
         // Protected routes (AuthUser extractor validates JWT).
-        .route("/api/v1/chat", post(chat_handler))
-        .route("/api/v1/analyze", post(analyze_handler))
+        .merge(llm_routes(&state))
+        .route("/api/v1/me", get(me_handler).patch(update_me_handler))
+        .route("/api/v1/jobs/{job_id}", get(job_handler))
+        .route(
+            "/api/v1/sessions/{session_id}/messages",
+            get(session_messages_handler),
+        )
+        .route(
+            "/api/v1/sessions/{session_id}/messages/{message_id}",
+            patch(edit_message_handler),
+        )
+        .route("/api/v1/analyses/search", get(analysis_search_handler))
+        .route("/api/v1/analyses/stats", get(analysis_stats_handler))
+        .route("/api/v1/analyses/{id}/export", get(analysis_export_handler))
+        .route("/api/v1/beliefs/search", post(belief_search_handler))
+        .route("/api/v1/beliefs/merge", post(belief_merge_handler))
+        .route("/api/v1/beliefs/revise", post(belief_revise_handler))
+        .route("/api/v1/beliefs/export", get(belief_export_handler))
+        .route("/api/v1/beliefs/import", post(belief_import_handler))
+        .route(
+            "/api/v1/beliefs/contradictions",
+            get(contradictions_handler),
+        )
+        .route(
+            "/api/v1/beliefs/contradictions/resolve",
+            post(contradiction_resolve_handler),
+        )
         .route("/api/v1/beliefs/{user_id}", get(beliefs_handler))
+        .route("/api/v1/beliefs/{user_id}/graph", get(belief_graph_handler))
+        .route(
+            "/api/v1/beliefs/{user_id}/categories",
+            get(belief_categories_handler),
+        )
+        .route(
+            "/api/v1/beliefs/{user_id}/summary",
+            get(belief_summary_handler),
+        )
+        .route("/api/v1/beliefs/{user_id}/audit", get(belief_audit_handler))
         .route("/api/v1/consciousness/state", get(consciousness_handler))
+        .route("/api/v1/digests", get(digest_handler))
+        .route(
+            "/api/v1/admin/users/bulk",
+            post(admin_bulk_import_users_handler),
+        )
+        .route(
+            "/api/v1/admin/users/{user_id}/consciousness",
+            get(admin_consciousness_handler),
+        )
+        .route(
+            "/api/v1/admin/users/{user_id}/consciousness/recompute",
+            post(admin_recompute_consciousness_handler),
+        )
         // WebSocket.
         .route("/ws/chat/{session_id}", get(ws_handler))
-        .layer(TraceLayer::new_for_http())
+        .layer(axum::middleware::from_fn(middleware::track_llm_usage))
+        .layer(axum::middleware::from_fn(middleware::scope_request_id))
+        .layer(body_limit)
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(
+            TraceLayer::new_for_http().make_span_with(|req: &axum::extract::Request| {
+                let request_id = req
+                    .headers()
+                    .get(REQUEST_ID_HEADER)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default();
+                tracing::info_span!(
+                    "request",
+                    method = %req.method(),
+                    uri = %req.uri(),
+                    request_id = %request_id,
+                )
+            }),
+        )
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         .layer(cors)
         .with_state(state)
 }
 
-// ── Health Check ──
+/// Routes that call the LLM directly, behind the per-user rate limiter.
+fn llm_routes(state: &AppState) -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/chat", post(chat_handler))
+        .route(
+            "/api/v1/sessions/{session_id}/regenerate",
+            post(regenerate_handler),
+        )
+        .route("/api/v1/messages/{message_id}/replay", post(replay_handler))
+        .route("/api/v1/analyze", post(analyze_handler))
+        .route("/api/v1/analyze/claims", post(analyze_claims_handler))
+        .route("/api/v1/analyze/diff", post(analyze_diff_handler))
+        .route("/api/v1/analyze/stream", post(analyze_stream_handler))
+        .route("/api/v1/analyze/upload", post(analyze_upload_handler))
+        .route("/api/v1/beliefs/extract", post(belief_extract_handler))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            rate_limit::limit_llm_requests,
+        ))
+}
 
-async fn health_handler(State(state): State<AppState>) -> Json<HealthResponse> {
-    let pg_status = match sqlx::query("SELECT 1").execute(&state.db.pg).await {
-        Ok(_) => ServiceStatus::up(),
-        Err(e) => ServiceStatus::down(e.to_string()),
+/// Build the CORS layer from config. Wildcards are only used without credentials;
+/// with credentials, methods and headers mirror the preflight request instead.
+fn build_cors(config: &CorsConfig) -> CorsLayer {
+    let origins = match &config.allowed_origins {
+        Some(list) => AllowOrigin::list(list.iter().filter_map(|o| o.parse::<HeaderValue>().ok())),
+        None => AllowOrigin::any(),
     };
 
-    let neo4j_status = match state.db.neo4j.run(neo4rs::query("RETURN 1")).await {
-        Ok(_) => ServiceStatus::up(),
-        Err(e) => ServiceStatus::down(e.to_string()),
+    let methods = match &config.allowed_methods {
+        Some(list) => AllowMethods::list(list.iter().filter_map(|m| m.parse::<Method>().ok())),
+        None if config.allow_credentials => AllowMethods::mirror_request(),
+        None => AllowMethods::any(),
     };
 
-    let qdrant_status = match state.db.qdrant.list_collections().await {
-        Ok(_) => ServiceStatus::up(),
-        Err(e) => ServiceStatus::down(e.to_string()),
+    let headers = if config.allow_credentials {
+        AllowHeaders::mirror_request()
+    } else {
+        AllowHeaders::any()
     };
 
-    let influx_status = match state.db.influx.ready().await {
-        Ok(_) => ServiceStatus::up(),
-        Err(e) => ServiceStatus::down(e.to_string()),
-    };
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(config.allow_credentials)
+}
+
+// ── Health Check ──
+
+async fn health_handler(State(state): State<AppState>) -> Json<HealthResponse> {
+    let services = check_dependencies(&state).await;
 
-    let redis_status = {
-        let mut conn = state.db.redis.clone();
-        match ::redis::cmd("PING").query_async::<String>(&mut conn).await {
-            Ok(_) => ServiceStatus::up(),
-            Err(e) => ServiceStatus::down(e.to_string()),
+    Json(HealthResponse {
+        status: if services.all_up() {
+            "healthy"
+        } else {
+            "degraded"
         }
-    };
+        .into(),
+        services,
+    })
+}
+
+/// Liveness probe: the process is up and serving requests. Does no I/O.
+async fn liveness_handler() -> Json<LivenessResponse> {
+    Json(LivenessResponse {
+        status: "alive".into(),
+    })
+}
+
+/// Prometheus scrape endpoint.
+async fn metrics_handler() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::render(),
+    )
+}
+
+/// Readiness probe: 200 only when every dependency is reachable, 503 otherwise.
+/// A `degraded` dependency (an open LLM circuit, a saturated Postgres pool)
+/// counts as reachable.
+async fn readiness_handler(State(state): State<AppState>) -> (StatusCode, Json<HealthResponse>) {
+    let services = check_dependencies(&state).await;
+
+    // A degraded service still serves requests, so only outright failures fail readiness.
+    if !services.any_down() {
+        (
+            StatusCode::OK,
+            Json(HealthResponse {
+                status: "ready".into(),
+                services,
+            }),
+        )
+    } else {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(HealthResponse {
+                status: "not_ready".into(),
+                services,
+            }),
+        )
+    }
+}
+
+/// Check every dependency concurrently, each bounded by the health check timeout.
+async fn check_dependencies(state: &AppState) -> HealthServices {
+    let timeout = Duration::from_secs(state.config.health_check_timeout_secs);
+
+    let (postgres, neo4j, qdrant, influxdb, redis, ollama) = tokio::join!(
+        timed_check(timeout, async {
+            sqlx::query("SELECT 1")
+                .execute(&state.db.pg)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }),
+        timed_check(timeout, async {
+            state
+                .db
+                .neo4j
+                .run(neo4rs::query("RETURN 1"))
+                .await
+                .map_err(|e| e.to_string())
+        }),
+        timed_check(timeout, async {
+            state
+                .db
+                .qdrant
+                .list_collections()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }),
+        timed_check(timeout, async {
+            state
+                .db
+                .influx
+                .ready()
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }),
+        timed_check(timeout, async {
+            let mut conn = state.db.redis.clone();
+            ::redis::cmd("PING")
+                .query_async::<String>(&mut conn)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        }),
+        timed_check(timeout, async {
+            match state.llm.health().await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err("Ollama not healthy".to_string()),
+                Err(e) => Err(e.to_string()),
+            }
+        }),
+    );
+
+    HealthServices {
+        postgres: with_pool_stats(
+            postgres,
+            &state.db.pg,
+            state.config.postgres.max_connections,
+        ),
+        neo4j,
+        qdrant,
+        influxdb,
+        redis,
+        ollama: with_circuit_state(ollama, state.llm.circuit_state()),
+    }
+}
 
-    let ollama_status = match state.ollama.health().await {
-        Ok(true) => ServiceStatus::up(),
-        Ok(false) => ServiceStatus::down("Ollama not healthy".into()),
-        Err(e) => ServiceStatus::down(e.to_string()),
+/// Attach the LLM circuit breaker's state, marking the service `degraded`
+/// while requests are being failed fast.
+fn with_circuit_state(mut status: ServiceStatus, circuit: Option<&str>) -> ServiceStatus {
+    let Some(circuit) = circuit else {
+        return status;
     };
+    if status.status == "up" && circuit != "closed" {
+        status.status = "degraded".into();
+    }
+    status.details = Some(serde_json::json!({ "circuit": circuit }));
+    status
+}
 
-    let all_up = [
-        &pg_status,
-        &neo4j_status,
-        &qdrant_status,
-        &influx_status,
-        &redis_status,
-        &ollama_status,
-    ]
-    .iter()
-    .all(|s| s.status == "up");
+/// Attach Postgres pool statistics, marking the service `degraded` when the
+/// pool is at its maximum size with (nearly) no idle connections left. A
+/// saturated pool still answers (callers wait for a connection), so this does
+/// not fail readiness; taking the instance out of rotation would only push its
+/// load onto the others.
+fn with_pool_stats(mut status: ServiceStatus, pool: &sqlx::PgPool, max: u32) -> ServiceStatus {
+    let size = pool.size();
+    let idle = pool.num_idle();
 
-    Json(HealthResponse {
-        status: if all_up { "healthy" } else { "degraded" }.into(),
-        services: HealthServices {
-            postgres: pg_status,
-            neo4j: neo4j_status,
-            qdrant: qdrant_status,
-            influxdb: influx_status,
-            redis: redis_status,
-            ollama: ollama_status,
-        },
-    })
+    if status.status == "up" && size >= max && idle * 10 <= max as usize {
+        status.status = "degraded".into();
+    }
+    status.details = Some(serde_json::json!({
+        "pool_size": size,
+        "pool_idle": idle,
+        "pool_max": max,
+    }));
+    status
+}
+
+/// Run a single dependency check, reporting it as down if it exceeds `timeout`.
+async fn timed_check(
+    timeout: Duration,
+    check: impl Future<Output = Result<(), String>>,
+) -> ServiceStatus {
+    match tokio::time::timeout(timeout, check).await {
+        Ok(Ok(())) => ServiceStatus::up(),
+        Ok(Err(e)) => ServiceStatus::down(e),
+        Err(_) => ServiceStatus::down("timeout".into()),
+    }
 }
 
 // ── Auth ──
@@ -107,24 +364,31 @@ async fn register_handler(
 ) -> Result<Json<AuthResponse>, AppError> {
     use nexus_common::error::NexusError;
 
-    let password_hash = hash_password(req.password.as_bytes());
+    req.validate()?;
+
+    let email = normalize_email(&req.email);
+    let password_hash = password::hash_password(&req.password).await?;
 
     let user_id = Uuid::new_v4();
     sqlx::query("INSERT INTO users (id, username, email, password_hash) VALUES ($1, $2, $3, $4)")
         .bind(user_id)
         .bind(&req.username)
-        .bind(&req.email)
+        .bind(&email)
         .bind(&password_hash)
         .execute(&state.db.pg)
         .await
-        .map_err(|e| NexusError::Database(format!("Failed to create user: {e}")))?;
+        .map_err(|e| match e.as_database_error() {
+            Some(db) if db.is_unique_violation() => {
+                let field = match db.constraint() {
+                    Some(c) if c.contains("username") => "username",
+                    _ => "email",
+                };
+                NexusError::Conflict(format!("An account with this {field} already exists"))
+            }
+            _ => NexusError::Database(format!("Failed to create user: {e}")),
+        })?;
 
-    let token = jwt::create_token(
-        user_id,
-        &req.username,
-        &state.config.jwt_secret,
-        state.config.jwt_expiry_hours,
-    )?;
+    let token = jwt::create_token(user_id, &req.username, jwt::Role::User, &state.config.jwt)?;
 
     Ok(Json(AuthResponse {
         token,
@@ -139,24 +403,37 @@ async fn login_handler(
 ) -> Result<Json<AuthResponse>, AppError> {
     use nexus_common::error::NexusError;
 
-    let password_hash = hash_password(req.password.as_bytes());
+    req.validate()?;
 
-    let row = sqlx::query_as::<_, (Uuid, String)>(
-        "SELECT id, username FROM users WHERE email = $1 AND password_hash = $2",
+    let row = sqlx::query_as::<_, (Uuid, String, String, String)>(
+        "SELECT id, username, role, password_hash FROM users WHERE lower(email) = $1",
     )
-    .bind(&req.email)
-    .bind(&password_hash)
+    .bind(normalize_email(&req.email))
     .fetch_optional(&state.db.pg)
     .await
     .map_err(|e| NexusError::Database(e.to_string()))?
     .ok_or_else(|| NexusError::Auth("Invalid credentials".into()))?;
 
-    let token = jwt::create_token(
-        row.0,
-        &row.1,
-        &state.config.jwt_secret,
-        state.config.jwt_expiry_hours,
-    )?;
+    match password::verify_password(&req.password, &row.3).await? {
+        PasswordCheck::Invalid => {
+            return Err(NexusError::Auth("Invalid credentials".into()).into());
+        }
+        PasswordCheck::Valid => {}
+        // Accounts from before Argon2 move to it on their next login.
+        PasswordCheck::ValidLegacy => {
+            let rehashed = password::hash_password(&req.password).await?;
+            sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1 AND password_hash = $3")
+                .bind(row.0)
+                .bind(&rehashed)
+                .bind(&row.3)
+                .execute(&state.db.pg)
+                .await
+                .map_err(|e| NexusError::Database(format!("Failed to rehash password: {e}")))?;
+            tracing::info!(user_id = %row.0, "Rehashed legacy password with Argon2");
+        }
+    }
+
+    let token = jwt::create_token(row.0, &row.1, jwt::Role::from_db(&row.2), &state.config.jwt)?;
 
     Ok(Json(AuthResponse {
         token,
@@ -165,12 +442,109 @@ async fn login_handler(
     }))
 }
 
-fn hash_password(data: &[u8]) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-    let mut hasher = DefaultHasher::new();
-    data.hash(&mut hasher);
-    format!("{:016x}", hasher.finish())
+/// Start a guest session: a fresh account with a short-lived token, deleted
+/// with its data after `GUEST_RETENTION_HOURS`. Limited per client IP by
+/// `GUEST_SIGNUPS_PER_HOUR`.
+async fn guest_handler(
+    State(state): State<AppState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+) -> Result<Response, AppError> {
+    use nexus_common::error::NexusError;
+
+    if !state.config.guest.enabled {
+        return Err(NexusError::NotFound("Guest access is not enabled".into()).into());
+    }
+    if let Some(retry_after) = rate_limit::acquire_guest_signup(&state, client.ip()).await {
+        return Ok(rate_limit::too_many_requests(retry_after));
+    }
+
+    let (user_id, username) = crate::api::guests::create_guest(&state).await?;
+    // The token must not outlive the account.
+    let guest = &state.config.guest;
+    let ttl_minutes = guest.token_ttl_minutes.min(guest.retention_hours * 60);
+    let token = jwt::create_guest_token(user_id, &username, ttl_minutes, &state.config.jwt)?;
+
+    Ok(Json(AuthResponse {
+        token,
+        user_id,
+        username,
+    })
+    .into_response())
+}
+
+async fn me_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+) -> Result<Json<UserProfile>, AppError> {
+    use nexus_common::error::NexusError;
+
+    let row: Option<(Uuid, String, String, String, chrono::DateTime<chrono::Utc>)> =
+        sqlx::query_as("SELECT id, username, email, role, created_at FROM users WHERE id = $1")
+            .bind(claims.sub)
+            .fetch_optional(&state.db.pg)
+            .await
+            .map_err(|e| NexusError::Database(format!("Failed to load user: {e}")))?;
+    // The token outlived the account.
+    let row = row.ok_or_else(|| NexusError::NotFound("User not found".into()))?;
+
+    Ok(Json(user_profile(row)))
+}
+
+/// Change the caller's username or email. Tokens already issued keep the old
+/// username until they are renewed.
+async fn update_me_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(req): Json<UpdateProfileRequest>,
+) -> Result<Json<UserProfile>, AppError> {
+    use nexus_common::error::NexusError;
+
+    req.validate()?;
+
+    let row: Option<(Uuid, String, String, String, chrono::DateTime<chrono::Utc>)> =
+        sqlx::query_as(
+            "UPDATE users
+             SET username = COALESCE($2, username), email = COALESCE($3, email),
+                 updated_at = NOW()
+             WHERE id = $1
+             RETURNING id, username, email, role, created_at",
+        )
+        .bind(claims.sub)
+        .bind(req.username.as_deref().map(str::trim))
+        .bind(req.email.as_deref().map(normalize_email))
+        .fetch_optional(&state.db.pg)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            Some(db) if db.is_unique_violation() => {
+                let field = match db.constraint() {
+                    Some(c) if c.contains("username") => "username",
+                    _ => "email",
+                };
+                NexusError::Conflict(format!("An account with this {field} already exists"))
+            }
+            _ => NexusError::Database(format!("Failed to update user: {e}")),
+        })?;
+    let row = row.ok_or_else(|| NexusError::NotFound("User not found".into()))?;
+
+    Ok(Json(user_profile(row)))
+}
+
+fn user_profile(
+    (id, username, email, role, created_at): (
+        Uuid,
+        String,
+        String,
+        String,
+        chrono::DateTime<chrono::Utc>,
+    ),
+) -> UserProfile {
+    UserProfile {
+        id,
+        username,
+        email,
+        role: jwt::Role::from_db(&role),
+        created_at,
+    }
 }
 
 // ── Chat ──
@@ -178,156 +552,1646 @@ fn hash_password(data: &[u8]) -> String {
 async fn chat_handler(
     State(state): State<AppState>,
     AuthUser(claims): AuthUser,
-    Json(req): Json<ChatRequest>,
-) -> Result<Json<ChatResponse>, AppError> {
-    let session_id = req.session_id.unwrap_or_else(Uuid::new_v4);
-    let user_id = claims.sub;
-    let mode_str = match req.mode {
-        nexus_common::types::ChatMode::Conversation => "conversation",
-        nexus_common::types::ChatMode::Analysis => "analysis",
-        nexus_common::types::ChatMode::Integrated => "integrated",
+    Query(query): Query<ChatQuery>,
+    headers: HeaderMap,
+    req: Result<Json<ChatRequest>, JsonRejection>,
+) -> Result<Response, AppError> {
+    use nexus_common::error::NexusError;
+
+    // A bad body, such as an unknown mode, is a validation error. Other
+    // rejections, like a missing content type, keep axum's status.
+    let Json(mut req) = match req {
+        Ok(req) => req,
+        Err(e @ (JsonRejection::JsonDataError(_) | JsonRejection::JsonSyntaxError(_))) => {
+            return Err(NexusError::Validation(e.body_text()).into());
+        }
+        Err(e) => return Ok(e.into_response()),
     };
+    req.validate(state.config.max_input_chars)?;
+    req.explain |= query.explain;
+    req.mode.get_or_insert(state.config.default_chat_mode);
+    moderate(&state, &req.message).await?;
 
-    // Ensure session exists.
-    ensure_session(&state, session_id, user_id, mode_str).await?;
+    if query.run_async {
+        let job_id = jobs::enqueue(&state, claims.sub, req).await?;
+        return Ok((StatusCode::ACCEPTED, Json(JobAcceptedResponse { job_id })).into_response());
+    }
 
-    // Save user message.
-    save_message(&state, session_id, user_id, "user", &req.message, mode_str).await?;
+    let idempotency_key = headers
+        .get(idempotency::IDEMPOTENCY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
 
-    match req.mode {
-        nexus_common::types::ChatMode::Conversation => {
-            let response =
-                crate::river::dialogue::process_message(&state, session_id, user_id, &req.message)
-                    .await?;
+    let Some(key) = idempotency_key else {
+        return Ok(Json(run_chat(&state, claims.sub, &req).await?).into_response());
+    };
 
-            save_message(&state, session_id, user_id, "assistant", &response, mode_str).await?;
+    let fingerprint = idempotency::fingerprint(&req);
+    if let Some(cached) = idempotency::reserve(&state, claims.sub, &key, &fingerprint).await? {
+        return Ok(Json(cached).into_response());
+    }
 
-            Ok(Json(ChatResponse {
-                session_id,
-                message: response,
-                mode: mode_str.into(),
-                analysis: None,
-                contradictions: None,
-                beliefs_updated: None,
-            }))
-        }
-        nexus_common::types::ChatMode::Analysis => {
-            let analysis = crate::perspective::engine::analyze_text(&state, &req.message).await?;
-
-            let summary = "Analysis complete.";
-            save_message(&state, session_id, user_id, "assistant", summary, mode_str).await?;
-
-            Ok(Json(ChatResponse {
-                session_id,
-                message: summary.into(),
-                mode: mode_str.into(),
-                analysis: Some(analysis),
-                contradictions: None,
-                beliefs_updated: None,
-            }))
+    let response = match run_chat(&state, claims.sub, &req).await {
+        Ok(response) => response,
+        Err(e) => {
+            if let Err(e) = idempotency::release(&state, claims.sub, &key).await {
+                tracing::warn!("Failed to release idempotency key: {e}");
+            }
+            return Err(e);
         }
-        nexus_common::types::ChatMode::Integrated => {
-            let (response, analysis) = crate::river::integrated::process_integrated(
-                &state,
-                session_id,
-                user_id,
-                &req.message,
-            )
-            .await?;
+    };
+
+    if let Err(e) = idempotency::store(&state, claims.sub, &key, &fingerprint, &response).await {
+        tracing::warn!("Failed to store idempotent response: {e}");
+    }
+
+    Ok(Json(response).into_response())
+}
+
+/// Reject input flagged by the moderation gate, before anything is stored or
+/// sent to the LLM.
+pub(crate) async fn moderate(state: &AppState, text: &str) -> Result<(), AppError> {
+    use nexus_common::error::NexusError;
+
+    let result = state.moderation.check(text).await;
+    if result.flagged {
+        tracing::info!(categories = ?result.categories, "Input rejected by moderation");
+        return Err(NexusError::Unprocessable(
+            "This message can't be processed because it was flagged by content moderation.".into(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Process a chat turn through the engine selected by the request mode.
+pub(crate) async fn run_chat(
+    state: &AppState,
+    user_id: Uuid,
+    req: &ChatRequest,
+) -> Result<ChatResponse, AppError> {
+    use nexus_common::error::NexusError;
 
-            save_message(&state, session_id, user_id, "assistant", &response, mode_str).await?;
+    let session_id = req.session_id.unwrap_or_else(Uuid::new_v4);
+    let mode = req.mode_or(state.config.default_chat_mode);
+    let mode_str = mode.as_str();
 
-            Ok(Json(ChatResponse {
-                session_id,
-                message: response,
-                mode: mode_str.into(),
-                analysis: Some(analysis),
-                contradictions: None,
-                beliefs_updated: None,
-            }))
+    // Nothing to work with: don't store it or spend LLM calls on it.
+    if !text_util::has_min_words(&req.message, state.config.min_input_words) {
+        if mode == ChatMode::Analysis {
+            return Err(NexusError::Validation(format!(
+                "message must contain at least {} word(s) to analyze",
+                state.config.min_input_words.max(1)
+            ))
+            .into());
         }
+        return Ok(ChatResponse {
+            session_id,
+            message: crate::river::dialogue::CLARIFYING_PROMPT.into(),
+            mode: mode_str.into(),
+            analysis: None,
+            contradictions: None,
+            beliefs_updated: None,
+            consciousness: None,
+            rationale: None,
+            warnings: Vec::new(),
+        });
     }
+
+    // Create the session and save the user message atomically.
+    let mut tx = state
+        .db
+        .pg
+        .begin()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+    ensure_session(&mut *tx, session_id, user_id, mode_str).await?;
+    let user_message_id = insert_message(
+        &mut *tx,
+        &state.redactor,
+        session_id,
+        user_id,
+        "user",
+        &req.message,
+        mode_str,
+        None,
+    )
+    .await?;
+    tx.commit()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to save message: {e}")))?;
+    publish_message(
+        state,
+        user_message_id,
+        session_id,
+        "user",
+        &req.message,
+        mode_str,
+    );
+
+    run_turn(state, session_id, user_id, user_message_id, req).await
 }
 
-async fn ensure_session(
+/// Run a saved user message through the engine for its mode and save the reply.
+async fn run_turn(
     state: &AppState,
     session_id: Uuid,
     user_id: Uuid,
+    user_message_id: Uuid,
+    req: &ChatRequest,
+) -> Result<ChatResponse, AppError> {
+    let mode = req.mode_or(state.config.default_chat_mode);
+    let mode_str = mode.as_str();
+    let opts = crate::river::dialogue::TurnOptions {
+        explain: req.explain,
+        source_message_id: Some(user_message_id),
+        recall_scope: req.recall_scope,
+        question_style: req.question_style,
+        ..Default::default()
+    };
+    let started = Instant::now();
+    let (turn, usage) = llm::track_usage(chat_engine::for_mode(mode).process(
+        state,
+        session_id,
+        user_id,
+        &req.message,
+        opts,
+    ))
+    .await;
+    let turn = turn?;
+
+    let metadata = MessageMetadata {
+        question_style: Some(req.question_style.as_str().into()),
+        ..message_metadata(
+            state,
+            usage,
+            started,
+            turn.memories_recalled,
+            turn.contradictions.as_ref().map(Vec::len),
+        )
+    };
+    if turn.remember {
+        save_turn(
+            state,
+            session_id,
+            user_id,
+            (user_message_id, &req.message),
+            &turn.response,
+            mode_str,
+            &metadata,
+        )
+        .await?;
+    } else {
+        save_message_with_metadata(
+            state,
+            session_id,
+            user_id,
+            "assistant",
+            &turn.response,
+            mode_str,
+            Some(&metadata),
+        )
+        .await?;
+    }
+
+    Ok(ChatResponse {
+        session_id,
+        message: turn.response,
+        mode: mode_str.into(),
+        analysis: turn.analysis,
+        contradictions: turn.contradictions,
+        beliefs_updated: turn.beliefs,
+        consciousness: turn.consciousness,
+        rationale: turn.rationale,
+        warnings: turn.warnings,
+    })
+}
+
+async fn ensure_session<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    session_id: Uuid,
+    user_id: Uuid,
     mode: &str,
 ) -> Result<(), AppError> {
     use nexus_common::error::NexusError;
-    sqlx::query(
-        "INSERT INTO sessions (id, user_id, mode) VALUES ($1, $2, $3) ON CONFLICT (id) DO NOTHING",
+    // Remember the latest mode so a reconnecting client resumes in it. The
+    // update is skipped, returning no row, if the session is someone else's.
+    let row: Option<(Uuid,)> = sqlx::query_as(
+        "INSERT INTO sessions (id, user_id, mode) VALUES ($1, $2, $3)
+         ON CONFLICT (id) DO UPDATE SET mode = EXCLUDED.mode, updated_at = NOW()
+         WHERE sessions.user_id = EXCLUDED.user_id
+         RETURNING id",
     )
     .bind(session_id)
     .bind(user_id)
     .bind(mode)
-    .execute(&state.db.pg)
+    .fetch_optional(executor)
     .await
     .map_err(|e| NexusError::Database(format!("Failed to ensure session: {e}")))?;
-    Ok(())
+
+    match row {
+        Some(_) => Ok(()),
+        None => Err(NexusError::NotFound(format!("Session {session_id} not found")).into()),
+    }
+}
+
+/// Record `mode` as the session's current mode, creating the session if needed.
+pub(crate) async fn set_session_mode(
+    state: &AppState,
+    session_id: Uuid,
+    user_id: Uuid,
+    mode: ChatMode,
+) -> Result<(), AppError> {
+    ensure_session(&state.db.pg, session_id, user_id, mode.as_str()).await
+}
+
+/// The mode the session was last used in, if it exists.
+pub(crate) async fn session_mode(
+    state: &AppState,
+    session_id: Uuid,
+) -> Result<Option<ChatMode>, AppError> {
+    use nexus_common::error::NexusError;
+    let mode: Option<(String,)> = sqlx::query_as("SELECT mode FROM sessions WHERE id = $1")
+        .bind(session_id)
+        .fetch_optional(&state.db.pg)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to load session: {e}")))?;
+
+    Ok(mode.map(|(mode,)| stored_mode(state, &mode)))
+}
+
+/// A mode read back from the database. Only valid names are ever written, so
+/// anything else is logged and treated as the configured `DEFAULT_CHAT_MODE`.
+fn stored_mode(state: &AppState, mode: &str) -> ChatMode {
+    mode.parse().unwrap_or_else(|e| {
+        tracing::warn!("Stored {e}; using the default");
+        state.config.default_chat_mode
+    })
+}
+
+/// The question style recorded in a reply's metadata.
+fn stored_question_style(metadata: Option<&serde_json::Value>) -> QuestionStyle {
+    QuestionStyle::from_stored(
+        metadata
+            .and_then(|m| m.get("question_style"))
+            .and_then(|s| s.as_str()),
+    )
+}
+
+/// Fail with `NotFound` if the session exists and belongs to another user.
+pub(crate) async fn check_session_owner(
+    state: &AppState,
+    session_id: Uuid,
+    user_id: Uuid,
+) -> Result<(), AppError> {
+    use nexus_common::error::NexusError;
+    let owner: Option<(Uuid,)> = sqlx::query_as("SELECT user_id FROM sessions WHERE id = $1")
+        .bind(session_id)
+        .fetch_optional(&state.db.pg)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to load session: {e}")))?;
+
+    match owner {
+        Some((owner,)) if owner != user_id => {
+            Err(NexusError::NotFound(format!("Session {session_id} not found")).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Diagnostics stored with an assistant message.
+fn message_metadata(
+    state: &AppState,
+    usage: TokenUsage,
+    started: Instant,
+    memories_recalled: Option<usize>,
+    contradictions: Option<usize>,
+) -> MessageMetadata {
+    MessageMetadata {
+        model: state.llm.model().to_string(),
+        prompt_tokens: usage.prompt_tokens,
+        completion_tokens: usage.completion_tokens,
+        latency_ms: started.elapsed().as_millis() as u64,
+        memories_recalled,
+        contradictions,
+        regenerates: None,
+        question_style: None,
+    }
 }
 
-async fn save_message(
+async fn save_message_with_metadata(
     state: &AppState,
     session_id: Uuid,
     user_id: Uuid,
     role: &str,
     content: &str,
     mode: &str,
+    metadata: Option<&MessageMetadata>,
+) -> Result<Uuid, AppError> {
+    let id = insert_message(
+        &state.db.pg,
+        &state.redactor,
+        session_id,
+        user_id,
+        role,
+        content,
+        mode,
+        metadata,
+    )
+    .await?;
+    publish_message(state, id, session_id, role, content, mode);
+    Ok(id)
+}
+
+/// Push a saved message to every socket open on the session, redacted as it
+/// was stored.
+fn publish_message(
+    state: &AppState,
+    id: Uuid,
+    session_id: Uuid,
+    role: &str,
+    content: &str,
+    mode: &str,
+) {
+    state.sessions.publish(SessionMessage {
+        id,
+        session_id,
+        role: role.to_string(),
+        content: state.redactor.redact(content).into_owned(),
+        mode: mode.to_string(),
+    });
+}
+
+/// Persist the assistant reply and, in the same transaction, queue both sides
+/// of the turn to be stored as episodic memories by the outbox processor.
+async fn save_turn(
+    state: &AppState,
+    session_id: Uuid,
+    user_id: Uuid,
+    (user_message_id, user_message): (Uuid, &str),
+    response: &str,
+    mode: &str,
+    metadata: &MessageMetadata,
 ) -> Result<(), AppError> {
     use nexus_common::error::NexusError;
+
+    let mut tx = state
+        .db
+        .pg
+        .begin()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let response_id = insert_message(
+        &mut *tx,
+        &state.redactor,
+        session_id,
+        user_id,
+        "assistant",
+        response,
+        mode,
+        Some(metadata),
+    )
+    .await?;
+
+    for (message_id, content, role) in [
+        (user_message_id, user_message, "user"),
+        (response_id, response, "assistant"),
+    ] {
+        // Outbox rows are persisted, so they carry the redacted text too.
+        let op = OutboxOp::StoreMemory {
+            user_id,
+            session_id,
+            message_id,
+            content: state.redactor.redact(content).into_owned(),
+            role: role.to_string(),
+        };
+        outbox::enqueue(&mut *tx, &op).await?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to save turn: {e}")))?;
+    publish_message(state, response_id, session_id, "assistant", response, mode);
+    Ok(())
+}
+
+/// Insert a message, storing its content with sensitive spans redacted.
+async fn insert_message<'e>(
+    executor: impl sqlx::PgExecutor<'e>,
+    redactor: &Redactor,
+    session_id: Uuid,
+    user_id: Uuid,
+    role: &str,
+    content: &str,
+    mode: &str,
+    metadata: Option<&MessageMetadata>,
+) -> Result<Uuid, AppError> {
+    use nexus_common::error::NexusError;
+    let id = Uuid::new_v4();
+    let metadata = metadata.map(serde_json::to_value).transpose()?;
     sqlx::query(
-        "INSERT INTO messages (id, session_id, user_id, role, content, mode) VALUES ($1, $2, $3, $4, $5, $6)",
+        "INSERT INTO messages (id, session_id, user_id, role, content, mode, metadata) VALUES ($1, $2, $3, $4, $5, $6, $7)",
     )
-    .bind(Uuid::new_v4())
+    .bind(id)
     .bind(session_id)
     .bind(user_id)
     .bind(role)
-    .bind(content)
+    .bind(redactor.redact(content))
     .bind(mode)
-    .execute(&state.db.pg)
+    .bind(metadata)
+    .execute(executor)
     .await
     .map_err(|e| NexusError::Database(format!("Failed to save message: {e}")))?;
-    Ok(())
+    Ok(id)
 }
 
-// ── Analyze ──
+// ── Sessions ──
 
-async fn analyze_handler(
+/// `id, role, content, mode, metadata, created_at` from `messages`.
+type MessageRow = (
+    Uuid,
+    String,
+    String,
+    String,
+    Option<serde_json::Value>,
+    chrono::DateTime<chrono::Utc>,
+);
+
+/// A session's messages in order, including any stored per-message metadata.
+async fn session_messages_handler(
     State(state): State<AppState>,
-    AuthUser(_claims): AuthUser,
-    Json(req): Json<AnalyzeRequest>,
-) -> Result<Json<AnalyzeResponse>, AppError> {
-    let analysis = crate::perspective::engine::analyze_text(&state, &req.text).await?;
-    Ok(Json(AnalyzeResponse { analysis }))
-}
+    AuthUser(claims): AuthUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<SessionMessagesResponse>, AppError> {
+    use nexus_common::error::NexusError;
 
-// ── Beliefs ──
+    let rows: Vec<MessageRow> = sqlx::query_as(
+        "SELECT id, role, content, mode, metadata, created_at FROM messages
+         WHERE session_id = $1 AND user_id = $2
+         ORDER BY created_at",
+    )
+    .bind(session_id)
+    .bind(claims.sub)
+    .fetch_all(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to load messages: {e}")))?;
 
-async fn beliefs_handler(
-    State(state): State<AppState>,
-    AuthUser(_claims): AuthUser,
-    Path(user_id): Path<Uuid>,
-) -> Result<Json<BeliefsResponse>, AppError> {
-    let beliefs = crate::river::beliefs::get_user_beliefs(&state, user_id).await?;
-    let total = beliefs.len();
-    Ok(Json(BeliefsResponse {
-        user_id,
-        beliefs,
-        total,
+    if rows.is_empty() {
+        return Err(NexusError::NotFound(format!("Session {session_id} not found")).into());
+    }
+
+    let messages = rows
+        .into_iter()
+        .map(|(id, role, content, mode, metadata, created_at)| Message {
+            id,
+            session_id,
+            user_id: claims.sub,
+            role: match role.as_str() {
+                "assistant" => MessageRole::Assistant,
+                "system" => MessageRole::System,
+                _ => MessageRole::User,
+            },
+            content,
+            mode: stored_mode(&state, &mode),
+            metadata,
+            created_at,
+        })
+        .collect();
+
+    Ok(Json(SessionMessagesResponse {
+        session_id,
+        messages,
     }))
 }
 
-// ── Consciousness ──
-
-async fn consciousness_handler(
+/// Correct a message the user sent. With `?reprocess=true` the session's latest
+/// message is also run through its engine again: the old reply is replaced,
+/// and the memories and beliefs derived from the original text are removed
+/// first so they aren't counted twice. Only reprocessing is rate limited.
+async fn edit_message_handler(
     State(state): State<AppState>,
     AuthUser(claims): AuthUser,
-) -> Result<Json<ConsciousnessResponse>, AppError> {
-    let consciousness_state =
-        crate::river::consciousness::get_current_state(&state, claims.sub).await?;
-    Ok(Json(ConsciousnessResponse {
-        state: consciousness_state,
-    }))
+    Path((session_id, message_id)): Path<(Uuid, Uuid)>,
+    Query(query): Query<EditMessageQuery>,
+    Json(req): Json<EditMessageRequest>,
+) -> Result<Response, AppError> {
+    use nexus_common::error::NexusError;
+
+    let user_id = claims.sub;
+    req.validate(state.config.max_input_chars)?;
+    moderate(&state, &req.content).await?;
+
+    let mut tx = state
+        .db
+        .pg
+        .begin()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let row: Option<(
+        String,
+        Option<serde_json::Value>,
+        chrono::DateTime<chrono::Utc>,
+    )> = sqlx::query_as(
+        "UPDATE messages SET content = $1
+             WHERE id = $2 AND session_id = $3 AND user_id = $4 AND role = 'user'
+             RETURNING mode, metadata, created_at",
+    )
+    .bind(state.redactor.redact(&req.content).as_ref())
+    .bind(message_id)
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to edit message: {e}")))?;
+    let Some((mode_str, metadata, created_at)) = row else {
+        return Err(NexusError::NotFound(format!("Message {message_id} not found")).into());
+    };
+    let mode = stored_mode(&state, &mode_str);
+
+    // Replies to the original text, superseded by reprocessing, and the
+    // question style they were asked in.
+    let mut superseded = Vec::new();
+    let mut question_style = QuestionStyle::default();
+    if query.reprocess {
+        let later: Option<(Uuid,)> = sqlx::query_as(
+            "SELECT id FROM messages
+             WHERE session_id = $1 AND role = 'user' AND created_at > $2
+             LIMIT 1",
+        )
+        .bind(session_id)
+        .bind(created_at)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to edit message: {e}")))?;
+        if later.is_some() {
+            return Err(NexusError::Conflict(
+                "Only the session's latest message can be reprocessed".into(),
+            )
+            .into());
+        }
+
+        // Charged before anything is deleted, so a limited request changes nothing.
+        let tier = rate_limit::Tier::for_mode(mode);
+        if let Some(retry_after) = rate_limit::acquire(&state, user_id, claims.guest, tier).await {
+            return Ok(rate_limit::too_many_requests(retry_after));
+        }
+
+        let replies: Vec<(Uuid, Option<serde_json::Value>)> = sqlx::query_as(
+            "DELETE FROM messages
+             WHERE session_id = $1 AND role = 'assistant' AND created_at > $2
+             RETURNING id, metadata",
+        )
+        .bind(session_id)
+        .bind(created_at)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to remove superseded reply: {e}")))?;
+        if let Some((_, metadata)) = replies.first() {
+            question_style = stored_question_style(metadata.as_ref());
+        }
+        superseded.extend(replies.into_iter().map(|(id, _)| id));
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to edit message: {e}")))?;
+
+    let message = Message {
+        id: message_id,
+        session_id,
+        user_id,
+        role: MessageRole::User,
+        content: req.content,
+        mode,
+        metadata,
+        created_at,
+    };
+    if !query.reprocess {
+        let response = EditMessageResponse {
+            message,
+            reply: None,
+        };
+        return Ok(Json(response).into_response());
+    }
+
+    let mut warnings = Vec::new();
+    superseded.push(message_id);
+    if let Err(e) = crate::river::episodic::delete_memories(&state, &superseded).await {
+        tracing::warn!(%message_id, "Failed to delete superseded memories: {e:#}");
+        warnings.push("Memories of the original message could not be removed".to_string());
+    }
+    if let Err(e) = crate::river::beliefs::delete_message_beliefs(&state, user_id, message_id).await
+    {
+        tracing::warn!(%message_id, "Failed to delete superseded beliefs: {e:#}");
+        warnings.push("Beliefs from the original message could not be removed".to_string());
+    }
+
+    let chat = ChatRequest {
+        message: message.content.clone(),
+        mode: Some(mode),
+        session_id: Some(session_id),
+        explain: false,
+        recall_scope: Default::default(),
+        question_style,
+    };
+    let mut reply = run_turn(&state, session_id, user_id, message_id, &chat).await?;
+    reply.warnings.extend(warnings);
+
+    let response = EditMessageResponse {
+        message,
+        reply: Some(reply),
+    };
+    Ok(Json(response).into_response())
+}
+
+/// Produce a different reply to the session's last user message and append it
+/// as a new assistant message. The original turn's beliefs, memories and
+/// metrics are not recorded again.
+async fn regenerate_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(session_id): Path<Uuid>,
+) -> Result<Json<ChatResponse>, AppError> {
+    use crate::river::dialogue::TurnOptions;
+    use nexus_common::error::NexusError;
+
+    let user_id = claims.sub;
+    let last_user: Option<(String, String)> = sqlx::query_as(
+        "SELECT content, mode FROM messages
+         WHERE session_id = $1 AND user_id = $2 AND role = 'user'
+         ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to load last message: {e}")))?;
+    let Some((message, mode_str)) = last_user else {
+        return Err(NexusError::NotFound(format!("Session {session_id} not found")).into());
+    };
+
+    let previous: Option<(Uuid, String, Option<serde_json::Value>)> = sqlx::query_as(
+        "SELECT id, content, metadata FROM messages
+         WHERE session_id = $1 AND user_id = $2 AND role = 'assistant'
+         ORDER BY created_at DESC LIMIT 1",
+    )
+    .bind(session_id)
+    .bind(user_id)
+    .fetch_optional(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to load last reply: {e}")))?;
+    let (previous_id, previous_response, question_style) = match previous {
+        Some((id, content, metadata)) => (
+            Some(id),
+            Some(content),
+            stored_question_style(metadata.as_ref()),
+        ),
+        None => (None, None, QuestionStyle::default()),
+    };
+
+    // Asked in the original reply's style, so only the angle changes.
+    let opts = TurnOptions {
+        previous_response: Some(previous_response.as_deref().unwrap_or_default()),
+        question_style,
+        ..Default::default()
+    };
+
+    let engine = chat_engine::for_mode(stored_mode(&state, &mode_str));
+    if !engine.can_regenerate() {
+        return Err(NexusError::Validation(
+            "Analysis replies are deterministic and can't be regenerated".into(),
+        )
+        .into());
+    }
+
+    let started = Instant::now();
+    let (turn, usage) =
+        llm::track_usage(engine.process(&state, session_id, user_id, &message, opts)).await;
+    let turn = turn?;
+    let metadata = message_metadata(
+        &state,
+        usage,
+        started,
+        turn.memories_recalled,
+        turn.contradictions.as_ref().map(Vec::len),
+    );
+    let response = ChatResponse {
+        session_id,
+        message: turn.response,
+        mode: mode_str.clone(),
+        analysis: turn.analysis,
+        contradictions: turn.contradictions,
+        beliefs_updated: None,
+        consciousness: None,
+        rationale: turn.rationale,
+        warnings: turn.warnings,
+    };
+
+    let metadata = MessageMetadata {
+        regenerates: previous_id,
+        question_style: Some(question_style.as_str().into()),
+        ..metadata
+    };
+    save_message_with_metadata(
+        &state,
+        session_id,
+        user_id,
+        "assistant",
+        &response.message,
+        &mode_str,
+        Some(&metadata),
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// Run one of the caller's stored messages through another mode and return
+/// the result. Nothing is saved: no messages, beliefs, memories or metrics.
+async fn replay_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(message_id): Path<Uuid>,
+    Json(req): Json<ReplayRequest>,
+) -> Result<Json<ChatResponse>, AppError> {
+    use crate::river::dialogue::TurnOptions;
+    use nexus_common::error::NexusError;
+
+    let user_id = claims.sub;
+    let stored: Option<(Uuid, String)> = sqlx::query_as(
+        "SELECT session_id, content FROM messages
+         WHERE id = $1 AND user_id = $2 AND role = 'user'",
+    )
+    .bind(message_id)
+    .bind(user_id)
+    .fetch_optional(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to load message: {e}")))?;
+    let Some((session_id, message)) = stored else {
+        return Err(NexusError::NotFound(format!("Message {message_id} not found")).into());
+    };
+
+    let opts = TurnOptions {
+        source_message_id: Some(message_id),
+        read_only: true,
+        ..Default::default()
+    };
+    let turn = chat_engine::for_mode(req.mode)
+        .process(&state, session_id, user_id, &message, opts)
+        .await?;
+
+    Ok(Json(ChatResponse {
+        session_id,
+        message: turn.response,
+        mode: req.mode.as_str().into(),
+        analysis: turn.analysis,
+        contradictions: turn.contradictions,
+        beliefs_updated: None,
+        consciousness: None,
+        rationale: turn.rationale,
+        warnings: turn.warnings,
+    }))
+}
+
+// ── Jobs ──
+
+async fn job_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<JobResponse>, AppError> {
+    Ok(Json(jobs::get(&state, claims.sub, job_id).await?))
+}
+
+// ── Analyze ──
+
+async fn analyze_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Query(query): Query<AnalyzeQuery>,
+    Json(req): Json<AnalyzeRequest>,
+) -> Result<Json<AnalyzeResponse>, AppError> {
+    req.validate(state.config.max_input_chars)?;
+    query.validate()?;
+    moderate(&state, &req.full_text()).await?;
+
+    let mut analysis = crate::perspective::engine::analyze_text_with(
+        &state,
+        claims.sub,
+        &req.text,
+        &req.context,
+        req.intensity,
+    )
+    .await?;
+    if let Some(min) = query.min_confidence {
+        crate::perspective::merge::retain_confident(&mut analysis, min);
+    }
+    Ok(Json(AnalyzeResponse { analysis }))
+}
+
+/// Analyse claims the caller has already extracted, skipping the layers that
+/// only make sense on whole texts.
+async fn analyze_claims_handler(
+    State(state): State<AppState>,
+    AuthUser(_claims): AuthUser,
+    Json(req): Json<AnalyzeClaimsRequest>,
+) -> Result<Json<AnalyzeClaimsResponse>, AppError> {
+    req.validate(state.config.max_input_chars)?;
+    moderate(&state, &req.claims.join("\n")).await?;
+
+    let results = crate::perspective::engine::analyze_claims(&state, &req.claims).await?;
+    Ok(Json(AnalyzeClaimsResponse { results }))
+}
+
+/// Largest page a listing endpoint returns.
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Content types accepted by `/analyze/upload`, and the extensions accepted
+/// when the client sends a generic type instead.
+const UPLOAD_CONTENT_TYPES: &[&str] = &["text/plain", "text/markdown", "text/x-markdown"];
+const UPLOAD_EXTENSIONS: &[&str] = &["txt", "md", "markdown"];
+
+/// Analyse an uploaded `.txt` or `.md` document, sent as the `file` field of a
+/// `multipart/form-data` body. Long documents are analysed in chunks and the
+/// results merged.
+async fn analyze_upload_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<AnalyzeResponse>, AppError> {
+    use nexus_common::error::NexusError;
+
+    let mut multipart = multipart.map_err(|e| {
+        NexusError::UnsupportedMediaType(format!("Expected multipart/form-data: {e}"))
+    })?;
+
+    let text = loop {
+        let field = multipart
+            .next_field()
+            .await
+            .map_err(|e| NexusError::Validation(format!("Invalid multipart body: {e}")))?
+            .ok_or_else(|| NexusError::Validation("Missing 'file' field".into()))?;
+        if field.name() == Some("file") {
+            break read_upload(field, state.config.max_upload_bytes).await?;
+        }
+    };
+
+    if text.trim().is_empty() {
+        return Err(NexusError::Validation("Uploaded file is empty".into()).into());
+    }
+    moderate(&state, &text).await?;
+
+    let analysis = crate::perspective::engine::analyze_text(&state, claims.sub, &text).await?;
+    Ok(Json(AnalyzeResponse { analysis }))
+}
+
+/// Check an uploaded file's type and read it as UTF-8, up to `max_bytes`.
+async fn read_upload(
+    mut field: axum::extract::multipart::Field<'_>,
+    max_bytes: usize,
+) -> Result<String, AppError> {
+    use nexus_common::error::NexusError;
+
+    let content_type = field.content_type().map(|ct| {
+        ct.split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+    });
+    let extension = field
+        .file_name()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_lowercase());
+    let supported = match content_type.as_deref() {
+        Some(ct) if UPLOAD_CONTENT_TYPES.contains(&ct) => true,
+        None | Some("application/octet-stream") => extension
+            .as_deref()
+            .is_some_and(|ext| UPLOAD_EXTENSIONS.contains(&ext)),
+        Some(_) => false,
+    };
+    if !supported {
+        return Err(NexusError::UnsupportedMediaType(format!(
+            "Unsupported file type {}; upload a .txt or .md file",
+            content_type.as_deref().unwrap_or("(none)")
+        ))
+        .into());
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field
+        .chunk()
+        .await
+        .map_err(|e| NexusError::Validation(format!("Failed to read upload: {e}")))?
+    {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(
+                NexusError::Validation(format!("file must be at most {max_bytes} bytes")).into(),
+            );
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    String::from_utf8(bytes)
+        .map_err(|_| NexusError::Validation("file must be UTF-8 text".into()).into())
+}
+
+/// Stream each analysis layer as a server-sent event as soon as it completes.
+/// Layer events are named after the layer; the final `result` event carries the
+/// assembled analysis, or an `error` event is sent instead if it failed.
+async fn analyze_stream_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(req): Json<AnalyzeRequest>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    req.validate(state.config.max_input_chars)?;
+    moderate(&state, &req.full_text()).await?;
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let layer_tx = tx.clone();
+        let outcome = crate::perspective::engine::analyze_text_streaming(
+            &state,
+            claims.sub,
+            &req.text,
+            &req.context,
+            req.intensity,
+            |output| {
+                let event = Event::default().event(output.layer()).json_data(output);
+                if let Ok(event) = event {
+                    let _ = layer_tx.send(event);
+                }
+            },
+        )
+        .await;
+
+        let event = match outcome {
+            Ok(analysis) => Event::default()
+                .event("result")
+                .json_data(AnalyzeResponse { analysis }),
+            Err(e) => Event::default()
+                .event("error")
+                .json_data(serde_json::json!({ "error": AppError(e).status_and_message().1 })),
+        };
+        if let Ok(event) = event {
+            let _ = tx.send(event);
+        }
+    });
+
+    let stream = UnboundedReceiverStream::new(rx).map(Ok);
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+async fn analyze_diff_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(req): Json<AnalyzeDiffRequest>,
+) -> Result<Json<AnalyzeDiffResponse>, AppError> {
+    req.validate(state.config.max_input_chars)?;
+    moderate(&state, &req.text_a).await?;
+    moderate(&state, &req.text_b).await?;
+
+    let (a, b) = tokio::try_join!(
+        crate::perspective::engine::analyze_text(&state, claims.sub, &req.text_a),
+        crate::perspective::engine::analyze_text(&state, claims.sub, &req.text_b),
+    )?;
+    let diff = crate::perspective::diff::diff_analyses(&a, &b);
+    Ok(Json(AnalyzeDiffResponse { diff }))
+}
+
+async fn analysis_search_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Query(query): Query<AnalysisSearchQuery>,
+) -> Result<Json<AnalysisSearchResponse>, AppError> {
+    query.validate(state.config.max_input_chars)?;
+
+    let matches = crate::perspective::engine::search_analyses(
+        &state,
+        claims.sub,
+        &query.q,
+        query.limit as i64,
+    )
+    .await?;
+    let total = matches.len();
+    Ok(Json(AnalysisSearchResponse { matches, total }))
+}
+
+async fn analysis_stats_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Query(query): Query<AnalysisStatsQuery>,
+) -> Result<Json<AnalysisStatsResponse>, AppError> {
+    let stats = crate::perspective::engine::analysis_stats(&state, claims.sub, query.since).await?;
+    Ok(Json(AnalysisStatsResponse {
+        user_id: claims.sub,
+        stats,
+    }))
+}
+
+async fn analysis_export_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<AnalysisExportQuery>,
+) -> Result<Response, AppError> {
+    let analysis = crate::perspective::engine::get_analysis(&state, claims.sub, id).await?;
+
+    match params.format {
+        ExportFormat::Json => Ok(Json(AnalyzeResponse { analysis }).into_response()),
+        ExportFormat::Markdown => {
+            let body = crate::perspective::report::to_markdown(&analysis);
+            Ok((
+                [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+                body,
+            )
+                .into_response())
+        }
+    }
+}
+
+// ── Beliefs ──
+
+async fn beliefs_handler(
+    State(state): State<AppState>,
+    AuthUser(_claims): AuthUser,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<BeliefsQuery>,
+) -> Result<Json<BeliefsResponse>, AppError> {
+    use nexus_common::cursor::Cursor;
+
+    let after = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+    let limit = query.limit.map(|limit| limit.clamp(1, MAX_PAGE_SIZE));
+
+    // Fetch one extra to tell whether there is another page.
+    let mut beliefs = crate::river::beliefs::get_user_beliefs_page(
+        &state,
+        user_id,
+        query.category.as_deref(),
+        after.as_ref(),
+        limit.map(|limit| limit + 1),
+    )
+    .await?;
+
+    let next_cursor = match limit {
+        Some(limit) if beliefs.len() > limit => {
+            beliefs.truncate(limit);
+            beliefs
+                .last()
+                .map(|b| Cursor::new(b.created_at, b.id).encode())
+        }
+        _ => None,
+    };
+
+    let total = beliefs.len();
+    Ok(Json(BeliefsResponse {
+        user_id,
+        beliefs,
+        total,
+        next_cursor,
+    }))
+}
+
+/// The user's belief audit log, newest first. Readable by the user and by admins.
+async fn belief_audit_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(user_id): Path<Uuid>,
+    Query(query): Query<BeliefAuditQuery>,
+) -> Result<Json<BeliefAuditResponse>, AppError> {
+    use nexus_common::error::NexusError;
+
+    if claims.sub != user_id && claims.role != jwt::Role::Admin {
+        return Err(NexusError::NotFound(format!("User {user_id} not found")).into());
+    }
+
+    let limit = query.limit.unwrap_or(MAX_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let entries = crate::river::belief_audit::list(&state, user_id, query.before, limit).await?;
+    let next_before = match entries.last() {
+        Some(last) if entries.len() == limit => Some(last.id),
+        _ => None,
+    };
+
+    Ok(Json(BeliefAuditResponse {
+        user_id,
+        entries,
+        next_before,
+    }))
+}
+
+async fn belief_categories_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<BeliefCategoriesResponse>, AppError> {
+    use nexus_common::error::NexusError;
+
+    if claims.sub != user_id && claims.role != jwt::Role::Admin {
+        return Err(NexusError::NotFound(format!("User {user_id} not found")).into());
+    }
+
+    let categories = crate::river::beliefs::get_belief_categories(&state, user_id).await?;
+    Ok(Json(BeliefCategoriesResponse {
+        user_id,
+        categories,
+    }))
+}
+
+/// Fold one of the caller's beliefs into another.
+async fn belief_merge_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(req): Json<BeliefMergeRequest>,
+) -> Result<Json<BeliefMergeResponse>, AppError> {
+    req.validate()?;
+
+    let merged =
+        crate::river::beliefs::merge_beliefs(&state, claims.sub, req.keep_id, req.merge_id).await?;
+    Ok(Json(BeliefMergeResponse {
+        belief: merged.belief,
+        merged_id: req.merge_id,
+        relationships_moved: merged.relationships_moved,
+    }))
+}
+
+/// Raise or lower the confidence of one of the caller's beliefs.
+async fn belief_revise_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(req): Json<BeliefReviseRequest>,
+) -> Result<Json<BeliefReviseResponse>, AppError> {
+    req.validate()?;
+
+    let belief =
+        crate::river::beliefs::revise_belief(&state, claims.sub, req.belief_id, req.delta).await?;
+    Ok(Json(BeliefReviseResponse { belief }))
+}
+
+/// The caller's beliefs and their relationships, as a snapshot for import
+/// into another instance.
+async fn belief_export_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+) -> Result<Json<BeliefSnapshot>, AppError> {
+    let snapshot = crate::river::beliefs::export_beliefs(&state, claims.sub).await?;
+    Ok(Json(snapshot))
+}
+
+/// Recreate an exported belief snapshot in the caller's graph, under new ids.
+async fn belief_import_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(snapshot): Json<BeliefSnapshot>,
+) -> Result<Json<BeliefImportResponse>, AppError> {
+    let imported = crate::river::beliefs::import_beliefs(&state, claims.sub, &snapshot).await?;
+    Ok(Json(BeliefImportResponse {
+        user_id: claims.sub,
+        beliefs: imported.beliefs,
+        relationships: imported.relationships,
+    }))
+}
+
+/// The caller's stored contradictions, most recently detected first.
+async fn contradictions_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Query(query): Query<ContradictionsQuery>,
+) -> Result<Json<ContradictionsResponse>, AppError> {
+    let limit = query.limit.unwrap_or(MAX_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let contradictions =
+        crate::river::contradictions::list(&state, claims.sub, query.unresolved, limit).await?;
+    Ok(Json(ContradictionsResponse {
+        user_id: claims.sub,
+        contradictions,
+    }))
+}
+
+/// Mark one of the caller's contradictions resolved.
+async fn contradiction_resolve_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(req): Json<ContradictionResolveRequest>,
+) -> Result<Json<ContradictionResponse>, AppError> {
+    let contradiction =
+        crate::river::contradictions::resolve(&state, claims.sub, req.belief_a_id, req.belief_b_id)
+            .await?;
+    Ok(Json(ContradictionResponse { contradiction }))
+}
+
+/// Preview the claims extraction would pull from a message, without storing them.
+async fn belief_extract_handler(
+    State(state): State<AppState>,
+    AuthUser(_claims): AuthUser,
+    Json(req): Json<BeliefExtractRequest>,
+) -> Result<Json<BeliefExtractResponse>, AppError> {
+    req.validate(state.config.max_input_chars)?;
+
+    let claims = crate::river::beliefs::extract_beliefs(&state, &req.message).await?;
+    Ok(Json(BeliefExtractResponse { claims }))
+}
+
+async fn belief_search_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Json(req): Json<BeliefSearchRequest>,
+) -> Result<Json<BeliefSearchResponse>, AppError> {
+    req.validate(state.config.max_input_chars)?;
+
+    let results =
+        crate::river::beliefs::search_beliefs(&state, claims.sub, &req.query, req.limit as u64)
+            .await?;
+
+    Ok(Json(BeliefSearchResponse {
+        query: req.query,
+        results,
+    }))
+}
+
+async fn belief_summary_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<BeliefSummaryResponse>, AppError> {
+    use nexus_common::error::NexusError;
+
+    if claims.sub != user_id && claims.role != jwt::Role::Admin {
+        return Err(NexusError::NotFound(format!("User {user_id} not found")).into());
+    }
+
+    let summary = crate::river::belief_summary::summarize(&state, user_id).await?;
+    Ok(Json(BeliefSummaryResponse { user_id, summary }))
+}
+
+async fn belief_graph_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Path(user_id): Path<Uuid>,
+    Query(params): Query<GraphExportQuery>,
+) -> Result<Response, AppError> {
+    use nexus_common::error::NexusError;
+
+    if claims.sub != user_id && claims.role != jwt::Role::Admin {
+        return Err(NexusError::NotFound(format!("User {user_id} not found")).into());
+    }
+
+    let graph = crate::river::beliefs::get_belief_graph(&state, user_id).await?;
+
+    match params.format {
+        GraphFormat::Json => Ok(Json(BeliefGraphResponse {
+            user_id,
+            nodes: graph.nodes,
+            edges: graph.edges,
+        })
+        .into_response()),
+        GraphFormat::Graphml => {
+            let body = crate::river::beliefs::to_graphml(&graph);
+            Ok(([(header::CONTENT_TYPE, "application/graphml+xml")], body).into_response())
+        }
+    }
+}
+
+// ── Consciousness ──
+
+async fn consciousness_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+) -> Result<Json<ConsciousnessResponse>, AppError> {
+    let consciousness_state =
+        crate::river::consciousness::get_current_state(&state, claims.sub).await?;
+    Ok(Json(ConsciousnessResponse {
+        state: consciousness_state,
+    }))
+}
+
+// ── Digests ──
+
+async fn digest_handler(
+    State(state): State<AppState>,
+    AuthUser(claims): AuthUser,
+    Query(query): Query<DigestQuery>,
+) -> Result<Json<DigestResponse>, AppError> {
+    use nexus_common::error::NexusError;
+
+    let date = query
+        .date
+        .or_else(|| chrono::Utc::now().date_naive().pred_opt())
+        .ok_or_else(|| NexusError::Validation("date is out of range".into()))?;
+
+    let digest = crate::river::digest::get_digest(&state, claims.sub, date)
+        .await?
+        .ok_or_else(|| NexusError::NotFound(format!("No digest for {date}")))?;
+    Ok(Json(DigestResponse { digest }))
+}
+
+// ── Admin ──
+
+async fn admin_consciousness_handler(
+    State(state): State<AppState>,
+    AdminUser(_claims): AdminUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<ConsciousnessResponse>, AppError> {
+    let consciousness_state =
+        crate::river::consciousness::get_current_state(&state, user_id).await?;
+    Ok(Json(ConsciousnessResponse {
+        state: consciousness_state,
+    }))
+}
+
+/// Admin-only: create many users at once, e.g. to seed a research cohort.
+/// Passwords go through the same hashing as registration, so imported users
+/// log in normally. All rows share one transaction, but each is inserted
+/// under its own savepoint: an invalid or duplicate row is reported and rolled
+/// back alone while the rest are kept.
+async fn admin_bulk_import_users_handler(
+    State(state): State<AppState>,
+    AdminUser(claims): AdminUser,
+    Json(req): Json<BulkUserImportRequest>,
+) -> Result<Json<BulkUserImportResponse>, AppError> {
+    use nexus_common::error::NexusError;
+
+    req.validate()?;
+
+    let mut tx = state
+        .db
+        .pg
+        .begin()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+
+    let mut results = Vec::with_capacity(req.users.len());
+    for (index, user) in req.users.iter().enumerate() {
+        let email = normalize_email(&user.email);
+        let row = |status, user_id, reason| BulkUserImportRow {
+            index,
+            email: email.clone(),
+            status,
+            user_id,
+            reason,
+        };
+
+        if let Err(e) = user.validate() {
+            results.push(row(BulkImportStatus::Failed, None, Some(e.to_string())));
+            continue;
+        }
+
+        let password_hash = password::hash_password(&user.password).await?;
+        let mut savepoint = tx
+            .begin()
+            .await
+            .map_err(|e| NexusError::Database(format!("Failed to create savepoint: {e}")))?;
+        let user_id = Uuid::new_v4();
+        let inserted = sqlx::query(
+            "INSERT INTO users (id, username, email, password_hash) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(user_id)
+        .bind(&user.username)
+        .bind(&email)
+        .bind(&password_hash)
+        .execute(&mut *savepoint)
+        .await;
+
+        match inserted {
+            Ok(_) => {
+                savepoint.commit().await.map_err(|e| {
+                    NexusError::Database(format!("Failed to release savepoint: {e}"))
+                })?;
+                results.push(row(BulkImportStatus::Created, Some(user_id), None));
+            }
+            Err(e) => {
+                savepoint.rollback().await.map_err(|e| {
+                    NexusError::Database(format!("Failed to roll back savepoint: {e}"))
+                })?;
+                let outcome = match e.as_database_error() {
+                    Some(db) if db.is_unique_violation() => {
+                        let field = match db.constraint() {
+                            Some(c) if c.contains("username") => "username",
+                            _ => "email",
+                        };
+                        row(
+                            BulkImportStatus::Skipped,
+                            None,
+                            Some(format!("An account with this {field} already exists")),
+                        )
+                    }
+                    _ => {
+                        tracing::warn!(index, "Bulk user import row failed: {e}");
+                        row(
+                            BulkImportStatus::Failed,
+                            None,
+                            Some("Failed to create user".into()),
+                        )
+                    }
+                };
+                results.push(outcome);
+            }
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to import users: {e}")))?;
+
+    let count = |status| results.iter().filter(|r| r.status == status).count();
+    let response = BulkUserImportResponse {
+        created: count(BulkImportStatus::Created),
+        skipped: count(BulkImportStatus::Skipped),
+        failed: count(BulkImportStatus::Failed),
+        results,
+    };
+    tracing::info!(
+        admin = %claims.sub,
+        created = response.created,
+        skipped = response.skipped,
+        failed = response.failed,
+        "Bulk user import"
+    );
+    Ok(Json(response))
+}
+
+/// Admin-only: rebuild a user's metrics history from stored messages and beliefs.
+async fn admin_recompute_consciousness_handler(
+    State(state): State<AppState>,
+    AdminUser(_claims): AdminUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<ConsciousnessBackfillResponse>, AppError> {
+    let snapshots = crate::river::consciousness::recompute_metrics(&state, user_id).await?;
+    Ok(Json(ConsciousnessBackfillResponse { user_id, snapshots }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::river::episodic::{self, SearchMode};
+    use crate::shared::redaction::RedactionConfig;
+    use crate::test_support;
+
+    #[tokio::test]
+    #[ignore = "needs the services in .env"]
+    async fn redaction_keeps_originals_out_of_storage() {
+        let state = test_support::live_state_with(|config| {
+            config.redaction = RedactionConfig {
+                enabled: true,
+                terms: vec!["Project Falcon".into()],
+                patterns: Vec::new(),
+                detect_pii: true,
+            };
+        })
+        .await;
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let text = "Project Falcon ships Friday, mail jo@example.com";
+
+        sqlx::query(
+            "INSERT INTO users (id, username, email, password_hash) VALUES ($1, $2, $3, '')",
+        )
+        .bind(user_id)
+        .bind(user_id.to_string())
+        .bind(format!("{user_id}@example.invalid"))
+        .execute(&state.db.pg)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO sessions (id, user_id) VALUES ($1, $2)")
+            .bind(session_id)
+            .bind(user_id)
+            .execute(&state.db.pg)
+            .await
+            .unwrap();
+        let message_id = insert_message(
+            &state.db.pg,
+            &state.redactor,
+            session_id,
+            user_id,
+            "user",
+            text,
+            "conversation",
+            None,
+        )
+        .await
+        .unwrap_or_else(|e| panic!("{:#}", e.0));
+        let mut subscription = state.sessions.subscribe(session_id, Uuid::new_v4());
+        publish_message(&state, message_id, session_id, "user", text, "conversation");
+        episodic::store_memory(&state, user_id, session_id, message_id, text, "user")
+            .await
+            .unwrap();
+        let analysis = crate::perspective::engine::empty_analysis(text);
+        crate::perspective::engine::store_analysis(&state, user_id, &analysis)
+            .await
+            .unwrap();
+
+        let stored: Result<String, _> =
+            sqlx::query_scalar("SELECT content FROM messages WHERE id = $1")
+                .bind(message_id)
+                .fetch_one(&state.db.pg)
+                .await;
+        let recalled =
+            episodic::recall_similar(&state, user_id, None, "ships Friday", 5, SearchMode::Vector)
+                .await;
+        let analysed: Result<(String, String), _> =
+            sqlx::query_as("SELECT input_text, result->>'input_text' FROM analyses WHERE id = $1")
+                .bind(analysis.id)
+                .fetch_one(&state.db.pg)
+                .await;
+        episodic::delete_user_memories(&state, &[user_id])
+            .await
+            .unwrap();
+        sqlx::query("DELETE FROM users WHERE id = $1")
+            .bind(user_id)
+            .execute(&state.db.pg)
+            .await
+            .unwrap();
+
+        let redacted = "[REDACTED] ships Friday, mail [EMAIL]";
+        assert_eq!(stored.unwrap(), redacted);
+        let recalled: Vec<String> = recalled.unwrap().into_iter().map(|m| m.content).collect();
+        assert_eq!(recalled, [redacted]);
+        assert_eq!(subscription.recv().await.content, redacted);
+        let (input_text, result_text) = analysed.unwrap();
+        assert_eq!(input_text, redacted);
+        assert_eq!(result_text, redacted);
+    }
+
+    #[test]
+    fn question_style_is_read_back_from_reply_metadata() {
+        let metadata = MessageMetadata {
+            model: "m".into(),
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            latency_ms: 0,
+            memories_recalled: None,
+            contradictions: None,
+            regenerates: None,
+            question_style: Some(QuestionStyle::Layered.as_str().into()),
+        };
+        let stored = serde_json::to_value(&metadata).unwrap();
+
+        assert_eq!(stored_question_style(Some(&stored)), QuestionStyle::Layered);
+        let legacy = serde_json::json!({ "model": "m" });
+        assert_eq!(stored_question_style(Some(&legacy)), QuestionStyle::Single);
+        assert_eq!(stored_question_style(None), QuestionStyle::Single);
+    }
+
+    fn claims_for(user_id: Uuid) -> jwt::Claims {
+        jwt::Claims {
+            sub: user_id,
+            username: user_id.to_string(),
+            role: jwt::Role::User,
+            guest: false,
+            exp: 0,
+            iat: 0,
+            iss: None,
+            aud: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the services in .env"]
+    async fn belief_summary_hides_other_users() {
+        let state = test_support::live_state().await;
+        let caller = claims_for(Uuid::new_v4());
+
+        let err = belief_summary_handler(State(state), AuthUser(caller), Path(Uuid::new_v4()))
+            .await
+            .err()
+            .expect("another user's summary must not be served");
+
+        assert_eq!(err.status_and_message().0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the services in .env"]
+    async fn belief_graph_hides_other_users() {
+        let state = test_support::live_state().await;
+        let caller = claims_for(Uuid::new_v4());
+        let query = GraphExportQuery {
+            format: GraphFormat::Json,
+        };
+
+        let err = belief_graph_handler(
+            State(state),
+            AuthUser(caller),
+            Path(Uuid::new_v4()),
+            Query(query),
+        )
+        .await
+        .err()
+        .expect("another user's belief graph must not be served");
+
+        assert_eq!(err.status_and_message().0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the services in .env"]
+    async fn belief_categories_hide_other_users() {
+        let state = test_support::live_state().await;
+        let caller = claims_for(Uuid::new_v4());
+
+        let err = belief_categories_handler(State(state), AuthUser(caller), Path(Uuid::new_v4()))
+            .await
+            .err()
+            .expect("another user's belief categories must not be served");
+
+        assert_eq!(err.status_and_message().0, StatusCode::NOT_FOUND);
+    }
 }
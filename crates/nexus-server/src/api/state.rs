@@ -1,29 +1,50 @@
 use std::sync::Arc;
 
+use crate::api::broadcast::SessionHub;
+use crate::api::jobs::JobQueue;
 use crate::config::AppConfig;
 use crate::db::DatabaseConnections;
-use crate::shared::embeddings::EmbeddingService;
-use crate::shared::ollama::OllamaClient;
+use crate::perspective::cache::LocalCache;
+use crate::shared::embeddings::{self, Embedder};
+use crate::shared::llm::{self, LlmClient};
+use crate::shared::moderation::Moderator;
+use crate::shared::redaction::Redactor;
 
 /// Shared application state injected into all handlers.
 #[derive(Clone)]
 pub struct AppState {
     pub db: DatabaseConnections,
-    pub ollama: OllamaClient,
-    pub embeddings: EmbeddingService,
+    pub llm: LlmClient,
+    pub embeddings: Arc<dyn Embedder>,
+    pub moderation: Moderator,
+    /// Scrubs text before it is persisted.
+    pub redactor: Redactor,
+    /// In-process layer over the Redis analysis cache.
+    pub analysis_cache: LocalCache,
     pub config: Arc<AppConfig>,
+    pub jobs: JobQueue,
+    /// Live sockets per session, for syncing messages across tabs.
+    pub sessions: SessionHub,
 }
 
 impl AppState {
-    pub fn new(db: DatabaseConnections, config: AppConfig) -> Self {
-        let ollama = OllamaClient::new(&config.ollama_url, &config.ollama_model);
-        let embeddings = EmbeddingService::new(&config.ollama_url, &config.ollama_embed_model);
+    pub fn new(db: DatabaseConnections, config: AppConfig, jobs: JobQueue) -> anyhow::Result<Self> {
+        let llm = llm::from_config(&config)?;
+        let embeddings = embeddings::from_config(&config, db.redis.clone());
+        let moderation = Moderator::new(config.moderation.clone());
+        let redactor = Redactor::new(&config.redaction)?;
+        let analysis_cache = LocalCache::new(config.analysis_lru_size);
 
-        Self {
+        Ok(Self {
             db,
-            ollama,
+            llm,
             embeddings,
+            moderation,
+            redactor,
+            analysis_cache,
             config: Arc::new(config),
-        }
+            jobs,
+            sessions: SessionHub::default(),
+        })
     }
 }
@@ -1,22 +1,68 @@
 use axum::{
+    body::Bytes,
     extract::{
-        Path, State, WebSocketUpgrade,
+        Path, Query, State, WebSocketUpgrade,
         ws::{Message, WebSocket},
     },
-    response::Response,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
-use futures::{SinkExt, StreamExt};
+use futures::{FutureExt, SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Semaphore, mpsc};
+use tokio::time::{Instant, MissedTickBehavior};
 use uuid::Uuid;
 
+use crate::api::broadcast::{self, SessionMessage};
+use crate::api::error::AppError;
+use crate::api::rate_limit;
+use crate::api::routes::{check_session_owner, moderate, run_chat, session_mode, set_session_mode};
 use crate::api::state::AppState;
+use crate::models::auth;
+use crate::models::requests::ChatRequest;
+use crate::river::dialogue::QuestionStyle;
+use crate::river::episodic::RecallScope;
 use nexus_common::types::ChatMode;
 
+/// Browsers can't set headers on the WebSocket handshake, so the JWT may also
+/// be passed as `?token=`.
+#[derive(Debug, Deserialize)]
+pub struct WsAuthQuery {
+    token: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct WsIncoming {
     message: String,
+    /// Falls back to the connection's mode when omitted.
     #[serde(default)]
-    mode: ChatMode,
+    mode: Option<ChatMode>,
+    #[serde(default)]
+    recall_scope: RecallScope,
+    #[serde(default)]
+    question_style: QuestionStyle,
+}
+
+/// `{"type": "set_mode", "mode": "..."}`: change the mode used for messages
+/// that don't name one.
+#[derive(Debug, Deserialize)]
+struct WsSetMode {
+    mode: String,
+}
+
+/// Control frames are told apart from chat messages by their `type`.
+const SET_MODE_TYPE: &str = "set_mode";
+
+/// A message saved to the session, from this socket or any other.
+#[derive(Debug, Serialize)]
+struct WsSessionMessage {
+    #[serde(rename = "type")]
+    msg_type: &'static str,
+    message: SessionMessage,
 }
 
 #[derive(Debug, Serialize)]
@@ -26,137 +72,574 @@ struct WsOutgoing {
     content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     analysis: Option<serde_json::Value>,
+    /// Which chat message this frame is about, counting from 1 per socket;
+    /// replies can arrive out of order when several run at once.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seq: Option<u64>,
+}
+
+/// A chat message waiting to be processed.
+struct WsJob {
+    seq: u64,
+    incoming: WsIncoming,
+    mode: ChatMode,
+}
+
+/// Progress of a queued chat message, reported back to the socket loop.
+enum WsProgress {
+    Started(u64),
+    Finished(WsOutgoing),
 }
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     Path(session_id): Path<Uuid>,
+    Query(query): Query<WsAuthQuery>,
+    headers: HeaderMap,
     State(state): State<AppState>,
 ) -> Response {
-    ws.on_upgrade(move |socket| handle_socket(socket, session_id, state))
+    let token = headers
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::to_owned)
+        .or(query.token);
+    let Some(claims) = token.and_then(|t| auth::verify_token(&t, &state.config.jwt).ok()) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+    let user_id = claims.sub;
+
+    if let Err(e) = check_session_owner(&state, session_id, user_id).await {
+        return e.into_response();
+    }
+
+    let guest = claims.guest;
+    ws.on_upgrade(move |socket| handle_socket(socket, session_id, user_id, guest, state))
+}
+
+/// Why a WebSocket session ended.
+#[derive(Debug, Clone, Copy)]
+enum CloseReason {
+    /// The client sent a close frame.
+    Client,
+    /// The stream ended or errored without a close frame.
+    Disconnected,
+    /// Nothing was received within the idle timeout.
+    IdleTimeout,
+    /// A ping could not be delivered.
+    SendFailed,
 }
 
-async fn handle_socket(socket: WebSocket, session_id: Uuid, state: AppState) {
+async fn handle_socket(
+    socket: WebSocket,
+    session_id: Uuid,
+    user_id: Uuid,
+    guest: bool,
+    state: AppState,
+) {
     let (mut sender, mut receiver) = socket.split();
 
-    tracing::info!(%session_id, "WebSocket connected");
+    tracing::info!(%session_id, %user_id, "WebSocket connected");
+
+    // Every message saved to the session by another tab or client is relayed
+    // as a "message" frame so all open tabs stay in sync. This socket's own
+    // turns are answered directly instead.
+    let connection_id = Uuid::new_v4();
+    let mut subscription = state.sessions.subscribe(session_id, connection_id);
+
+    // Resume in the mode the session was last used in.
+    let mut mode = match session_mode(&state, session_id).await {
+        Ok(mode) => mode.unwrap_or(state.config.default_chat_mode),
+        Err(e) => {
+            tracing::warn!(%session_id, "Failed to load session mode: {}", e.0);
+            state.config.default_chat_mode
+        }
+    };
 
     // Send welcome message.
     let welcome = WsOutgoing {
         msg_type: "connected".into(),
         content: format!("Session {session_id} established"),
         analysis: None,
+        seq: None,
     };
     if let Ok(json) = serde_json::to_string(&welcome) {
         let _ = sender.send(Message::Text(json.into())).await;
     }
 
-    while let Some(Ok(msg)) = receiver.next().await {
-        match msg {
-            Message::Text(text) => {
-                let incoming: WsIncoming = match serde_json::from_str(&text) {
-                    Ok(m) => m,
-                    Err(e) => {
-                        let err = WsOutgoing {
-                            msg_type: "error".into(),
-                            content: format!("Invalid message format: {e}"),
-                            analysis: None,
-                        };
-                        if let Ok(json) = serde_json::to_string(&err) {
-                            let _ = sender.send(Message::Text(json.into())).await;
-                        }
-                        continue;
-                    }
-                };
+    let idle_timeout = Duration::from_secs(state.config.ws_idle_timeout_secs);
+    let mut ping = tokio::time::interval(Duration::from_secs(state.config.ws_ping_interval_secs));
+    ping.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    // The first tick completes immediately; skip it so we don't ping on connect.
+    ping.tick().await;
+    let mut last_seen = Instant::now();
 
-                // Send thinking indicator.
-                let thinking = WsOutgoing {
-                    msg_type: "thinking".into(),
-                    content: "Processing...".into(),
-                    analysis: None,
+    // Chat messages are processed off the receive loop so it keeps answering
+    // pings and control frames, and queued work is bounded per socket.
+    let (jobs, job_rx) = mpsc::channel(state.config.ws_queue_capacity.max(1));
+    let (progress_tx, mut progress) = mpsc::unbounded_channel();
+    tokio::spawn(process_jobs(
+        state.clone(),
+        session_id,
+        connection_id,
+        user_id,
+        guest,
+        job_rx,
+        progress_tx,
+    ));
+    let mut next_seq = 0u64;
+    let mut pending = 0usize;
+
+    let reason = loop {
+        tokio::select! {
+            _ = ping.tick() => {
+                if sender.send(Message::Ping(Bytes::new())).await.is_err() {
+                    break CloseReason::SendFailed;
+                }
+            }
+            // Processing can outlast the idle timeout; don't count it against the client.
+            _ = tokio::time::sleep_until(last_seen + idle_timeout), if pending == 0 => {
+                break CloseReason::IdleTimeout;
+            }
+            Some(update) = progress.recv() => {
+                let frame = match update {
+                    WsProgress::Started(seq) => WsOutgoing {
+                        msg_type: "thinking".into(),
+                        content: "Processing...".into(),
+                        analysis: None,
+                        seq: Some(seq),
+                    },
+                    WsProgress::Finished(response) => {
+                        pending -= 1;
+                        last_seen = Instant::now();
+                        response
+                    }
                 };
-                if let Ok(json) = serde_json::to_string(&thinking) {
+                if let Ok(json) = serde_json::to_string(&frame) {
                     let _ = sender.send(Message::Text(json.into())).await;
                 }
-
-                // Process through the appropriate engine.
-                let response = process_ws_message(&state, session_id, &incoming).await;
-
-                if let Ok(json) = serde_json::to_string(&response) {
+            }
+            message = subscription.recv() => {
+                let frame = WsSessionMessage { msg_type: "message", message };
+                if let Ok(json) = serde_json::to_string(&frame) {
                     let _ = sender.send(Message::Text(json.into())).await;
                 }
             }
-            Message::Close(_) => {
-                tracing::info!(%session_id, "WebSocket closed");
-                break;
+            msg = receiver.next() => {
+                let Some(Ok(msg)) = msg else {
+                    break CloseReason::Disconnected;
+                };
+                last_seen = Instant::now();
+
+                match msg {
+                    Message::Text(text) => {
+                        let parsed = serde_json::from_str::<serde_json::Value>(&text);
+                        let is_set_mode = parsed.as_ref().is_ok_and(|v| {
+                            v.get("type").and_then(serde_json::Value::as_str)
+                                == Some(SET_MODE_TYPE)
+                        });
+                        if is_set_mode {
+                            let reply = match parsed.and_then(serde_json::from_value::<WsSetMode>) {
+                                Ok(req) => {
+                                    apply_set_mode(&state, session_id, user_id, &req.mode, &mut mode)
+                                        .await
+                                }
+                                Err(e) => WsOutgoing {
+                                    msg_type: "error".into(),
+                                    content: format!("Invalid message format: {e}"),
+                                    analysis: None,
+                                    seq: None,
+                                },
+                            };
+                            if let Ok(json) = serde_json::to_string(&reply) {
+                                let _ = sender.send(Message::Text(json.into())).await;
+                            }
+                            continue;
+                        }
+
+                        let incoming: WsIncoming = match parsed.and_then(serde_json::from_value) {
+                            Ok(m) => m,
+                            Err(e) => {
+                                let err = WsOutgoing {
+                                    msg_type: "error".into(),
+                                    content: format!("Invalid message format: {e}"),
+                                    analysis: None,
+                                    seq: None,
+                                };
+                                if let Ok(json) = serde_json::to_string(&err) {
+                                    let _ = sender.send(Message::Text(json.into())).await;
+                                }
+                                continue;
+                            }
+                        };
+
+                        // Hand the message to the processing task and ack at once,
+                        // or turn it away if too many are already waiting.
+                        next_seq += 1;
+                        let job = WsJob { seq: next_seq, incoming, mode };
+                        let reply = match jobs.try_send(job) {
+                            Ok(()) => {
+                                pending += 1;
+                                WsOutgoing {
+                                    msg_type: "queued".into(),
+                                    content: format!("Queued ({pending} pending)"),
+                                    analysis: None,
+                                    seq: Some(next_seq),
+                                }
+                            }
+                            Err(_) => WsOutgoing {
+                                msg_type: "busy".into(),
+                                content: "Too many messages in progress; try again shortly".into(),
+                                analysis: None,
+                                seq: Some(next_seq),
+                            },
+                        };
+                        if let Ok(json) = serde_json::to_string(&reply) {
+                            let _ = sender.send(Message::Text(json.into())).await;
+                        }
+                    }
+                    Message::Ping(payload) => {
+                        let _ = sender.send(Message::Pong(payload)).await;
+                    }
+                    Message::Close(_) => break CloseReason::Client,
+                    _ => {}
+                }
             }
-            _ => {}
+        }
+    };
+
+    match reason {
+        CloseReason::Client => tracing::info!(%session_id, "WebSocket closed by client"),
+        CloseReason::Disconnected => tracing::info!(%session_id, "WebSocket disconnected"),
+        CloseReason::IdleTimeout => {
+            tracing::warn!(
+                %session_id,
+                idle_timeout_secs = idle_timeout.as_secs(),
+                "WebSocket closed after idle timeout"
+            );
+            let _ = sender.send(Message::Close(None)).await;
+        }
+        CloseReason::SendFailed => {
+            tracing::warn!(%session_id, "WebSocket closed after failed ping")
         }
     }
 }
 
+/// Run queued chat messages, at most `WS_MAX_IN_FLIGHT` at a time, until the
+/// socket closes. Messages still queued when it does are dropped; those
+/// already running are finished, since their turns are saved to the session
+/// either way. A message whose
+/// processing panics is answered with an error frame; the socket stays open.
+async fn process_jobs(
+    state: AppState,
+    session_id: Uuid,
+    connection_id: Uuid,
+    user_id: Uuid,
+    guest: bool,
+    jobs: mpsc::Receiver<WsJob>,
+    progress: mpsc::UnboundedSender<WsProgress>,
+) {
+    let max_in_flight = state.config.ws_max_in_flight;
+    run_jobs(session_id, max_in_flight, jobs, progress, move |job| {
+        let state = state.clone();
+        broadcast::from_connection(connection_id, async move {
+            process_ws_message(&state, session_id, user_id, guest, job.incoming, job.mode).await
+        })
+    })
+    .await;
+}
+
+/// The queue behind [`process_jobs`], with each message handled by `process`.
+async fn run_jobs<F, Fut>(
+    session_id: Uuid,
+    max_in_flight: usize,
+    mut jobs: mpsc::Receiver<WsJob>,
+    progress: mpsc::UnboundedSender<WsProgress>,
+    process: F,
+) where
+    F: Fn(WsJob) -> Fut,
+    Fut: Future<Output = WsOutgoing> + Send + 'static,
+{
+    let slots = Arc::new(Semaphore::new(max_in_flight.max(1)));
+    while let Some(job) = jobs.recv().await {
+        let Ok(permit) = slots.clone().acquire_owned().await else {
+            break;
+        };
+        // The socket closed while this job waited; nobody is left to answer.
+        if progress.is_closed() {
+            break;
+        }
+        let progress = progress.clone();
+        let seq = job.seq;
+        let processing = process(job);
+        tokio::spawn(async move {
+            let _ = progress.send(WsProgress::Started(seq));
+            let response = answer(session_id, seq, processing).await;
+            let _ = progress.send(WsProgress::Finished(response));
+            drop(permit);
+        });
+    }
+}
+
+/// Await a message's processing and tag the reply with its `seq`. A panic is
+/// logged and answered with a generic error frame.
+async fn answer(
+    session_id: Uuid,
+    seq: u64,
+    processing: impl Future<Output = WsOutgoing>,
+) -> WsOutgoing {
+    let outcome = AssertUnwindSafe(processing).catch_unwind().await;
+    let mut response = outcome.unwrap_or_else(|panic| {
+        tracing::error!(
+            %session_id,
+            seq,
+            "WebSocket message processing panicked: {}",
+            panic_message(&*panic)
+        );
+        WsOutgoing {
+            msg_type: "error".into(),
+            content: "Internal error while processing the message".into(),
+            analysis: None,
+            seq: None,
+        }
+    });
+    response.seq = Some(seq);
+    response
+}
+
+/// The message a panic was raised with, if it was a string.
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
+}
+
+/// Validate and store a `set_mode` request, replying with the mode now in use.
+async fn apply_set_mode(
+    state: &AppState,
+    session_id: Uuid,
+    user_id: Uuid,
+    requested: &str,
+    mode: &mut ChatMode,
+) -> WsOutgoing {
+    let new_mode = match requested.parse::<ChatMode>() {
+        Ok(mode) => mode,
+        Err(e) => {
+            return WsOutgoing {
+                msg_type: "error".into(),
+                content: format!("Invalid mode: {e}"),
+                analysis: None,
+                seq: None,
+            };
+        }
+    };
+
+    if let Err(e) = set_session_mode(state, session_id, user_id, new_mode).await {
+        tracing::warn!(%session_id, "Failed to persist session mode: {}", e.0);
+    }
+    *mode = new_mode;
+
+    WsOutgoing {
+        msg_type: "mode".into(),
+        content: new_mode.to_string(),
+        analysis: None,
+        seq: None,
+    }
+}
+
+/// Run a turn through the same path as `POST /api/v1/chat`, so the session and
+/// both messages are persisted under the authenticated user.
 async fn process_ws_message(
     state: &AppState,
     session_id: Uuid,
-    incoming: &WsIncoming,
+    user_id: Uuid,
+    guest: bool,
+    incoming: WsIncoming,
+    default_mode: ChatMode,
 ) -> WsOutgoing {
-    match incoming.mode {
-        ChatMode::Conversation => {
-            match crate::river::dialogue::process_message(
-                state,
-                session_id,
-                // Use a placeholder user_id for WS (auth should be added).
-                Uuid::nil(),
-                &incoming.message,
-            )
-            .await
-            {
-                Ok(response) => WsOutgoing {
-                    msg_type: "response".into(),
-                    content: response,
-                    analysis: None,
-                },
-                Err(e) => WsOutgoing {
-                    msg_type: "error".into(),
-                    content: format!("River error: {e}"),
-                    analysis: None,
-                },
+    let mode = incoming.mode.unwrap_or(default_mode);
+    let req = ChatRequest {
+        message: incoming.message,
+        mode: Some(mode),
+        session_id: Some(session_id),
+        explain: false,
+        recall_scope: incoming.recall_scope,
+        question_style: incoming.question_style,
+    };
+
+    if let Some(retry_after) =
+        rate_limit::acquire(state, user_id, guest, rate_limit::Tier::for_mode(mode)).await
+    {
+        return WsOutgoing {
+            msg_type: "error".into(),
+            content: format!(
+                "Rate limit exceeded; retry in {}s",
+                retry_after.as_secs_f64().ceil().max(1.0) as u64
+            ),
+            analysis: None,
+            seq: None,
+        };
+    }
+
+    let result = async {
+        req.validate(state.config.max_input_chars)?;
+        moderate(state, &req.message).await?;
+        run_chat(state, user_id, &req).await
+    }
+    .await;
+
+    match result {
+        Ok(response) => {
+            let msg_type = match mode {
+                ChatMode::Conversation => "response",
+                ChatMode::Analysis => "analysis",
+                ChatMode::Integrated => "integrated",
+            };
+            WsOutgoing {
+                msg_type: msg_type.into(),
+                content: response.message,
+                analysis: response
+                    .analysis
+                    .and_then(|a| serde_json::to_value(&a).ok()),
+                seq: None,
             }
         }
-        ChatMode::Analysis => {
-            match crate::perspective::engine::analyze_text(state, &incoming.message).await {
-                Ok(result) => WsOutgoing {
-                    msg_type: "analysis".into(),
-                    content: "Analysis complete".into(),
-                    analysis: serde_json::to_value(&result).ok(),
-                },
-                Err(e) => WsOutgoing {
-                    msg_type: "error".into(),
-                    content: format!("Perspective error: {e}"),
-                    analysis: None,
-                },
-            }
+        Err(e) => error_frame(mode, &e),
+    }
+}
+
+/// The frame reporting a failed turn. Like an HTTP error response, it carries
+/// only the client-facing message; the details are logged.
+fn error_frame(mode: ChatMode, e: &AppError) -> WsOutgoing {
+    let label = match mode {
+        ChatMode::Conversation => "River error",
+        ChatMode::Analysis => "Perspective error",
+        ChatMode::Integrated => "Integrated mode error",
+    };
+    tracing::warn!("WebSocket turn failed in {mode} mode: {:#}", e.0);
+    WsOutgoing {
+        msg_type: "error".into(),
+        content: format!("{label}: {}", e.status_and_message().1),
+        analysis: None,
+        seq: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn panicking_engine() -> WsOutgoing {
+        panic!("engine exploded")
+    }
+
+    #[tokio::test]
+    async fn a_panicking_engine_is_answered_with_an_error() {
+        let response = answer(Uuid::new_v4(), 7, panicking_engine()).await;
+        assert_eq!(response.msg_type, "error");
+        assert_eq!(
+            response.content,
+            "Internal error while processing the message"
+        );
+        assert_eq!(response.seq, Some(7));
+    }
+
+    #[tokio::test]
+    async fn a_normal_reply_is_tagged_with_its_seq() {
+        let reply = WsOutgoing {
+            msg_type: "response".into(),
+            content: "Why do you think so?".into(),
+            analysis: None,
+            seq: None,
+        };
+        let response = answer(Uuid::new_v4(), 3, async { reply }).await;
+        assert_eq!(response.msg_type, "response");
+        assert_eq!(response.seq, Some(3));
+    }
+
+    fn job(seq: u64, message: &str) -> WsJob {
+        WsJob {
+            seq,
+            incoming: WsIncoming {
+                message: message.into(),
+                mode: None,
+                recall_scope: Default::default(),
+                question_style: Default::default(),
+            },
+            mode: ChatMode::Conversation,
         }
-        ChatMode::Integrated => {
-            match crate::river::integrated::process_integrated(
-                state,
-                session_id,
-                Uuid::nil(),
-                &incoming.message,
-            )
-            .await
+    }
+
+    async fn next_reply(progress: &mut mpsc::UnboundedReceiver<WsProgress>) -> WsOutgoing {
+        loop {
+            match progress
+                .recv()
+                .await
+                .expect("the queue should still be running")
             {
-                Ok((response, analysis)) => WsOutgoing {
-                    msg_type: "integrated".into(),
-                    content: response,
-                    analysis: serde_json::to_value(&analysis).ok(),
-                },
-                Err(e) => WsOutgoing {
-                    msg_type: "error".into(),
-                    content: format!("Integrated mode error: {e}"),
-                    analysis: None,
-                },
+                WsProgress::Started(_) => {}
+                WsProgress::Finished(reply) => return reply,
             }
         }
     }
+
+    #[tokio::test]
+    async fn the_queue_keeps_answering_after_a_panic() {
+        let (jobs, job_rx) = mpsc::channel(4);
+        let (progress_tx, mut progress) = mpsc::unbounded_channel();
+        tokio::spawn(run_jobs(
+            Uuid::new_v4(),
+            1,
+            job_rx,
+            progress_tx,
+            |job: WsJob| async move {
+                if job.incoming.message == "explode" {
+                    panic!("engine exploded");
+                }
+                WsOutgoing {
+                    msg_type: "response".into(),
+                    content: format!("You said: {}", job.incoming.message),
+                    analysis: None,
+                    seq: None,
+                }
+            },
+        ));
+
+        jobs.send(job(1, "explode")).await.unwrap();
+        let reply = next_reply(&mut progress).await;
+        assert_eq!(reply.msg_type, "error");
+        assert_eq!(reply.seq, Some(1));
+
+        jobs.send(job(2, "hello")).await.unwrap();
+        let reply = next_reply(&mut progress).await;
+        assert_eq!(reply.msg_type, "response");
+        assert_eq!(reply.content, "You said: hello");
+        assert_eq!(reply.seq, Some(2));
+    }
+
+    #[test]
+    fn error_frames_hide_internal_details() {
+        let internal = AppError(anyhow::anyhow!("connection refused by 10.0.0.7:5432"));
+        let frame = error_frame(ChatMode::Conversation, &internal);
+        assert_eq!(frame.msg_type, "error");
+        assert_eq!(frame.content, "River error: Internal server error");
+
+        let invalid = AppError::from(nexus_common::error::NexusError::Validation(
+            "message must not be empty".into(),
+        ));
+        let frame = error_frame(ChatMode::Analysis, &invalid);
+        assert_eq!(
+            frame.content,
+            "Perspective error: message must not be empty"
+        );
+    }
+
+    #[test]
+    fn panic_messages_are_read_from_strings() {
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&String::from("owned")), "owned");
+        assert_eq!(panic_message(&42), "non-string panic payload");
+    }
 }
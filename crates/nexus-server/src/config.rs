@@ -1,36 +1,292 @@
-use crate::db::{influxdb::InfluxConfig, neo4j::Neo4jConfig};
+use jsonwebtoken::Algorithm;
+
+use crate::api::rate_limit::RateLimitConfig;
+use crate::db::{
+    influxdb::InfluxConfig, neo4j::Neo4jConfig, postgres::PostgresConfig, qdrant::QdrantConfig,
+};
+use crate::models::auth::{GuestConfig, JwtConfig};
+use crate::perspective::engine::Layer;
+use crate::river::episodic::SearchMode;
+use crate::shared::circuit_breaker::CircuitBreakerConfig;
+use crate::shared::embeddings::{EmbedBackend, OpenAiConfig};
+use crate::shared::llm::{LlmBackendKind, NumPredictConfig};
+use crate::shared::moderation::ModerationConfig;
+use crate::shared::ollama::LlmIoLogConfig;
+use crate::shared::redaction::RedactionConfig;
+use nexus_common::types::ChatMode;
 
 /// Application configuration loaded from environment variables.
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub host: String,
     pub port: u16,
-    pub database_url: String,
+    pub postgres: PostgresConfig,
     pub neo4j: Neo4jConfig,
-    pub qdrant_url: String,
+    pub qdrant: QdrantConfig,
     pub influxdb: InfluxConfig,
     pub redis_url: String,
     pub ollama_url: String,
     pub ollama_model: String,
     pub ollama_embed_model: String,
-    pub jwt_secret: String,
-    pub jwt_expiry_hours: u64,
+    /// How long Ollama keeps models loaded between calls; `None` uses its default.
+    pub ollama_keep_alive: Option<String>,
+    /// Load the chat and embedding models at startup.
+    pub ollama_warmup: bool,
+    /// When to stop calling a failing Ollama for a while.
+    pub llm_circuit: CircuitBreakerConfig,
+    /// Token budgets for LLM output.
+    pub llm_num_predict: NumPredictConfig,
+    pub llm_backend: LlmBackendKind,
+    /// Directory of canned responses for `LLM_BACKEND=mock`; defaults to the
+    /// `fixtures/llm` directory shipped with this crate.
+    pub llm_fixtures_dir: String,
+    pub embed_backend: EmbedBackend,
+    pub embed_dimension: Option<u64>,
+    /// Cache embeddings in Redis, keyed by model and text hash.
+    pub embedding_cache_enabled: bool,
+    pub embedding_cache_ttl_secs: u64,
+    pub openai: OpenAiConfig,
+    pub jwt: JwtConfig,
+    pub max_body_bytes: usize,
+    pub max_input_chars: usize,
+    /// Input with fewer words is not sent to the LLM.
+    pub min_input_words: usize,
+    /// Largest document accepted by `/analyze/upload`. Requests are still
+    /// bounded by `max_body_bytes` as a whole.
+    pub max_upload_bytes: usize,
+    /// Analysis input longer than this is split between sentences and each
+    /// layer run per chunk, so long texts don't overflow the model's context.
+    /// Falls back to the older `UPLOAD_CHUNK_CHARS`.
+    pub analysis_chunk_chars: usize,
+    pub health_check_timeout_secs: u64,
+    /// How long startup waits for each database to accept connections.
+    pub startup_max_wait_secs: u64,
+    pub cors: CorsConfig,
+    pub max_context_tokens: usize,
+    pub enable_rerank: bool,
+    pub rerank_candidates: u64,
+    pub memory_search_mode: SearchMode,
+    /// Analysis layers run on each integrated-mode turn; fewer is faster.
+    pub integrated_analysis_layers: Vec<Layer>,
+    /// Mode for chat requests and new WebSocket sessions that don't name one.
+    pub default_chat_mode: ChatMode,
+    /// Days to keep episodic memories; `None` keeps them forever.
+    pub episodic_retention_days: Option<u64>,
+    /// How often expired memories are pruned; `None` (0) never prunes.
+    pub episodic_prune_interval_secs: Option<u64>,
+    /// How often a WebSocket is pinged; must be at least 1.
+    pub ws_ping_interval_secs: u64,
+    /// Close a WebSocket when nothing is received for this long.
+    pub ws_idle_timeout_secs: u64,
+    /// Chat messages a WebSocket may have waiting; more are refused as busy.
+    pub ws_queue_capacity: usize,
+    /// Chat messages a WebSocket may have running at once.
+    pub ws_max_in_flight: usize,
+    pub job_workers: usize,
+    pub job_queue_capacity: usize,
+    /// Contradictions below this severity are not linked or raised in dialogue.
+    pub contradiction_min_severity: f64,
+    /// Sampling temperature for belief extraction and contradiction checks.
+    pub belief_extraction_temperature: f32,
+    /// Fixed seed for belief extraction and contradiction checks, making them
+    /// repeatable if Ollama honours it; `None` samples freely.
+    pub belief_extraction_seed: Option<i64>,
+    /// Overall deadline for a 4-layer analysis.
+    pub analysis_timeout_secs: u64,
+    /// Analyses kept in the in-process cache; 0 disables it.
+    pub analysis_lru_size: usize,
+    /// Beliefs kept per user; beyond this the weakest, stalest are evicted.
+    pub max_beliefs_per_user: usize,
+    /// Existing beliefs (most similar first) a new claim is checked against.
+    pub contradiction_candidates: u64,
+    /// Beliefs most similar to the message included in the dialogue prompt.
+    pub context_beliefs: usize,
+    pub llm_io_log: LlmIoLogConfig,
+    pub moderation: ModerationConfig,
+    pub redaction: RedactionConfig,
+    /// How often pending Neo4j/Qdrant writes are applied from the outbox; must
+    /// be at least 1.
+    pub outbox_poll_interval_secs: u64,
+    /// How often to check for days needing a digest; `None` disables digests.
+    pub digest_interval_secs: Option<u64>,
+    pub rate_limit: RateLimitConfig,
+    pub guest: GuestConfig,
+}
+
+/// CORS policy. `None` for origins or methods means any (`*`).
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Option<Vec<String>>,
+    pub allowed_methods: Option<Vec<String>>,
+    pub allow_credentials: bool,
+}
+
+impl CorsConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let allowed_origins =
+            parse_list(&std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_else(|_| "*".into()));
+        let allowed_methods = parse_list(
+            &std::env::var("CORS_ALLOWED_METHODS")
+                .unwrap_or_else(|_| "*".into())
+                .to_uppercase(),
+        );
+        let allow_credentials: bool = std::env::var("CORS_ALLOW_CREDENTIALS")
+            .unwrap_or_else(|_| "false".into())
+            .parse()?;
+
+        if allow_credentials && allowed_origins.is_none() {
+            anyhow::bail!(
+                "CORS_ALLOWED_ORIGINS must list explicit origins when CORS_ALLOW_CREDENTIALS is true"
+            );
+        }
+        for origin in allowed_origins.iter().flatten() {
+            axum::http::HeaderValue::from_str(origin)
+                .map_err(|e| anyhow::anyhow!("Invalid CORS origin '{origin}': {e}"))?;
+        }
+        for method in allowed_methods.iter().flatten() {
+            axum::http::Method::from_bytes(method.as_bytes())
+                .map_err(|e| anyhow::anyhow!("Invalid CORS method '{method}': {e}"))?;
+        }
+
+        Ok(Self {
+            allowed_origins,
+            allowed_methods,
+            allow_credentials,
+        })
+    }
+}
+
+/// Non-blank lines of a file, skipping `#` comments.
+fn read_lines(path: &str) -> anyhow::Result<Vec<String>> {
+    use anyhow::Context;
+
+    let contents =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+    Ok(contents
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Parse a comma-separated list of analysis layers, `*` meaning all four.
+fn parse_layers(raw: &str) -> anyhow::Result<Vec<Layer>> {
+    let Some(names) = parse_list(raw) else {
+        return Ok(Layer::ALL.to_vec());
+    };
+    let mut layers = Vec::new();
+    for name in names {
+        let layer: Layer = name.parse()?;
+        if !layers.contains(&layer) {
+            layers.push(layer);
+        }
+    }
+    if layers.is_empty() {
+        anyhow::bail!("INTEGRATED_ANALYSIS_LAYERS must name at least one layer");
+    }
+    Ok(layers)
+}
+
+/// Read a timer period in seconds from `key`. Zero is rejected, since a
+/// zero-length interval panics in tokio.
+fn interval_secs(key: &str, default: &str) -> anyhow::Result<u64> {
+    let secs: u64 = std::env::var(key)
+        .unwrap_or_else(|_| default.into())
+        .parse()?;
+    if secs == 0 {
+        anyhow::bail!("{key} must be at least 1");
+    }
+    Ok(secs)
+}
+
+/// Parse a comma-separated list, treating `*` as "any".
+fn parse_list(raw: &str) -> Option<Vec<String>> {
+    let raw = raw.trim();
+    if raw == "*" {
+        return None;
+    }
+    Some(
+        raw.split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect(),
+    )
 }
 
 impl AppConfig {
     pub fn from_env() -> anyhow::Result<Self> {
+        let embed_backend: EmbedBackend = std::env::var("EMBED_BACKEND")
+            .unwrap_or_else(|_| "ollama".into())
+            .parse()?;
+        let openai_api_key = std::env::var("OPENAI_API_KEY").ok();
+        if embed_backend == EmbedBackend::OpenAi && openai_api_key.is_none() {
+            anyhow::bail!("OPENAI_API_KEY is required when EMBED_BACKEND=openai");
+        }
+        let jwt_algorithm: Algorithm = std::env::var("JWT_ALGORITHM")
+            .unwrap_or_else(|_| "HS256".into())
+            .parse()?;
+        if !matches!(
+            jwt_algorithm,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            anyhow::bail!(
+                "JWT_ALGORITHM {jwt_algorithm:?} is not supported; use HS256, HS384 or HS512"
+            );
+        }
+
         Ok(Self {
             host: std::env::var("HOST").unwrap_or_else(|_| "0.0.0.0".into()),
             port: std::env::var("PORT")
                 .unwrap_or_else(|_| "3001".into())
                 .parse()?,
-            database_url: std::env::var("DATABASE_URL")?,
+            postgres: PostgresConfig {
+                url: std::env::var("DATABASE_URL")?,
+                max_connections: std::env::var("PG_MAX_CONNECTIONS")
+                    .unwrap_or_else(|_| "20".into())
+                    .parse()?,
+                min_connections: std::env::var("PG_MIN_CONNECTIONS")
+                    .unwrap_or_else(|_| "0".into())
+                    .parse()?,
+                acquire_timeout_secs: std::env::var("PG_ACQUIRE_TIMEOUT_SECS")
+                    .unwrap_or_else(|_| "30".into())
+                    .parse()?,
+            },
             neo4j: Neo4jConfig {
                 uri: std::env::var("NEO4J_URI")?,
                 user: std::env::var("NEO4J_USER")?,
                 password: std::env::var("NEO4J_PASSWORD")?,
+                max_connections: std::env::var("NEO4J_MAX_CONNECTIONS")
+                    .unwrap_or_else(|_| "16".into())
+                    .parse()?,
+                query_timeout_secs: std::env::var("NEO4J_QUERY_TIMEOUT_SECS")
+                    .or_else(|_| std::env::var("NEO4J_ACQUIRE_TIMEOUT_SECS"))
+                    .unwrap_or_else(|_| "30".into())
+                    .parse()?,
+                query_attempts: std::env::var("NEO4J_QUERY_ATTEMPTS")
+                    .unwrap_or_else(|_| "3".into())
+                    .parse()?,
+            },
+            qdrant: QdrantConfig {
+                url: std::env::var("QDRANT_URL")?,
+                timeout_secs: std::env::var("QDRANT_TIMEOUT_SECS")
+                    .unwrap_or_else(|_| "5".into())
+                    .parse()?,
+                connect_timeout_secs: std::env::var("QDRANT_CONNECT_TIMEOUT_SECS")
+                    .unwrap_or_else(|_| "5".into())
+                    .parse()?,
+                recreate_on_dimension_mismatch: std::env::var(
+                    "QDRANT_RECREATE_ON_DIMENSION_MISMATCH",
+                )
+                .unwrap_or_else(|_| "false".into())
+                .parse()?,
+                scalar_quantization: std::env::var("QDRANT_SCALAR_QUANTIZATION")
+                    .unwrap_or_else(|_| "false".into())
+                    .parse()?,
+                distance: crate::db::qdrant::parse_distance(
+                    &std::env::var("QDRANT_DISTANCE").unwrap_or_else(|_| "cosine".into()),
+                )?,
             },
-            qdrant_url: std::env::var("QDRANT_URL")?,
             influxdb: InfluxConfig {
                 url: std::env::var("INFLUXDB_URL")?,
                 token: std::env::var("INFLUXDB_TOKEN")?,
@@ -43,10 +299,240 @@ impl AppConfig {
             ollama_model: std::env::var("OLLAMA_MODEL").unwrap_or_else(|_| "llama3.1:8b".into()),
             ollama_embed_model: std::env::var("OLLAMA_EMBED_MODEL")
                 .unwrap_or_else(|_| "nomic-embed-text".into()),
-            jwt_secret: std::env::var("JWT_SECRET")?,
-            jwt_expiry_hours: std::env::var("JWT_EXPIRY_HOURS")
-                .unwrap_or_else(|_| "24".into())
+            ollama_keep_alive: std::env::var("OLLAMA_KEEP_ALIVE")
+                .ok()
+                .filter(|v| !v.trim().is_empty()),
+            ollama_warmup: std::env::var("OLLAMA_WARMUP")
+                .unwrap_or_else(|_| "true".into())
+                .parse()?,
+            llm_circuit: CircuitBreakerConfig {
+                failure_threshold: std::env::var("LLM_CIRCUIT_FAILURE_THRESHOLD")
+                    .unwrap_or_else(|_| "5".into())
+                    .parse()?,
+                cooldown_secs: std::env::var("LLM_CIRCUIT_COOLDOWN_SECS")
+                    .unwrap_or_else(|_| "30".into())
+                    .parse()?,
+            },
+            llm_num_predict: NumPredictConfig {
+                text: std::env::var("OLLAMA_NUM_PREDICT_CHAT")
+                    .unwrap_or_else(|_| "2048".into())
+                    .parse()?,
+                json: std::env::var("OLLAMA_NUM_PREDICT_JSON")
+                    .unwrap_or_else(|_| "4096".into())
+                    .parse()?,
+                analysis: std::env::var("OLLAMA_NUM_PREDICT_ANALYSIS")
+                    .unwrap_or_else(|_| "6144".into())
+                    .parse()?,
+                max: std::env::var("OLLAMA_NUM_PREDICT_MAX")
+                    .unwrap_or_else(|_| "16384".into())
+                    .parse()?,
+            },
+            llm_backend: std::env::var("LLM_BACKEND")
+                .unwrap_or_else(|_| "ollama".into())
+                .parse()?,
+            llm_fixtures_dir: std::env::var("LLM_FIXTURES_DIR")
+                .unwrap_or_else(|_| concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/llm").into()),
+            embed_backend,
+            embedding_cache_enabled: std::env::var("EMBEDDING_CACHE_ENABLED")
+                .unwrap_or_else(|_| "true".into())
                 .parse()?,
+            embedding_cache_ttl_secs: std::env::var("EMBEDDING_CACHE_TTL_SECS")
+                .unwrap_or_else(|_| "604800".into())
+                .parse()?,
+            embed_dimension: std::env::var("EMBED_DIMENSION")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()?,
+            openai: OpenAiConfig {
+                api_key: openai_api_key,
+                base_url: std::env::var("OPENAI_BASE_URL")
+                    .unwrap_or_else(|_| "https://api.openai.com".into()),
+                embed_model: std::env::var("OPENAI_EMBED_MODEL")
+                    .unwrap_or_else(|_| "text-embedding-3-small".into()),
+            },
+            jwt: JwtConfig {
+                secret: std::env::var("JWT_SECRET")?,
+                expiry_hours: std::env::var("JWT_EXPIRY_HOURS")
+                    .unwrap_or_else(|_| "24".into())
+                    .parse()?,
+                algorithm: jwt_algorithm,
+                issuer: std::env::var("JWT_ISSUER").ok(),
+                audience: std::env::var("JWT_AUDIENCE").ok(),
+            },
+            max_body_bytes: std::env::var("MAX_BODY_BYTES")
+                .unwrap_or_else(|_| "1048576".into())
+                .parse()?,
+            max_input_chars: std::env::var("MAX_INPUT_CHARS")
+                .unwrap_or_else(|_| "20000".into())
+                .parse()?,
+            min_input_words: std::env::var("MIN_INPUT_WORDS")
+                .unwrap_or_else(|_| "1".into())
+                .parse()?,
+            max_upload_bytes: std::env::var("MAX_UPLOAD_BYTES")
+                .unwrap_or_else(|_| "1000000".into())
+                .parse()?,
+            analysis_chunk_chars: std::env::var("ANALYSIS_CHUNK_CHARS")
+                .or_else(|_| std::env::var("UPLOAD_CHUNK_CHARS"))
+                .unwrap_or_else(|_| "8000".into())
+                .parse()?,
+            health_check_timeout_secs: std::env::var("HEALTH_CHECK_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "2".into())
+                .parse()?,
+            startup_max_wait_secs: std::env::var("STARTUP_MAX_WAIT_SECS")
+                .unwrap_or_else(|_| "60".into())
+                .parse()?,
+            cors: CorsConfig::from_env()?,
+            max_context_tokens: std::env::var("MAX_CONTEXT_TOKENS")
+                .unwrap_or_else(|_| "3000".into())
+                .parse()?,
+            enable_rerank: std::env::var("ENABLE_RERANK")
+                .unwrap_or_else(|_| "false".into())
+                .parse()?,
+            rerank_candidates: std::env::var("RERANK_CANDIDATES")
+                .unwrap_or_else(|_| "20".into())
+                .parse()?,
+            memory_search_mode: std::env::var("MEMORY_SEARCH_MODE")
+                .unwrap_or_else(|_| "vector".into())
+                .parse()?,
+            integrated_analysis_layers: parse_layers(
+                &std::env::var("INTEGRATED_ANALYSIS_LAYERS").unwrap_or_else(|_| "*".into()),
+            )?,
+            default_chat_mode: std::env::var("DEFAULT_CHAT_MODE")
+                .unwrap_or_else(|_| "integrated".into())
+                .parse()?,
+            episodic_retention_days: std::env::var("EPISODIC_RETENTION_DAYS")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()?
+                .filter(|&days| days > 0),
+            episodic_prune_interval_secs: std::env::var("EPISODIC_PRUNE_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".into())
+                .parse::<u64>()
+                .map(|secs| (secs > 0).then_some(secs))?,
+            ws_ping_interval_secs: interval_secs("WS_PING_INTERVAL_SECS", "30")?,
+            ws_idle_timeout_secs: std::env::var("WS_IDLE_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "90".into())
+                .parse()?,
+            ws_queue_capacity: std::env::var("WS_QUEUE_CAPACITY")
+                .unwrap_or_else(|_| "8".into())
+                .parse()?,
+            ws_max_in_flight: std::env::var("WS_MAX_IN_FLIGHT")
+                .unwrap_or_else(|_| "1".into())
+                .parse()?,
+            job_workers: std::env::var("JOB_WORKERS")
+                .unwrap_or_else(|_| "4".into())
+                .parse()?,
+            job_queue_capacity: std::env::var("JOB_QUEUE_CAPACITY")
+                .unwrap_or_else(|_| "100".into())
+                .parse()?,
+            contradiction_min_severity: std::env::var("CONTRADICTION_MIN_SEVERITY")
+                .unwrap_or_else(|_| "0.5".into())
+                .parse()?,
+            belief_extraction_temperature: std::env::var("BELIEF_EXTRACTION_TEMPERATURE")
+                .unwrap_or_else(|_| "0.3".into())
+                .parse()?,
+            belief_extraction_seed: std::env::var("BELIEF_EXTRACTION_SEED")
+                .ok()
+                .map(|v| v.parse())
+                .transpose()?,
+            analysis_timeout_secs: std::env::var("ANALYSIS_TIMEOUT_SECS")
+                .unwrap_or_else(|_| "120".into())
+                .parse()?,
+            analysis_lru_size: std::env::var("ANALYSIS_LRU_SIZE")
+                .unwrap_or_else(|_| "256".into())
+                .parse()?,
+            max_beliefs_per_user: std::env::var("MAX_BELIEFS_PER_USER")
+                .unwrap_or_else(|_| "1000".into())
+                .parse()?,
+            contradiction_candidates: std::env::var("CONTRADICTION_CANDIDATES")
+                .unwrap_or_else(|_| "20".into())
+                .parse()?,
+            context_beliefs: std::env::var("CONTEXT_BELIEFS")
+                .unwrap_or_else(|_| "20".into())
+                .parse()?,
+            llm_io_log: LlmIoLogConfig {
+                enabled: std::env::var("LOG_LLM_IO")
+                    .unwrap_or_else(|_| "false".into())
+                    .parse()?,
+                max_chars: std::env::var("LOG_LLM_IO_MAX_CHARS")
+                    .unwrap_or_else(|_| "4000".into())
+                    .parse()?,
+                redact: std::env::var("LOG_LLM_IO_REDACT")
+                    .or_else(|_| std::env::var("LOG_LLM_IO_REDACT_USER"))
+                    .unwrap_or_else(|_| "false".into())
+                    .parse()?,
+            },
+            moderation: ModerationConfig {
+                enabled: std::env::var("ENABLE_MODERATION")
+                    .unwrap_or_else(|_| "false".into())
+                    .parse()?,
+                url: std::env::var("MODERATION_URL").ok(),
+                api_key: std::env::var("MODERATION_API_KEY").ok(),
+                timeout_secs: std::env::var("MODERATION_TIMEOUT_SECS")
+                    .unwrap_or_else(|_| "5".into())
+                    .parse()?,
+                blocked_terms: parse_list(
+                    &std::env::var("MODERATION_BLOCKLIST").unwrap_or_default(),
+                )
+                .unwrap_or_default(),
+            },
+            redaction: RedactionConfig {
+                enabled: std::env::var("ENABLE_REDACTION")
+                    .unwrap_or_else(|_| "false".into())
+                    .parse()?,
+                terms: {
+                    let mut terms =
+                        parse_list(&std::env::var("REDACTION_TERMS").unwrap_or_default())
+                            .unwrap_or_default();
+                    if let Ok(path) = std::env::var("REDACTION_TERMS_FILE") {
+                        terms.extend(read_lines(&path)?);
+                    }
+                    terms
+                },
+                patterns: match std::env::var("REDACTION_PATTERNS_FILE") {
+                    Ok(path) => read_lines(&path)?,
+                    Err(_) => Vec::new(),
+                },
+                detect_pii: std::env::var("REDACTION_PII")
+                    .unwrap_or_else(|_| "true".into())
+                    .parse()?,
+            },
+            outbox_poll_interval_secs: interval_secs("OUTBOX_POLL_INTERVAL_SECS", "5")?,
+            digest_interval_secs: std::env::var("DIGEST_INTERVAL_SECS")
+                .unwrap_or_else(|_| "3600".into())
+                .parse::<u64>()
+                .map(|secs| (secs > 0).then_some(secs))?,
+            rate_limit: RateLimitConfig {
+                conversation_per_minute: std::env::var("RATE_LIMIT_CONVERSATION_PER_MINUTE")
+                    .unwrap_or_else(|_| "30".into())
+                    .parse()?,
+                expensive_per_minute: std::env::var("RATE_LIMIT_EXPENSIVE_PER_MINUTE")
+                    .unwrap_or_else(|_| "6".into())
+                    .parse()?,
+                guest_conversation_per_minute: std::env::var(
+                    "RATE_LIMIT_GUEST_CONVERSATION_PER_MINUTE",
+                )
+                .unwrap_or_else(|_| "10".into())
+                .parse()?,
+                guest_expensive_per_minute: std::env::var("RATE_LIMIT_GUEST_EXPENSIVE_PER_MINUTE")
+                    .unwrap_or_else(|_| "2".into())
+                    .parse()?,
+            },
+            guest: GuestConfig {
+                enabled: std::env::var("ENABLE_GUEST_ACCESS")
+                    .unwrap_or_else(|_| "false".into())
+                    .parse()?,
+                token_ttl_minutes: std::env::var("GUEST_TOKEN_TTL_MINUTES")
+                    .unwrap_or_else(|_| "60".into())
+                    .parse()?,
+                retention_hours: std::env::var("GUEST_RETENTION_HOURS")
+                    .unwrap_or_else(|_| "24".into())
+                    .parse()?,
+                prune_interval_secs: interval_secs("GUEST_PRUNE_INTERVAL_SECS", "600")?,
+                signups_per_hour: std::env::var("GUEST_SIGNUPS_PER_HOUR")
+                    .unwrap_or_else(|_| "10".into())
+                    .parse()?,
+            },
         })
     }
 
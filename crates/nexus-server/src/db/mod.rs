@@ -4,26 +4,43 @@ pub mod postgres;
 pub mod qdrant;
 pub mod redis;
 
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// Delay before the first reconnect attempt at startup; doubles after each
+/// failure up to `MAX_CONNECT_BACKOFF`.
+const CONNECT_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(10);
 
 /// All database connections bundled together.
 #[derive(Clone)]
 pub struct DatabaseConnections {
     pub pg: sqlx::PgPool,
-    pub neo4j: Arc<neo4rs::Graph>,
+    pub neo4j: Arc<self::neo4j::Neo4jClient>,
     pub qdrant: Arc<qdrant_client::Qdrant>,
     pub influx: Arc<influxdb2::Client>,
     pub redis: ::redis::aio::ConnectionManager,
 }
 
 impl DatabaseConnections {
+    /// Connect to every store, each retried until it comes up or
+    /// `STARTUP_MAX_WAIT_SECS` passes, since in compose setups the databases
+    /// often start after us.
     pub async fn connect(config: &crate::config::AppConfig) -> anyhow::Result<Self> {
+        let max_wait = Duration::from_secs(config.startup_max_wait_secs);
         let (pg, neo4j, qdrant, influx, redis) = tokio::try_join!(
-            self::postgres::connect(&config.database_url),
-            self::neo4j::connect(&config.neo4j),
-            self::qdrant::connect(&config.qdrant_url),
-            self::influxdb::connect(&config.influxdb),
-            self::redis::connect(&config.redis_url),
+            wait_for("PostgreSQL", max_wait, || self::postgres::connect(
+                &config.postgres
+            )),
+            wait_for("Neo4j", max_wait, || self::neo4j::connect(&config.neo4j)),
+            wait_for("Qdrant", max_wait, || self::qdrant::connect(&config.qdrant)),
+            wait_for("InfluxDB", max_wait, || self::influxdb::connect(
+                &config.influxdb
+            )),
+            wait_for("Redis", max_wait, || self::redis::connect(
+                &config.redis_url
+            )),
         )?;
 
         Ok(Self {
@@ -35,3 +52,33 @@ impl DatabaseConnections {
         })
     }
 }
+
+/// Retry `connect` with exponential backoff until it succeeds or `max_wait`
+/// has passed, then fail with the last error.
+async fn wait_for<T, F, Fut>(name: &str, max_wait: Duration, mut connect: F) -> anyhow::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let deadline = tokio::time::Instant::now() + max_wait;
+    let mut backoff = CONNECT_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match connect().await {
+            Ok(value) => return Ok(value),
+            Err(e) if tokio::time::Instant::now() + backoff < deadline => {
+                tracing::warn!(
+                    attempt,
+                    retry_in_ms = backoff.as_millis() as u64,
+                    "Waiting for {name}: {e:#}"
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_CONNECT_BACKOFF);
+                attempt += 1;
+            }
+            Err(e) => {
+                return Err(e.context(format!("{name} not available after {max_wait:?}")));
+            }
+        }
+    }
+}
@@ -1,21 +1,110 @@
-use neo4rs::{ConfigBuilder, Graph};
+use std::time::Duration;
+
+use neo4rs::{ConfigBuilder, DetachedRowStream, Graph, Query};
+
+/// Delay before the first retry of a failed read; doubles after each failure.
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
 
 #[derive(Debug, Clone)]
 pub struct Neo4jConfig {
     pub uri: String,
     pub user: String,
     pub password: String,
+    /// Size of the driver's connection pool (`NEO4J_MAX_CONNECTIONS`, 16).
+    pub max_connections: usize,
+    /// How long one attempt at a query may take, including waiting for a
+    /// pooled connection (`NEO4J_QUERY_TIMEOUT_SECS`, 30).
+    pub query_timeout_secs: u64,
+    /// Attempts at a read that fails to connect (`NEO4J_QUERY_ATTEMPTS`, 3).
+    pub query_attempts: u32,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Neo4jError {
+    #[error(transparent)]
+    Driver(#[from] neo4rs::Error),
+    #[error("Neo4j query timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+impl Neo4jError {
+    /// Whether the failure was in reaching Neo4j rather than in the query.
+    /// Timeouts aren't retried: the query may still be running.
+    fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::Driver(neo4rs::Error::IOError { .. } | neo4rs::Error::ConnectionError)
+        )
+    }
+}
+
+/// A pooled Neo4j graph whose queries are bounded by the query timeout.
+/// Only reads are retried on connection failures: a write that failed
+/// mid-flight may already have been applied.
+pub struct Neo4jClient {
+    graph: Graph,
+    query_timeout: Duration,
+    attempts: u32,
+}
+
+impl Neo4jClient {
+    /// Run a query once, discarding any results.
+    pub async fn run(&self, q: Query) -> Result<(), Neo4jError> {
+        self.attempt(self.graph.run(q)).await
+    }
+
+    /// Run a query once and stream its rows.
+    pub async fn execute(&self, q: Query) -> Result<DetachedRowStream, Neo4jError> {
+        self.attempt(self.graph.execute(q)).await
+    }
+
+    /// Run a read-only query and stream its rows, retrying with backoff if
+    /// Neo4j can't be reached. Never pass a query that writes.
+    pub async fn execute_read(&self, q: Query) -> Result<DetachedRowStream, Neo4jError> {
+        let mut backoff = RETRY_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            match self.attempt(self.graph.execute(q.clone())).await {
+                Err(e) if e.is_transient() && attempt < self.attempts => {
+                    tracing::warn!(
+                        attempt,
+                        retry_in_ms = backoff.as_millis() as u64,
+                        "Neo4j read failed, retrying: {e}"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                outcome => return outcome,
+            }
+        }
+    }
+
+    async fn attempt<T>(
+        &self,
+        op: impl Future<Output = Result<T, neo4rs::Error>>,
+    ) -> Result<T, Neo4jError> {
+        match tokio::time::timeout(self.query_timeout, op).await {
+            Ok(result) => result.map_err(Neo4jError::from),
+            Err(_) => Err(Neo4jError::Timeout(self.query_timeout)),
+        }
+    }
 }
 
-pub async fn connect(config: &Neo4jConfig) -> anyhow::Result<Graph> {
+pub async fn connect(config: &Neo4jConfig) -> anyhow::Result<Neo4jClient> {
     let graph_config = ConfigBuilder::default()
         .uri(&config.uri)
         .user(&config.user)
         .password(&config.password)
+        .max_connections(config.max_connections)
         .build()?;
 
     let graph = Graph::connect(graph_config).await?;
 
-    tracing::info!("Neo4j connected");
-    Ok(graph)
+    tracing::info!(max_connections = config.max_connections, "Neo4j connected");
+    Ok(Neo4jClient {
+        graph,
+        query_timeout: Duration::from_secs(config.query_timeout_secs),
+        attempts: config.query_attempts.max(1),
+    })
 }
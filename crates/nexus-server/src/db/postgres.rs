@@ -1,11 +1,27 @@
+use std::time::Duration;
+
 use sqlx::postgres::{PgPool, PgPoolOptions};
 
-pub async fn connect(database_url: &str) -> anyhow::Result<PgPool> {
+#[derive(Debug, Clone)]
+pub struct PostgresConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_secs: u64,
+}
+
+pub async fn connect(config: &PostgresConfig) -> anyhow::Result<PgPool> {
     let pool = PgPoolOptions::new()
-        .max_connections(20)
-        .connect(database_url)
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+        .connect(&config.url)
         .await?;
 
-    tracing::info!("PostgreSQL connected");
+    tracing::info!(
+        max_connections = config.max_connections,
+        min_connections = config.min_connections,
+        "PostgreSQL connected"
+    );
     Ok(pool)
 }
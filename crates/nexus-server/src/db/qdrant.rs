@@ -1,7 +1,55 @@
+use anyhow::Context;
+use std::time::Duration;
+
 use qdrant_client::Qdrant;
+use qdrant_client::qdrant::{
+    CreateCollectionBuilder, CreateFieldIndexCollectionBuilder, Distance, FieldType,
+    ScalarQuantizationBuilder, TextIndexParamsBuilder, TokenizerType, VectorParamsBuilder,
+    VectorsConfigBuilder, vectors_config,
+};
+
+#[derive(Debug, Clone)]
+pub struct QdrantConfig {
+    pub url: String,
+    pub timeout_secs: u64,
+    pub connect_timeout_secs: u64,
+    /// Drop and recreate a collection whose vector size no longer matches the
+    /// embedding model, instead of refusing to start. Deletes its contents.
+    pub recreate_on_dimension_mismatch: bool,
+    /// Store int8-quantized copies of vectors in RAM for search, cutting memory
+    /// roughly fourfold. Only applied when a collection is created.
+    pub scalar_quantization: bool,
+    /// Similarity metric for new collections; existing ones must already use it.
+    pub distance: Distance,
+}
+
+/// Parse `QDRANT_DISTANCE`: `cosine`, `dot` or `euclid`.
+pub fn parse_distance(value: &str) -> anyhow::Result<Distance> {
+    match value.trim().to_lowercase().as_str() {
+        "cosine" => Ok(Distance::Cosine),
+        "dot" => Ok(Distance::Dot),
+        "euclid" => Ok(Distance::Euclid),
+        other => {
+            anyhow::bail!("Unknown QDRANT_DISTANCE '{other}' (expected cosine, dot or euclid)")
+        }
+    }
+}
+
+fn distance_name(distance: Distance) -> &'static str {
+    match distance {
+        Distance::Cosine => "cosine",
+        Distance::Dot => "dot",
+        Distance::Euclid => "euclid",
+        Distance::Manhattan => "manhattan",
+        Distance::UnknownDistance => "unknown",
+    }
+}
 
-pub async fn connect(url: &str) -> anyhow::Result<Qdrant> {
-    let client = Qdrant::from_url(url).build()?;
+pub async fn connect(config: &QdrantConfig) -> anyhow::Result<Qdrant> {
+    let client = Qdrant::from_url(&config.url)
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+        .build()?;
 
     // Verify connectivity by listing collections.
     client.list_collections().await?;
@@ -9,3 +57,159 @@ pub async fn connect(url: &str) -> anyhow::Result<Qdrant> {
     tracing::info!("Qdrant connected");
     Ok(client)
 }
+
+/// Create `collection` with a single named vector of `size` dimensions,
+/// optionally with int8 scalar quantization.
+pub async fn create_collection(
+    client: &Qdrant,
+    collection: &str,
+    vector: &str,
+    size: u64,
+    distance: Distance,
+    quantize: bool,
+) -> anyhow::Result<()> {
+    let mut vectors = VectorsConfigBuilder::default();
+    vectors.add_named_vector_params(vector, VectorParamsBuilder::new(size, distance));
+
+    let mut create = CreateCollectionBuilder::new(collection).vectors_config(vectors);
+    if quantize {
+        create = create.quantization_config(ScalarQuantizationBuilder::default());
+    }
+    client
+        .create_collection(create)
+        .await
+        .with_context(|| format!("Failed to create Qdrant collection {collection}"))?;
+    Ok(())
+}
+
+/// A full-text index on `field` for keyword search: lowercased words of two
+/// or more characters. Creating an index that already exists is a no-op.
+pub async fn ensure_text_index(
+    client: &Qdrant,
+    collection: &str,
+    field: &str,
+) -> anyhow::Result<()> {
+    client
+        .create_field_index(
+            CreateFieldIndexCollectionBuilder::new(collection, field, FieldType::Text)
+                .field_index_params(
+                    TextIndexParamsBuilder::new(TokenizerType::Word)
+                        .lowercase(true)
+                        .min_token_len(2),
+                ),
+        )
+        .await
+        .with_context(|| {
+            format!("Failed to create {field} text index on Qdrant collection {collection}")
+        })?;
+    Ok(())
+}
+
+/// Keyword payload indexes on `fields`, so filters on them (`user_id` above
+/// all) narrow the search up front instead of scanning every point. Creating
+/// an index that already exists is a no-op in Qdrant.
+pub async fn ensure_keyword_indexes(
+    client: &Qdrant,
+    collection: &str,
+    fields: &[&str],
+) -> anyhow::Result<()> {
+    for field in fields {
+        client
+            .create_field_index(CreateFieldIndexCollectionBuilder::new(
+                collection,
+                *field,
+                FieldType::Keyword,
+            ))
+            .await
+            .with_context(|| {
+                format!("Failed to create {field} index on Qdrant collection {collection}")
+            })?;
+    }
+    Ok(())
+}
+
+/// Compare an existing collection's vector size with the embedding dimension,
+/// and its distance metric with `QDRANT_DISTANCE`.
+///
+/// `vector` names the vector to check; a collection with a single unnamed
+/// vector is checked against that instead. A different metric fails with an
+/// actionable error, since Qdrant can't change it in place. A size mismatch
+/// does too, or, with `recreate_on_dimension_mismatch`, deletes the
+/// collection and returns `true` so the caller creates it afresh.
+pub async fn drop_on_vector_mismatch(
+    client: &Qdrant,
+    config: &QdrantConfig,
+    collection: &str,
+    vector: &str,
+    expected: u64,
+) -> anyhow::Result<bool> {
+    let info = client
+        .collection_info(collection)
+        .await
+        .with_context(|| format!("Failed to inspect Qdrant collection {collection}"))?;
+
+    let params = match info
+        .result
+        .and_then(|r| r.config)
+        .and_then(|c| c.params)
+        .and_then(|p| p.vectors_config)
+        .and_then(|v| v.config)
+    {
+        Some(vectors_config::Config::Params(params)) => Some(params),
+        Some(vectors_config::Config::ParamsMap(mut map)) => map.map.remove(vector),
+        None => None,
+    };
+
+    let Some(params) = params else {
+        tracing::warn!(
+            collection,
+            "Could not determine Qdrant collection vector size"
+        );
+        return Ok(false);
+    };
+
+    let distance = params.distance();
+    if distance != config.distance {
+        anyhow::bail!(
+            "Qdrant collection {collection} uses {} distance but QDRANT_DISTANCE is {}. \
+             The metric of an existing collection can't be changed: delete the collection \
+             to have it recreated (its contents will be lost), or set QDRANT_DISTANCE={}.",
+            distance_name(distance),
+            distance_name(config.distance),
+            distance_name(distance),
+        );
+    }
+
+    let detected = params.size;
+
+    tracing::info!(
+        collection,
+        detected,
+        expected,
+        "Qdrant collection vector size"
+    );
+    if detected == expected {
+        return Ok(false);
+    }
+
+    if !config.recreate_on_dimension_mismatch {
+        anyhow::bail!(
+            "Qdrant collection {collection} stores {detected}-dimensional vectors but the \
+             embedding model produces {expected}. Set QDRANT_RECREATE_ON_DIMENSION_MISMATCH=true \
+             to drop and recreate it (its contents will be lost), or switch back to the \
+             previous embedding model."
+        );
+    }
+
+    tracing::warn!(
+        collection,
+        detected,
+        expected,
+        "Dropping Qdrant collection with mismatched vector size"
+    );
+    client
+        .delete_collection(collection)
+        .await
+        .with_context(|| format!("Failed to delete Qdrant collection {collection}"))?;
+    Ok(true)
+}
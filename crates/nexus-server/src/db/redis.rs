@@ -1,5 +1,7 @@
 use ::redis::aio::ConnectionManager;
 
+use crate::shared::metrics;
+
 pub async fn connect(redis_url: &str) -> anyhow::Result<ConnectionManager> {
     let client = ::redis::Client::open(redis_url)?;
     let manager = ConnectionManager::new(client).await?;
@@ -7,3 +9,9 @@ pub async fn connect(redis_url: &str) -> anyhow::Result<ConnectionManager> {
     tracing::info!("Redis connected");
     Ok(manager)
 }
+
+/// Log a Redis backend failure and count it in `nexus_redis_backend_errors_total`.
+pub fn record_backend_error(operation: &str, error: &::redis::RedisError) {
+    metrics::REDIS_BACKEND_ERRORS.inc();
+    tracing::warn!(operation, "Redis backend error: {error}");
+}
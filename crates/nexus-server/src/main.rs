@@ -5,6 +5,8 @@ mod models;
 mod perspective;
 mod river;
 mod shared;
+#[cfg(test)]
+mod test_support;
 
 use tracing_subscriber::{EnvFilter, fmt};
 
@@ -34,13 +36,82 @@ async fn main() -> anyhow::Result<()> {
     sqlx::migrate!("../../migrations").run(&db.pg).await?;
     tracing::info!("PostgreSQL migrations applied");
 
-    // Ensure Qdrant collection exists.
-    river::episodic::ensure_collection(&api::state::AppState::new(db.clone(), config.clone()))
-        .await?;
+    // Build application state.
+    let (jobs, job_rx) = api::jobs::channel(config.job_queue_capacity);
+    let state = api::state::AppState::new(db, config.clone(), jobs)?;
+
+    // Fail fast when a configured model isn't pulled. An unreachable host
+    // may still be starting, so that only warns.
+    match state.llm.health().await {
+        Ok(true) => tracing::info!("LLM backend ready"),
+        Ok(false) => tracing::warn!("LLM backend not healthy at startup"),
+        Err(e) if e.is::<shared::ollama::ModelNotFound>() => return Err(e),
+        Err(e) => tracing::warn!("LLM backend unreachable at startup: {e:#}"),
+    }
+
+    // Start background workers for async chat jobs.
+    api::jobs::spawn_workers(state.clone(), job_rx, config.job_workers);
+
+    // Load models in the background so the first request skips the cold start.
+    if config.ollama_warmup {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let (llm, embed) = tokio::join!(state.llm.warm_up(), state.embeddings.warm_up());
+            match (llm, embed) {
+                (Ok(()), Ok(())) => tracing::info!("Models warmed up"),
+                (llm, embed) => {
+                    if let Err(e) = llm {
+                        tracing::warn!("LLM warm-up failed: {e:#}");
+                    }
+                    if let Err(e) = embed {
+                        tracing::warn!("Embedding warm-up failed: {e:#}");
+                    }
+                }
+            }
+        });
+    }
+
+    // Ensure Qdrant collections exist.
+    river::episodic::ensure_collection(&state).await?;
+    river::beliefs::ensure_collection(&state).await?;
     tracing::info!("Qdrant collections initialized");
 
-    // Build application state.
-    let state = api::state::AppState::new(db, config.clone());
+    // Periodically prune expired episodic memories.
+    if let Some(days) = config.episodic_retention_days {
+        match config.episodic_prune_interval_secs {
+            Some(secs) => {
+                tokio::spawn(river::episodic::run_pruning(
+                    state.clone(),
+                    days,
+                    std::time::Duration::from_secs(secs),
+                ));
+                tracing::info!("Episodic memory retention: {days} days");
+            }
+            None => tracing::warn!(
+                "EPISODIC_RETENTION_DAYS is set but EPISODIC_PRUNE_INTERVAL_SECS is 0; memories will not be pruned"
+            ),
+        }
+    }
+
+    // Delete guest accounts past their retention window.
+    if config.guest.enabled {
+        tokio::spawn(api::guests::run_pruning(state.clone()));
+        tracing::info!("Guest access enabled");
+    }
+
+    // Apply queued cross-store writes.
+    tokio::spawn(api::outbox::run_processor(
+        state.clone(),
+        std::time::Duration::from_secs(config.outbox_poll_interval_secs),
+    ));
+
+    // Write end-of-day digests for active users.
+    if let Some(secs) = config.digest_interval_secs {
+        tokio::spawn(river::digest::run_scheduler(
+            state.clone(),
+            std::time::Duration::from_secs(secs),
+        ));
+    }
 
     // Build the router.
     let app = api::build_router(state);
@@ -50,7 +121,12 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("Listening on {bind_addr}");
 
     let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
-    axum::serve(listener, app).await?;
+    // Client addresses are needed to rate-limit guest signups.
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await?;
 
     Ok(())
 }
@@ -1,46 +1,142 @@
 use chrono::{Duration, Utc};
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub secret: String,
+    pub expiry_hours: u64,
+    /// Only HMAC algorithms (HS256/384/512) are supported for now.
+    pub algorithm: Algorithm,
+    /// When set, issued tokens carry `iss` and verification requires it.
+    pub issuer: Option<String>,
+    /// When set, issued tokens carry `aud` and verification requires it.
+    pub audience: Option<String>,
+}
+
+/// Anonymous demo accounts issued by `POST /api/v1/auth/guest`.
+#[derive(Debug, Clone)]
+pub struct GuestConfig {
+    /// Off unless `ENABLE_GUEST_ACCESS` is set.
+    pub enabled: bool,
+    /// Lifetime of a guest token.
+    pub token_ttl_minutes: u64,
+    /// Guest accounts, and everything stored for them, are deleted this long
+    /// after they were created.
+    pub retention_hours: u64,
+    /// How often expired guests are deleted; must be at least 1.
+    pub prune_interval_secs: u64,
+    /// Guest accounts one client IP may create per hour; 0 is unlimited.
+    pub signups_per_hour: u32,
+}
+
+/// Authorization role, stored in `users.role`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[default]
+    User,
+    Admin,
+}
+
+impl Role {
+    /// Parse the `users.role` column; unknown values get the least privilege.
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "admin" => Role::Admin,
+            _ => Role::User,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: Uuid,
     pub username: String,
+    /// Tokens issued before roles existed have no role claim.
+    #[serde(default)]
+    pub role: Role,
+    /// Issued to an ephemeral guest account.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub guest: bool,
     pub exp: usize,
     pub iat: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
 }
 
 pub fn create_token(
     user_id: Uuid,
     username: &str,
-    secret: &str,
-    expiry_hours: u64,
+    role: Role,
+    config: &JwtConfig,
+) -> anyhow::Result<String> {
+    let ttl = Duration::hours(config.expiry_hours as i64);
+    issue_token(user_id, username, role, false, ttl, config)
+}
+
+/// A token for a guest account, valid for `ttl_minutes`.
+pub fn create_guest_token(
+    user_id: Uuid,
+    username: &str,
+    ttl_minutes: u64,
+    config: &JwtConfig,
+) -> anyhow::Result<String> {
+    let ttl = Duration::minutes(ttl_minutes as i64);
+    issue_token(user_id, username, Role::User, true, ttl, config)
+}
+
+fn issue_token(
+    user_id: Uuid,
+    username: &str,
+    role: Role,
+    guest: bool,
+    ttl: Duration,
+    config: &JwtConfig,
 ) -> anyhow::Result<String> {
     let now = Utc::now();
-    let exp = now + Duration::hours(expiry_hours as i64);
+    let exp = now + ttl;
 
     let claims = Claims {
         sub: user_id,
         username: username.to_string(),
+        role,
+        guest,
         exp: exp.timestamp() as usize,
         iat: now.timestamp() as usize,
+        iss: config.issuer.clone(),
+        aud: config.audience.clone(),
     };
 
     let token = encode(
-        &Header::default(),
+        &Header::new(config.algorithm),
         &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
+        &EncodingKey::from_secret(config.secret.as_bytes()),
     )?;
 
     Ok(token)
 }
 
-pub fn verify_token(token: &str, secret: &str) -> anyhow::Result<Claims> {
+pub fn verify_token(token: &str, config: &JwtConfig) -> anyhow::Result<Claims> {
+    let mut validation = Validation::new(config.algorithm);
+    let mut required = vec!["exp"];
+    if let Some(issuer) = &config.issuer {
+        validation.set_issuer(&[issuer]);
+        required.push("iss");
+    }
+    if let Some(audience) = &config.audience {
+        validation.set_audience(&[audience]);
+        required.push("aud");
+    }
+    validation.set_required_spec_claims(&required);
+
     let token_data = decode::<Claims>(
         token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
+        &DecodingKey::from_secret(config.secret.as_bytes()),
+        &validation,
     )?;
 
     Ok(token_data.claims)
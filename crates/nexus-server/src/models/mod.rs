@@ -1,3 +1,4 @@
 pub mod auth;
+pub mod password;
 pub mod requests;
 pub mod responses;
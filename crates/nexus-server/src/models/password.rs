@@ -0,0 +1,111 @@
+use anyhow::{Result, anyhow};
+use argon2::Argon2;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use uuid::Uuid;
+
+/// Outcome of checking a password against a stored hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordCheck {
+    Invalid,
+    Valid,
+    /// Correct, but stored in the old unsalted format; rehash it.
+    ValidLegacy,
+}
+
+/// Hash `password` with Argon2id and a fresh random salt, as a PHC string.
+/// Runs on the blocking pool, since each hash takes tens of milliseconds.
+pub async fn hash_password(password: &str) -> Result<String> {
+    let password = password.to_owned();
+    tokio::task::spawn_blocking(move || {
+        // A v4 UUID is 122 random bits from the OS generator.
+        let salt = SaltString::encode_b64(Uuid::new_v4().as_bytes())
+            .map_err(|e| anyhow!("Failed to encode salt: {e}"))?;
+        let hash = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| anyhow!("Failed to hash password: {e}"))?;
+        Ok(hash.to_string())
+    })
+    .await?
+}
+
+/// Check `password` against `stored`, an Argon2 PHC string or, for accounts
+/// created before Argon2, the legacy unsalted hash. Guest accounts and other
+/// placeholders never match.
+pub async fn verify_password(password: &str, stored: &str) -> Result<PasswordCheck> {
+    if !stored.starts_with("$argon2") {
+        let check = if !stored.is_empty() && legacy_hash(password.as_bytes()) == stored {
+            PasswordCheck::ValidLegacy
+        } else {
+            PasswordCheck::Invalid
+        };
+        return Ok(check);
+    }
+
+    let password = password.to_owned();
+    let stored = stored.to_owned();
+    tokio::task::spawn_blocking(move || {
+        let Ok(hash) = PasswordHash::new(&stored) else {
+            return PasswordCheck::Invalid;
+        };
+        match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => PasswordCheck::Valid,
+            Err(_) => PasswordCheck::Invalid,
+        }
+    })
+    .await
+    .map_err(Into::into)
+}
+
+/// The unsalted hash passwords were stored with before Argon2. Only used to
+/// accept, and then replace, those hashes at login.
+fn legacy_hash(data: &[u8]) -> String {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn hashes_are_salted_and_verify() {
+        let a = hash_password("correct horse").await.unwrap();
+        let b = hash_password("correct horse").await.unwrap();
+        assert!(a.starts_with("$argon2id$"));
+        assert_ne!(a, b);
+        assert_eq!(
+            verify_password("correct horse", &a).await.unwrap(),
+            PasswordCheck::Valid
+        );
+        assert_eq!(
+            verify_password("battery staple", &a).await.unwrap(),
+            PasswordCheck::Invalid
+        );
+    }
+
+    #[tokio::test]
+    async fn legacy_hashes_verify_for_rehashing() {
+        let legacy = legacy_hash(b"hunter22");
+        assert_eq!(
+            verify_password("hunter22", &legacy).await.unwrap(),
+            PasswordCheck::ValidLegacy
+        );
+        assert_eq!(
+            verify_password("hunter23", &legacy).await.unwrap(),
+            PasswordCheck::Invalid
+        );
+    }
+
+    #[tokio::test]
+    async fn placeholders_never_match() {
+        for stored in ["", "!"] {
+            assert_eq!(
+                verify_password("", stored).await.unwrap(),
+                PasswordCheck::Invalid
+            );
+        }
+    }
+}
@@ -1,18 +1,79 @@
+use nexus_common::error::NexusError;
 use nexus_common::types::ChatMode;
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::river::dialogue::QuestionStyle;
+use crate::river::episodic::RecallScope;
+
 #[derive(Debug, Deserialize)]
 pub struct ChatRequest {
     pub message: String,
-    #[serde(default)]
-    pub mode: ChatMode,
+    /// `None` uses the server's `DEFAULT_CHAT_MODE`.
+    pub mode: Option<ChatMode>,
     pub session_id: Option<Uuid>,
+    /// Also return why River asked what it did. Set by `?explain=true`.
+    #[serde(default)]
+    pub explain: bool,
+    /// `user` (default) recalls memories from every session, `session` only
+    /// from this one.
+    #[serde(default)]
+    pub recall_scope: RecallScope,
+    /// `single` (default), `layered` or `guided`; how Socratic mode asks.
+    #[serde(default)]
+    pub question_style: QuestionStyle,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ChatQuery {
+    /// Queue the turn as a background job instead of waiting for it.
+    #[serde(rename = "async", default)]
+    pub run_async: bool,
+    /// Include a rationale for River's question in the response.
+    #[serde(default)]
+    pub explain: bool,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct AnalyzeRequest {
     pub text: String,
+    /// How aggressively the synthesis layer challenges the text, 0–1.
+    #[serde(default = "default_intensity")]
+    pub intensity: f64,
+    /// Preceding passages that help interpret `text`. The model sees them,
+    /// but only `text` is analysed and stored.
+    #[serde(default)]
+    pub context: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyzeClaimsRequest {
+    pub claims: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyzeDiffRequest {
+    pub text_a: String,
+    pub text_b: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditMessageRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EditMessageQuery {
+    /// Re-run the engine on the edited message, replacing the reply and
+    /// whatever was derived from the original text.
+    #[serde(default)]
+    pub reprocess: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayRequest {
+    /// Mode to run the stored message through.
+    pub mode: ChatMode,
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,8 +83,382 @@ pub struct RegisterRequest {
     pub password: String,
 }
 
+/// Changes to the caller's own profile; omitted fields are left as they are.
+#[derive(Debug, Deserialize)]
+pub struct UpdateProfileRequest {
+    pub username: Option<String>,
+    pub email: Option<String>,
+}
+
+/// A JSON array of users to create, each shaped like a registration.
+#[derive(Debug, Deserialize)]
+#[serde(transparent)]
+pub struct BulkUserImportRequest {
+    pub users: Vec<RegisterRequest>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct BeliefSearchRequest {
+    pub query: String,
+    #[serde(default = "default_search_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BeliefExtractRequest {
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BeliefMergeRequest {
+    /// Belief that survives the merge.
+    pub keep_id: Uuid,
+    /// Belief folded into `keep_id` and deleted.
+    pub merge_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BeliefReviseRequest {
+    pub belief_id: Uuid,
+    /// Added to the belief's confidence, which is then clamped to 0–1.
+    pub delta: f64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AnalyzeQuery {
+    /// Only return findings at least this confident, 0–1.
+    pub min_confidence: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalysisSearchQuery {
+    /// Web-style search: words, `"quoted phrases"`, `or`, `-excluded`.
+    pub q: String,
+    #[serde(default = "default_search_limit")]
+    pub limit: usize,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AnalysisStatsQuery {
+    /// Only aggregate analyses created at or after this time.
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BeliefsQuery {
+    /// Only return beliefs in this category.
+    pub category: Option<String>,
+    /// `next_cursor` from the previous page.
+    pub cursor: Option<String>,
+    /// Page size; all beliefs are returned when omitted.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BeliefAuditQuery {
+    /// `next_before` from the previous page.
+    pub before: Option<i64>,
+    /// Page size, at most 100.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ContradictionsQuery {
+    /// Only contradictions not yet resolved.
+    #[serde(default)]
+    pub unresolved: bool,
+    /// At most 100.
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContradictionResolveRequest {
+    pub belief_a_id: Uuid,
+    pub belief_b_id: Uuid,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DigestQuery {
+    /// UTC day to fetch, `YYYY-MM-DD`; defaults to yesterday.
+    pub date: Option<chrono::NaiveDate>,
+}
+
+fn default_intensity() -> f64 {
+    crate::perspective::synthesis::DEFAULT_INTENSITY
+}
+
+fn default_search_limit() -> usize {
+    10
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Json,
+    Graphml,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphExportQuery {
+    #[serde(default)]
+    pub format: GraphFormat,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalysisExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+impl ChatRequest {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        validate_text("message", &self.message, max_chars)
+    }
+
+    /// The requested mode, or `default` if the request didn't name one.
+    pub fn mode_or(&self, default: ChatMode) -> ChatMode {
+        self.mode.unwrap_or(default)
+    }
+}
+
+/// Most context passages an analysis request may carry.
+pub const MAX_CONTEXT_PASSAGES: usize = 10;
+
+impl AnalyzeRequest {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        validate_text("text", &self.text, max_chars)?;
+        if !(0.0..=1.0).contains(&self.intensity) {
+            return Err(NexusError::Validation(
+                "intensity must be between 0 and 1".into(),
+            ));
+        }
+        if self.context.len() > MAX_CONTEXT_PASSAGES {
+            return Err(NexusError::Validation(format!(
+                "context must contain at most {MAX_CONTEXT_PASSAGES} passages"
+            )));
+        }
+        for (i, passage) in self.context.iter().enumerate() {
+            validate_text(&format!("context[{i}]"), passage, max_chars)?;
+        }
+        // Context shares the prompt with the text, so it shares the budget too.
+        let total: usize = self.context.iter().map(|p| p.chars().count()).sum();
+        if total > max_chars {
+            return Err(NexusError::Validation(format!(
+                "context must be at most {max_chars} characters in total (got {total})"
+            )));
+        }
+        Ok(())
+    }
+
+    /// The text and its context, for moderation.
+    pub fn full_text(&self) -> String {
+        let mut parts: Vec<&str> = self.context.iter().map(String::as_str).collect();
+        parts.push(&self.text);
+        parts.join("\n\n")
+    }
+}
+
+impl AnalyzeQuery {
+    pub fn validate(&self) -> Result<(), NexusError> {
+        match self.min_confidence {
+            Some(min) if !(0.0..=1.0).contains(&min) => Err(NexusError::Validation(
+                "min_confidence must be between 0 and 1".into(),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Most claims `/analyze/claims` takes at once; each costs two LLM calls.
+pub const MAX_CLAIMS: usize = 20;
+
+impl AnalyzeClaimsRequest {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        if self.claims.is_empty() || self.claims.len() > MAX_CLAIMS {
+            return Err(NexusError::Validation(format!(
+                "claims must contain between 1 and {MAX_CLAIMS} entries"
+            )));
+        }
+        for (i, claim) in self.claims.iter().enumerate() {
+            validate_text(&format!("claims[{i}]"), claim, max_chars)?;
+        }
+        Ok(())
+    }
+}
+
+impl AnalyzeDiffRequest {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        validate_text("text_a", &self.text_a, max_chars)?;
+        validate_text("text_b", &self.text_b, max_chars)
+    }
+}
+
+impl AnalysisSearchQuery {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        validate_text("q", &self.q, max_chars)?;
+        if !(1..=100).contains(&self.limit) {
+            return Err(NexusError::Validation(
+                "limit must be between 1 and 100".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl BeliefExtractRequest {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        validate_text("message", &self.message, max_chars)
+    }
+}
+
+impl BeliefMergeRequest {
+    pub fn validate(&self) -> Result<(), NexusError> {
+        if self.keep_id == self.merge_id {
+            return Err(NexusError::Validation(
+                "keep_id and merge_id must be different beliefs".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl BeliefReviseRequest {
+    pub fn validate(&self) -> Result<(), NexusError> {
+        if !(-1.0..=1.0).contains(&self.delta) || self.delta == 0.0 {
+            return Err(NexusError::Validation(
+                "delta must be non-zero and between -1 and 1".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl BeliefSearchRequest {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        validate_text("query", &self.query, max_chars)?;
+        if !(1..=100).contains(&self.limit) {
+            return Err(NexusError::Validation(
+                "limit must be between 1 and 100".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl RegisterRequest {
+    pub fn validate(&self) -> Result<(), NexusError> {
+        validate_text("username", &self.username, 255)?;
+        validate_text("email", &self.email, 255)?;
+        if !is_valid_email(&normalize_email(&self.email)) {
+            return Err(NexusError::Validation(format!(
+                "'{}' is not a valid email address",
+                self.email
+            )));
+        }
+        if self.password.is_empty() {
+            return Err(NexusError::Validation("password must not be empty".into()));
+        }
+        Ok(())
+    }
+}
+
+impl UpdateProfileRequest {
+    pub fn validate(&self) -> Result<(), NexusError> {
+        if self.username.is_none() && self.email.is_none() {
+            return Err(NexusError::Validation(
+                "at least one of username or email must be given".into(),
+            ));
+        }
+        if let Some(username) = &self.username {
+            validate_text("username", username, 255)?;
+        }
+        if let Some(email) = &self.email {
+            validate_text("email", email, 255)?;
+            if !is_valid_email(&normalize_email(email)) {
+                return Err(NexusError::Validation(format!(
+                    "'{email}' is not a valid email address"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl EditMessageRequest {
+    pub fn validate(&self, max_chars: usize) -> Result<(), NexusError> {
+        validate_text("content", &self.content, max_chars)
+    }
+}
+
+/// Most users one bulk import may create.
+pub const MAX_BULK_USERS: usize = 1000;
+
+impl BulkUserImportRequest {
+    /// Checks the batch as a whole; rows are validated one by one on import.
+    pub fn validate(&self) -> Result<(), NexusError> {
+        if self.users.is_empty() || self.users.len() > MAX_BULK_USERS {
+            return Err(NexusError::Validation(format!(
+                "users must contain between 1 and {MAX_BULK_USERS} entries"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl LoginRequest {
+    pub fn validate(&self) -> Result<(), NexusError> {
+        validate_text("email", &self.email, 255)?;
+        if self.password.is_empty() {
+            return Err(NexusError::Validation("password must not be empty".into()));
+        }
+        Ok(())
+    }
+}
+
+/// Check that a text field is non-blank and at most `max_chars` characters.
+fn validate_text(field: &str, value: &str, max_chars: usize) -> Result<(), NexusError> {
+    if value.trim().is_empty() {
+        return Err(NexusError::Validation(format!("{field} must not be empty")));
+    }
+    let len = value.chars().count();
+    if len > max_chars {
+        return Err(NexusError::Validation(format!(
+            "{field} must be at most {max_chars} characters (got {len})"
+        )));
+    }
+    Ok(())
+}
+
+/// Canonical form used for storage and lookup: trimmed and lowercased.
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Minimal structural email check: `local@domain.tld` with no whitespace.
+pub fn is_valid_email(email: &str) -> bool {
+    let Some((local, domain)) = email.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && !domain.contains('@')
+        && !email.chars().any(char::is_whitespace)
+        && domain
+            .split_once('.')
+            .is_some_and(|(name, tld)| !name.is_empty() && !tld.is_empty())
+        && !domain.ends_with('.')
+}
@@ -1,8 +1,32 @@
-use nexus_common::types::{AnalysisResult, Belief, ConsciousnessState, Contradiction};
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use nexus_common::types::{
+    AnalysisDiff, AnalysisMatch, AnalysisResult, AnalysisStats, Belief, BeliefAuditEntry,
+    BeliefCategoryCount, BeliefEdge, BeliefSummary, ConsciousnessState, Contradiction,
+    ContradictionRecord, Digest, Message, ScoredBelief,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
+use crate::models::auth::Role;
+use crate::perspective::engine::ClaimAnalysis;
+use crate::river::beliefs::ExtractedClaim;
+
 #[derive(Debug, Serialize)]
+pub struct SessionMessagesResponse {
+    pub session_id: Uuid,
+    pub messages: Vec<Message>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EditMessageResponse {
+    pub message: Message,
+    /// The new reply, when the message was reprocessed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply: Option<ChatResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ChatResponse {
     pub session_id: Uuid,
     pub message: String,
@@ -13,6 +37,70 @@ pub struct ChatResponse {
     pub contradictions: Option<Vec<Contradiction>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub beliefs_updated: Option<Vec<Belief>>,
+    /// Consciousness metrics computed during this turn.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consciousness: Option<ConsciousnessState>,
+    /// What prompted River's question, when requested with `?explain=true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rationale: Option<String>,
+    /// Steps that failed without failing the turn; the reply may be based on
+    /// incomplete context.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobAcceptedResponse {
+    pub job_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkUserImportResponse {
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// One entry per submitted row, in order.
+    pub results: Vec<BulkUserImportRow>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkUserImportRow {
+    pub index: usize,
+    pub email: String,
+    pub status: BulkImportStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkImportStatus {
+    Created,
+    /// The username or email is already taken.
+    Skipped,
+    /// The row failed validation or couldn't be inserted.
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobResponse {
+    pub job_id: Uuid,
+    pub status: JobStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<ChatResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -20,11 +108,115 @@ pub struct AnalyzeResponse {
     pub analysis: AnalysisResult,
 }
 
+#[derive(Debug, Serialize)]
+pub struct AnalyzeClaimsResponse {
+    /// Results keyed by claim, trimmed; repeated claims are analysed once.
+    pub results: BTreeMap<String, ClaimAnalysis>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalysisSearchResponse {
+    pub matches: Vec<AnalysisMatch>,
+    pub total: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalysisStatsResponse {
+    pub user_id: Uuid,
+    #[serde(flatten)]
+    pub stats: AnalysisStats,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnalyzeDiffResponse {
+    pub diff: AnalysisDiff,
+}
+
 #[derive(Debug, Serialize)]
 pub struct BeliefsResponse {
     pub user_id: Uuid,
     pub beliefs: Vec<Belief>,
     pub total: usize,
+    /// Pass as `cursor` to fetch the next page; absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefAuditResponse {
+    pub user_id: Uuid,
+    pub entries: Vec<BeliefAuditEntry>,
+    /// Pass as `before` to fetch older entries; absent on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_before: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefSearchResponse {
+    pub query: String,
+    pub results: Vec<ScoredBelief>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefReviseResponse {
+    pub belief: Belief,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefMergeResponse {
+    pub belief: Belief,
+    pub merged_id: Uuid,
+    /// Relationships moved from the merged belief onto `belief`.
+    pub relationships_moved: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContradictionsResponse {
+    pub user_id: Uuid,
+    pub contradictions: Vec<ContradictionRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ContradictionResponse {
+    pub contradiction: ContradictionRecord,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefImportResponse {
+    pub user_id: Uuid,
+    /// The imported beliefs, under their new ids.
+    pub beliefs: Vec<Belief>,
+    /// Relationships recreated between the imported beliefs.
+    pub relationships: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefExtractResponse {
+    pub claims: Vec<ExtractedClaim>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefCategoriesResponse {
+    pub user_id: Uuid,
+    pub categories: Vec<BeliefCategoryCount>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DigestResponse {
+    pub digest: Digest,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefSummaryResponse {
+    pub user_id: Uuid,
+    pub summary: BeliefSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BeliefGraphResponse {
+    pub user_id: Uuid,
+    pub nodes: Vec<Belief>,
+    pub edges: Vec<BeliefEdge>,
 }
 
 #[derive(Debug, Serialize)]
@@ -32,6 +224,13 @@ pub struct ConsciousnessResponse {
     pub state: ConsciousnessState,
 }
 
+#[derive(Debug, Serialize)]
+pub struct ConsciousnessBackfillResponse {
+    pub user_id: Uuid,
+    /// One recomputed snapshot per session, oldest first.
+    pub snapshots: Vec<ConsciousnessState>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,
@@ -39,6 +238,16 @@ pub struct AuthResponse {
     pub username: String,
 }
 
+/// The caller's own account, as stored.
+#[derive(Debug, Serialize)]
+pub struct UserProfile {
+    pub id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub role: Role,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct HealthResponse {
     pub status: String,
@@ -55,11 +264,47 @@ pub struct HealthServices {
     pub ollama: ServiceStatus,
 }
 
+impl HealthServices {
+    pub fn all_up(&self) -> bool {
+        [
+            &self.postgres,
+            &self.neo4j,
+            &self.qdrant,
+            &self.influxdb,
+            &self.redis,
+            &self.ollama,
+        ]
+        .iter()
+        .all(|s| s.status == "up")
+    }
+
+    /// Whether any service is unreachable (degraded services still count as available).
+    pub fn any_down(&self) -> bool {
+        [
+            &self.postgres,
+            &self.neo4j,
+            &self.qdrant,
+            &self.influxdb,
+            &self.redis,
+            &self.ollama,
+        ]
+        .iter()
+        .any(|s| s.status == "down")
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct LivenessResponse {
+    pub status: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ServiceStatus {
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
 }
 
 impl ServiceStatus {
@@ -67,6 +312,7 @@ impl ServiceStatus {
         Self {
             status: "up".into(),
             error: None,
+            details: None,
         }
     }
 
@@ -74,6 +320,7 @@ impl ServiceStatus {
         Self {
             status: "down".into(),
             error: Some(error),
+            details: None,
         }
     }
 }
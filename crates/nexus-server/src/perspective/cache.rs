@@ -1,58 +1,149 @@
-use anyhow::{Context, Result};
+use lru::LruCache;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use crate::api::state::AppState;
+use crate::db::redis::record_backend_error;
 use nexus_common::types::AnalysisResult;
 
 /// Cache analysis results in Redis with a TTL of 1 hour.
 const CACHE_TTL_SECS: u64 = 3600;
 
-/// Generate a cache key for a given text input.
-fn cache_key(text: &str) -> String {
+/// In-process LRU in front of Redis for hot inputs, sized by `ANALYSIS_LRU_SIZE`
+/// (0 disables it). Entries share the Redis key and expire with the Redis
+/// entry they mirror, so the two layers never disagree.
+#[derive(Clone)]
+pub struct LocalCache {
+    entries: Option<Arc<Mutex<LruCache<String, LocalEntry>>>>,
+}
+
+struct LocalEntry {
+    result: AnalysisResult,
+    expires_at: Instant,
+}
+
+impl LocalCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: NonZeroUsize::new(capacity)
+                .map(|cap| Arc::new(Mutex::new(LruCache::new(cap)))),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<AnalysisResult> {
+        let mut entries = self.entries.as_ref()?.lock().ok()?;
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.result.clone()),
+            Some(_) => {
+                entries.pop(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn put(&self, key: String, result: &AnalysisResult, ttl: Duration) {
+        let Some(entries) = &self.entries else {
+            return;
+        };
+        if let Ok(mut entries) = entries.lock() {
+            entries.put(
+                key,
+                LocalEntry {
+                    result: result.clone(),
+                    expires_at: Instant::now() + ttl,
+                },
+            );
+        }
+    }
+}
+
+/// Generate a cache key for a given text input, its context passages and
+/// synthesis intensity.
+fn cache_key(text: &str, context: &[String], intensity: f64) -> String {
     let mut hasher = DefaultHasher::new();
     text.hash(&mut hasher);
+    context.hash(&mut hasher);
+    intensity.to_bits().hash(&mut hasher);
     let hash = hasher.finish();
     format!("analysis:{hash:x}")
 }
 
-/// Try to retrieve a cached analysis result.
-pub async fn get_cached(state: &AppState, text: &str) -> Result<Option<AnalysisResult>> {
-    let mut conn = state.db.redis.clone();
-    let key = cache_key(text);
+/// Try to retrieve a cached analysis result, from the in-process LRU first.
+///
+/// An unreachable or failing Redis, or an entry that no longer deserializes,
+/// is logged and treated as a miss: the cache never fails an analysis.
+pub async fn get_cached(
+    state: &AppState,
+    text: &str,
+    context: &[String],
+    intensity: f64,
+) -> Option<AnalysisResult> {
+    let key = cache_key(text, context, intensity);
+    if let Some(result) = state.analysis_cache.get(&key) {
+        tracing::debug!("Local cache hit for analysis");
+        return Some(result);
+    }
 
-    let raw: Option<String> = redis::cmd("GET")
+    let mut conn = state.db.redis.clone();
+    // The remaining TTL bounds how long the local copy may live.
+    let (raw, ttl_ms): (Option<String>, i64) = redis::pipe()
+        .cmd("GET")
+        .arg(&key)
+        .cmd("PTTL")
         .arg(&key)
         .query_async(&mut conn)
         .await
-        .unwrap_or(None);
-
-    match raw {
-        Some(json) => {
-            let result: AnalysisResult =
-                serde_json::from_str(&json).context("Failed to deserialize cached analysis")?;
-            tracing::debug!("Cache hit for analysis");
-            Ok(Some(result))
-        }
-        None => Ok(None),
+        .inspect_err(|e| record_backend_error("analysis_cache_get", e))
+        .ok()?;
+
+    let result: AnalysisResult = serde_json::from_str(&raw?)
+        .inspect_err(|e| tracing::warn!("Ignoring unreadable cached analysis: {e}"))
+        .ok()?;
+    tracing::debug!("Cache hit for analysis");
+    if ttl_ms > 0 {
+        let ttl = Duration::from_millis(ttl_ms as u64);
+        state.analysis_cache.put(key, &result, ttl);
     }
+    Some(result)
 }
 
-/// Store an analysis result in the cache.
-pub async fn set_cached(state: &AppState, text: &str, result: &AnalysisResult) -> Result<()> {
+/// Store an analysis result in the cache. Failures are logged and otherwise
+/// ignored.
+pub async fn set_cached(
+    state: &AppState,
+    text: &str,
+    context: &[String],
+    intensity: f64,
+    result: &AnalysisResult,
+) {
+    let key = cache_key(text, context, intensity);
+    let json = match serde_json::to_string(result) {
+        Ok(json) => json,
+        Err(e) => {
+            tracing::warn!("Failed to serialize analysis for the cache: {e}");
+            return;
+        }
+    };
     let mut conn = state.db.redis.clone();
-    let key = cache_key(text);
-    let json = serde_json::to_string(result)?;
 
-    redis::cmd("SET")
+    if let Err(e) = redis::cmd("SET")
         .arg(&key)
         .arg(&json)
         .arg("EX")
         .arg(CACHE_TTL_SECS)
         .query_async::<()>(&mut conn)
         .await
-        .context("Failed to cache analysis result")?;
+    {
+        record_backend_error("analysis_cache_set", &e);
+        return;
+    }
 
+    state
+        .analysis_cache
+        .put(key, result, Duration::from_secs(CACHE_TTL_SECS));
     tracing::debug!("Cached analysis result");
-    Ok(())
 }
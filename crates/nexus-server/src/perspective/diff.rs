@@ -0,0 +1,70 @@
+use std::collections::BTreeSet;
+
+use nexus_common::types::{AnalysisDiff, AnalysisResult, FeatureDiff, VoiceType};
+
+/// Compare two analyses: which nominalisations, presuppositions and framings
+/// appear in only one of them, and how the passive-voice count changed.
+pub fn diff_analyses(a: &AnalysisResult, b: &AnalysisResult) -> AnalysisDiff {
+    let nominalisations = feature_diff(
+        a.syntactic
+            .nominalisations
+            .iter()
+            .map(|n| n.original.as_str()),
+        b.syntactic
+            .nominalisations
+            .iter()
+            .map(|n| n.original.as_str()),
+    );
+    let presuppositions = feature_diff(
+        a.semantic
+            .presuppositions
+            .iter()
+            .map(|p| p.presupposed_content.as_str()),
+        b.semantic
+            .presuppositions
+            .iter()
+            .map(|p| p.presupposed_content.as_str()),
+    );
+    let framings = feature_diff(
+        a.discourse.framing.iter().map(|f| f.frame_name.as_str()),
+        b.discourse.framing.iter().map(|f| f.frame_name.as_str()),
+    );
+
+    let passive_count_a = passive_count(a);
+    let passive_count_b = passive_count(b);
+
+    AnalysisDiff {
+        analysis_a: a.id,
+        analysis_b: b.id,
+        nominalisations,
+        presuppositions,
+        framings,
+        passive_count_a,
+        passive_count_b,
+        passive_delta: passive_count_b as i64 - passive_count_a as i64,
+    }
+}
+
+fn passive_count(analysis: &AnalysisResult) -> usize {
+    analysis
+        .syntactic
+        .voice_analysis
+        .iter()
+        .filter(|v| v.voice == VoiceType::Passive)
+        .count()
+}
+
+/// Set difference in both directions, compared case-insensitively.
+fn feature_diff<'a>(
+    a: impl Iterator<Item = &'a str>,
+    b: impl Iterator<Item = &'a str>,
+) -> FeatureDiff {
+    let normalise = |s: &str| s.trim().to_lowercase();
+    let a: BTreeSet<String> = a.map(normalise).collect();
+    let b: BTreeSet<String> = b.map(normalise).collect();
+
+    FeatureDiff {
+        only_in_a: a.difference(&b).cloned().collect(),
+        only_in_b: b.difference(&a).cloned().collect(),
+    }
+}
@@ -2,40 +2,49 @@ use anyhow::Result;
 use serde::Deserialize;
 
 use crate::api::state::AppState;
+use crate::perspective::engine::{confidence, with_context};
 use nexus_common::types::{
     CollocationPattern, DiscourseAnalysis, FramingInstance, IntertextualityMarker,
     StrategicOmission,
 };
 
 /// Layer 3: Discourse analysis via a single Ollama call.
-pub async fn analyze(state: &AppState, text: &str) -> Result<DiscourseAnalysis> {
+pub async fn analyze(
+    state: &AppState,
+    text: &str,
+    context: &[String],
+) -> Result<DiscourseAnalysis> {
     let system = r#"Perform a comprehensive discourse analysis of the given text. Return a single JSON object with these four arrays:
 
 1. "frames": How the text frames issues. Each entry:
    - "frame_name": name of the frame
    - "evidence": specific text evidence
    - "effect": how this frame shapes understanding
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 2. "omissions": What is strategically omitted. Each entry:
    - "what_is_missing": description of what's omitted
    - "why_it_matters": why this omission is significant
    - "who_benefits": who benefits from this omission
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 3. "collocations": Significant word pairings and their ideological implications. Each entry:
    - "pattern": the collocation
    - "frequency_note": how often/where this appears
    - "ideological_loading": what ideology this serves
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 4. "markers": Intertextual references (echoes of other texts/discourses). Each entry:
    - "reference": the intertextual element
    - "source_discourse": where it comes from
    - "function": what it does in this context
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 Limit each array to at most 3 entries. Focus on the most significant findings."#;
 
     let result: CombinedDiscourseResponse = state
-        .ollama
-        .generate_json(text, Some(system))
+        .llm
+        .generate_analysis_json(&with_context(context, text), Some(system))
         .await
         .unwrap_or_else(|_| CombinedDiscourseResponse::default());
 
@@ -47,6 +56,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 frame_name: f.frame_name,
                 evidence: f.evidence,
                 effect: f.effect,
+                confidence: confidence(f.confidence),
+                chunk_index: None,
             })
             .collect(),
         strategic_omissions: result
@@ -56,6 +67,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 what_is_missing: o.what_is_missing,
                 why_it_matters: o.why_it_matters,
                 who_benefits: o.who_benefits,
+                confidence: confidence(o.confidence),
+                chunk_index: None,
             })
             .collect(),
         collocations: result
@@ -65,6 +78,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 pattern: c.pattern,
                 frequency_note: c.frequency_note,
                 ideological_loading: c.ideological_loading,
+                confidence: confidence(c.confidence),
+                chunk_index: None,
             })
             .collect(),
         intertextuality: result
@@ -74,6 +89,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 reference: m.reference,
                 source_discourse: m.source_discourse,
                 function: m.function,
+                confidence: confidence(m.confidence),
+                chunk_index: None,
             })
             .collect(),
     })
@@ -96,6 +113,8 @@ struct FramingEntry {
     frame_name: String,
     evidence: String,
     effect: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -103,6 +122,8 @@ struct OmissionEntry {
     what_is_missing: String,
     why_it_matters: String,
     who_benefits: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -110,6 +131,8 @@ struct CollocationEntry {
     pattern: String,
     frequency_note: String,
     ideological_loading: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -117,4 +140,6 @@ struct IntertextEntry {
     reference: String,
     source_discourse: String,
     function: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
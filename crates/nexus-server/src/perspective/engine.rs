@@ -1,61 +1,703 @@
 use anyhow::Result;
 use chrono::Utc;
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use futures::stream::{self, FuturesUnordered};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::api::state::AppState;
-use crate::perspective::{cache, discourse, semantic, syntactic, synthesis};
-use nexus_common::types::AnalysisResult;
+use crate::perspective::{cache, discourse, merge, semantic, syntactic, synthesis};
+use crate::river::dialogue::TurnOptions;
+use crate::shared::chat_engine::{ChatEngine, ChatTurn};
+use crate::shared::{llm, text_util};
+use nexus_common::error::NexusError;
+use nexus_common::types::{
+    AnalysisMatch, AnalysisResult, AnalysisStats, CriticalSynthesis, DiscourseAnalysis,
+    FeatureStats, SemanticAnalysis, SyntacticAnalysis, VoiceType,
+};
+
+/// Reply recorded for an analysis turn; the analysis itself is the result.
+const ANALYSIS_REPLY: &str = "Analysis complete.";
+
+/// Analysis mode: the Perspective analysis alone. Turns are neither
+/// remembered nor regenerable, as the analysis is deterministic.
+pub struct AnalysisEngine;
+
+impl ChatEngine for AnalysisEngine {
+    fn process<'a>(
+        &'a self,
+        state: &'a AppState,
+        _session_id: Uuid,
+        user_id: Uuid,
+        message: &'a str,
+        opts: TurnOptions<'a>,
+    ) -> BoxFuture<'a, Result<ChatTurn>> {
+        Box::pin(async move {
+            let owner = (!opts.read_only).then_some(user_id);
+            let analysis = run_analysis(
+                state,
+                owner,
+                message,
+                &[],
+                synthesis::DEFAULT_INTENSITY,
+                &Layer::ALL,
+                |_| {},
+            )
+            .await?;
+            Ok(ChatTurn {
+                response: ANALYSIS_REPLY.into(),
+                analysis: Some(analysis),
+                contradictions: None,
+                beliefs: None,
+                consciousness: None,
+                memories_recalled: None,
+                rationale: None,
+                warnings: Vec::new(),
+                remember: false,
+            })
+        })
+    }
+
+    fn can_regenerate(&self) -> bool {
+        false
+    }
+}
+
+/// Claims analysed at once; each runs two LLM layers.
+const CLAIM_CONCURRENCY: usize = 4;
+
+/// A single layer call gets this fraction of `ANALYSIS_TIMEOUT_SECS`, so a
+/// hung layer fails into a warning well before the whole run times out.
+const LAYER_TIMEOUT_DIVISOR: u32 = 2;
+
+/// One of the four analysis layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Syntactic,
+    Semantic,
+    Discourse,
+    Synthesis,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 4] = [
+        Self::Syntactic,
+        Self::Semantic,
+        Self::Discourse,
+        Self::Synthesis,
+    ];
+}
+
+impl FromStr for Layer {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "syntactic" => Ok(Self::Syntactic),
+            "semantic" => Ok(Self::Semantic),
+            "discourse" => Ok(Self::Discourse),
+            "synthesis" => Ok(Self::Synthesis),
+            other => anyhow::bail!(
+                "Unknown analysis layer '{other}' (expected syntactic, semantic, discourse or synthesis)"
+            ),
+        }
+    }
+}
+
+/// The output of a single analysis layer, tagged with the layer that produced it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum LayerOutput {
+    Syntactic(SyntacticAnalysis),
+    Semantic(SemanticAnalysis),
+    Discourse(DiscourseAnalysis),
+    Synthesis(CriticalSynthesis),
+}
+
+impl LayerOutput {
+    pub fn layer(&self) -> &'static str {
+        match self {
+            Self::Syntactic(_) => "syntactic",
+            Self::Semantic(_) => "semantic",
+            Self::Discourse(_) => "discourse",
+            Self::Synthesis(_) => "synthesis",
+        }
+    }
+
+    fn apply(self, result: &mut AnalysisResult) {
+        match self {
+            Self::Syntactic(layer) => result.syntactic = layer,
+            Self::Semantic(layer) => result.semantic = layer,
+            Self::Discourse(layer) => result.discourse = layer,
+            Self::Synthesis(layer) => result.critical_synthesis = layer,
+        }
+    }
+}
 
 /// Run full 4-layer Perspective analysis on the given text.
 /// Results are cached in Redis.
-pub async fn analyze_text(state: &AppState, text: &str) -> Result<AnalysisResult> {
+///
+/// A failing layer is replaced with an empty result and noted in `warnings`;
+/// the analysis only fails if every layer does. The whole run is bounded by
+/// `ANALYSIS_TIMEOUT_SECS`, and each layer call by a fraction of it (see
+/// [`LAYER_TIMEOUT_DIVISOR`]), so a hung layer costs only its own findings.
+///
+/// Text longer than `ANALYSIS_CHUNK_CHARS` is split between sentences; each
+/// layer runs over the chunks in turn and merges their findings, and the
+/// timeout is scaled by the number of chunks. Chunks under `MIN_INPUT_WORDS`
+/// words are skipped.
+pub async fn analyze_text(state: &AppState, user_id: Uuid, text: &str) -> Result<AnalysisResult> {
+    analyze_text_with(state, user_id, text, &[], synthesis::DEFAULT_INTENSITY).await
+}
+
+/// Like [`analyze_text`], with the synthesis layer's devil's-advocate
+/// `intensity` (0–1) and `context`: preceding passages shown to the model to
+/// resolve references in `text`, but neither analysed nor stored. Results are
+/// cached per context and intensity.
+pub async fn analyze_text_with(
+    state: &AppState,
+    user_id: Uuid,
+    text: &str,
+    context: &[String],
+    intensity: f64,
+) -> Result<AnalysisResult> {
+    analyze_text_streaming(state, user_id, text, context, intensity, |_| {}).await
+}
+
+/// Like [`analyze_text`], but running only `layers`; the others are left
+/// empty. A cached full analysis is still used if there is one. The result is
+/// stored for `owner`, if any, and only when every layer runs.
+pub async fn analyze_layers(
+    state: &AppState,
+    owner: Option<Uuid>,
+    text: &str,
+    layers: &[Layer],
+) -> Result<AnalysisResult> {
+    run_analysis(
+        state,
+        owner,
+        text,
+        &[],
+        synthesis::DEFAULT_INTENSITY,
+        layers,
+        |_| {},
+    )
+    .await
+}
+
+/// Like [`analyze_text_with`], but calls `on_layer` with each layer as soon as
+/// it completes, fastest first. On a cache hit every layer is reported up front.
+pub async fn analyze_text_streaming(
+    state: &AppState,
+    user_id: Uuid,
+    text: &str,
+    context: &[String],
+    intensity: f64,
+    on_layer: impl FnMut(&LayerOutput),
+) -> Result<AnalysisResult> {
+    run_analysis(
+        state,
+        Some(user_id),
+        text,
+        context,
+        intensity,
+        &Layer::ALL,
+        on_layer,
+    )
+    .await
+}
+
+/// Run `layers` over `text`, storing the result for `owner` if there is one;
+/// `None` stores nothing, as when replaying a message.
+async fn run_analysis(
+    state: &AppState,
+    owner: Option<Uuid>,
+    text: &str,
+    context: &[String],
+    intensity: f64,
+    layers: &[Layer],
+    mut on_layer: impl FnMut(&LayerOutput),
+) -> Result<AnalysisResult> {
+    let min_words = state.config.min_input_words;
+    if !text_util::has_min_words(text, min_words) {
+        return Err(NexusError::Validation(format!(
+            "text must contain at least {} word(s) to analyze",
+            min_words.max(1)
+        ))
+        .into());
+    }
+
     // Check cache first.
-    if let Ok(Some(cached)) = cache::get_cached(state, text).await {
+    if let Some(mut cached) = cache::get_cached(state, text, context, intensity).await {
+        // The cache is shared between users: store this user their own copy.
+        cached.id = Uuid::new_v4();
+        cached.created_at = Utc::now();
+        if let Some(user_id) = owner {
+            let _ = store_analysis(state, user_id, &cached).await;
+        }
+        on_layer(&LayerOutput::Syntactic(cached.syntactic.clone()));
+        on_layer(&LayerOutput::Semantic(cached.semantic.clone()));
+        on_layer(&LayerOutput::Discourse(cached.discourse.clone()));
+        on_layer(&LayerOutput::Synthesis(cached.critical_synthesis.clone()));
         return Ok(cached);
     }
 
-    tracing::info!("Running full 4-layer Perspective analysis");
+    tracing::info!(layers = layers.len(), "Running Perspective analysis");
+
+    let chunks = text_util::chunk_text(text, state.config.analysis_chunk_chars);
+    let mut chunks: Vec<(&str, usize)> = chunks
+        .into_iter()
+        .map(|range| {
+            (
+                &text[range.clone()],
+                text_util::char_range(text, range).start,
+            )
+        })
+        .collect();
+    // Chunks too short to analyse on their own are skipped, unless that would
+    // leave nothing to analyse.
+    if chunks
+        .iter()
+        .any(|(chunk, _)| text_util::has_min_words(chunk, min_words))
+    {
+        chunks.retain(|(chunk, _)| text_util::has_min_words(chunk, min_words));
+    }
+    let chunks = chunks.as_slice();
+    if chunks.len() > 1 {
+        tracing::info!(chunks = chunks.len(), "Analysing long text in chunks");
+    }
+
+    let deadline = Duration::from_secs(state.config.analysis_timeout_secs) * chunks.len() as u32;
+    let layer_timeout = layer_timeout(state);
+
+    // Run the layers in parallel, handling each as it finishes.
+    let mut running: FuturesUnordered<BoxFuture<'_, (&'static str, Result<LayerOutput>, bool)>> =
+        FuturesUnordered::new();
+    if layers.contains(&Layer::Syntactic) {
+        running.push(Box::pin(async move {
+            let (out, truncated) = llm::track_truncation(run_chunked(
+                chunks,
+                |chunk, offset| async move {
+                    let mut out =
+                        run_layer(layer_timeout, syntactic::analyze(state, chunk, context)).await?;
+                    syntactic::shift_offsets(&mut out, offset);
+                    Ok(out)
+                },
+                merge::merge_syntactic,
+            ))
+            .await;
+            ("syntactic", out.map(LayerOutput::Syntactic), truncated)
+        }));
+    }
+    if layers.contains(&Layer::Semantic) {
+        running.push(Box::pin(async move {
+            let (out, truncated) = llm::track_truncation(run_chunked(
+                chunks,
+                |chunk, _| run_layer(layer_timeout, semantic::analyze(state, chunk, context)),
+                merge::merge_semantic,
+            ))
+            .await;
+            ("semantic", out.map(LayerOutput::Semantic), truncated)
+        }));
+    }
+    if layers.contains(&Layer::Discourse) {
+        running.push(Box::pin(async move {
+            let (out, truncated) = llm::track_truncation(run_chunked(
+                chunks,
+                |chunk, _| run_layer(layer_timeout, discourse::analyze(state, chunk, context)),
+                merge::merge_discourse,
+            ))
+            .await;
+            ("discourse", out.map(LayerOutput::Discourse), truncated)
+        }));
+    }
+    if layers.contains(&Layer::Synthesis) {
+        running.push(Box::pin(async move {
+            let (out, truncated) = llm::track_truncation(run_chunked(
+                chunks,
+                |chunk, _| {
+                    run_layer(
+                        layer_timeout,
+                        synthesis::analyze(state, chunk, context, intensity),
+                    )
+                },
+                merge::merge_synthesis,
+            ))
+            .await;
+            ("synthesis", out.map(LayerOutput::Synthesis), truncated)
+        }));
+    }
+
+    let mut result = empty_analysis(text);
+    let mut failed = 0;
 
-    // Run all 4 layers in parallel.
-    let (syntactic_result, semantic_result, discourse_result, synthesis_result) = tokio::try_join!(
-        syntactic::analyze(state, text),
-        semantic::analyze(state, text),
-        discourse::analyze(state, text),
-        synthesis::analyze(state, text),
-    )?;
+    tokio::time::timeout(deadline, async {
+        while let Some((layer, outcome, truncated)) = running.next().await {
+            if truncated {
+                result.warnings.push(format!(
+                    "{layer} layer output was truncated; some findings may be missing"
+                ));
+            }
+            match outcome {
+                Ok(output) => {
+                    on_layer(&output);
+                    output.apply(&mut result);
+                }
+                Err(e) => {
+                    tracing::error!("{layer} analysis layer failed: {e:#}");
+                    result.warnings.push(format!("{layer} layer failed"));
+                    failed += 1;
+                }
+            }
+        }
+    })
+    .await
+    .map_err(|_| {
+        tracing::warn!(timeout_secs = deadline.as_secs(), "Analysis timed out");
+        NexusError::Analysis("timeout".into())
+    })?;
 
-    let result = AnalysisResult {
+    if failed == layers.len() {
+        return Err(NexusError::Analysis("Every analysis layer failed".into()).into());
+    }
+
+    // Cache the result (best effort). Partial results aren't cached so the
+    // next request gets another chance at the failed or skipped layers.
+    let complete = layers.len() == Layer::ALL.len();
+    if result.warnings.is_empty() && complete {
+        cache::set_cached(state, text, context, intensity, &result).await;
+    }
+
+    // Store in PostgreSQL for persistence. Runs that skipped layers aren't
+    // stored: their empty layers would read as findings of nothing in
+    // searches, stats and exports.
+    if let Some(user_id) = owner.filter(|_| complete) {
+        let _ = store_analysis(state, user_id, &result).await;
+    }
+
+    Ok(result)
+}
+
+/// The semantic and synthesis layers run over a single claim.
+#[derive(Debug, Clone, Serialize)]
+pub struct ClaimAnalysis {
+    pub semantic: SemanticAnalysis,
+    pub critical_synthesis: CriticalSynthesis,
+    pub warnings: Vec<String>,
+}
+
+/// Analyse claims that have already been extracted, keyed by claim. Only the
+/// semantic and synthesis layers run; the syntactic and discourse passes say
+/// little about a single sentence. Claims are trimmed and deduplicated, then
+/// analysed [`CLAIM_CONCURRENCY`] at a time, and the whole run is bounded by
+/// `ANALYSIS_TIMEOUT_SECS`, each layer call by a fraction of it as in
+/// [`analyze_text`].
+/// A failed layer is left empty and noted in that claim's `warnings`; the call
+/// only fails if every layer of every claim does.
+pub async fn analyze_claims(
+    state: &AppState,
+    claims: &[String],
+) -> Result<BTreeMap<String, ClaimAnalysis>> {
+    tracing::info!(claims = claims.len(), "Running claim analysis");
+
+    let deadline = Duration::from_secs(state.config.analysis_timeout_secs);
+    let layer_timeout = layer_timeout(state);
+
+    let mut unique: Vec<&str> = claims.iter().map(|c| c.trim()).collect();
+    unique.sort_unstable();
+    unique.dedup();
+
+    let analyses = stream::iter(unique).map(|claim| async move {
+        let (semantic, synthesis) = tokio::join!(
+            run_layer(layer_timeout, semantic::analyze(state, claim, &[])),
+            run_layer(
+                layer_timeout,
+                synthesis::analyze(state, claim, &[], synthesis::DEFAULT_INTENSITY),
+            ),
+        );
+        let mut warnings = Vec::new();
+        let semantic = semantic.unwrap_or_else(|e| {
+            tracing::error!("semantic analysis of claim failed: {e:#}");
+            warnings.push("semantic layer failed".into());
+            SemanticAnalysis::default()
+        });
+        let critical_synthesis = synthesis.unwrap_or_else(|e| {
+            tracing::error!("synthesis analysis of claim failed: {e:#}");
+            warnings.push("synthesis layer failed".into());
+            CriticalSynthesis::default()
+        });
+        (
+            claim.to_string(),
+            ClaimAnalysis {
+                semantic,
+                critical_synthesis,
+                warnings,
+            },
+        )
+    });
+    let analyses = analyses
+        .buffer_unordered(CLAIM_CONCURRENCY)
+        .collect::<Vec<_>>();
+
+    let results: BTreeMap<_, _> = tokio::time::timeout(deadline, analyses)
+        .await
+        .map_err(|_| {
+            tracing::warn!(
+                timeout_secs = deadline.as_secs(),
+                "Claim analysis timed out"
+            );
+            NexusError::Analysis("timeout".into())
+        })?
+        .into_iter()
+        .collect();
+
+    if results.values().all(|r| r.warnings.len() == 2) {
+        return Err(NexusError::Analysis("Every claim analysis failed".into()).into());
+    }
+    Ok(results)
+}
+
+/// An analysis of `text` with every layer empty, to be filled in.
+pub fn empty_analysis(text: &str) -> AnalysisResult {
+    AnalysisResult {
         id: Uuid::new_v4(),
         input_text: text.to_string(),
-        syntactic: syntactic_result,
-        semantic: semantic_result,
-        discourse: discourse_result,
-        critical_synthesis: synthesis_result,
+        syntactic: Default::default(),
+        semantic: Default::default(),
+        discourse: Default::default(),
+        critical_synthesis: Default::default(),
         created_at: Utc::now(),
-    };
+        warnings: Vec::new(),
+    }
+}
+
+/// The prompt for an LLM layer: `text`, preceded by any `context` passages in
+/// a delimited block the model is told to read but not analyse.
+pub fn with_context<'a>(context: &[String], text: &'a str) -> Cow<'a, str> {
+    if context.is_empty() {
+        return Cow::Borrowed(text);
+    }
+    let passages: Vec<String> = context
+        .iter()
+        .enumerate()
+        .map(|(i, passage)| format!("[Passage {}]\n{}", i + 1, passage.trim()))
+        .collect();
+    Cow::Owned(format!(
+        "The passages in <context> precede the text and are given only to \
+         resolve references and framing. Do not analyse them; every finding \
+         must come from the text in <text>.\n\n\
+         <context>\n{}\n</context>\n\n<text>\n{text}\n</text>",
+        passages.join("\n\n")
+    ))
+}
+
+/// A model-reported confidence clamped to 0–1. Findings it didn't score are
+/// treated as a coin flip.
+pub fn confidence(raw: Option<f64>) -> f64 {
+    raw.filter(|c| c.is_finite()).unwrap_or(0.5).clamp(0.0, 1.0)
+}
 
-    // Cache the result (best effort).
-    let _ = cache::set_cached(state, text, &result).await;
+/// Run a layer over each chunk in turn, merging the results. Each chunk is
+/// passed with its character offset in the full text. A single chunk is
+/// returned as is, without chunk tags.
+async fn run_chunked<'a, T, F, Fut>(
+    chunks: &[(&'a str, usize)],
+    mut run: F,
+    merge: fn(&mut T, T, usize),
+) -> Result<T>
+where
+    T: Default,
+    F: FnMut(&'a str, usize) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    if let [(only, offset)] = chunks {
+        return run(only, *offset).await;
+    }
+    let mut merged = T::default();
+    for (index, &(chunk, offset)) in chunks.iter().enumerate() {
+        let part = run(chunk, offset).await?;
+        merge(&mut merged, part, index);
+    }
+    Ok(merged)
+}
 
-    // Store in PostgreSQL for persistence.
-    let _ = store_analysis(state, &result).await;
+/// How long a single layer call may run.
+fn layer_timeout(state: &AppState) -> Duration {
+    Duration::from_secs(state.config.analysis_timeout_secs) / LAYER_TIMEOUT_DIVISOR
+}
 
-    Ok(result)
+/// Run a layer, failing it if it takes longer than `layer_timeout`.
+async fn run_layer<T>(
+    layer_timeout: Duration,
+    layer: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    tokio::time::timeout(layer_timeout, layer)
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("layer timed out")))
 }
 
-/// Persist analysis result to PostgreSQL.
-async fn store_analysis(state: &AppState, result: &AnalysisResult) -> Result<()> {
-    let analysis_json = serde_json::to_value(result)?;
+/// Persist analysis result to PostgreSQL, with the input text redacted like
+/// stored messages.
+pub(crate) async fn store_analysis(
+    state: &AppState,
+    user_id: Uuid,
+    result: &AnalysisResult,
+) -> Result<()> {
+    let input_text = state.redactor.redact(&result.input_text);
+    let mut analysis_json = serde_json::to_value(result)?;
+    analysis_json["input_text"] = serde_json::Value::from(input_text.as_ref());
+    let passive_voice = result
+        .syntactic
+        .voice_analysis
+        .iter()
+        .filter(|v| v.voice == VoiceType::Passive)
+        .count();
 
     sqlx::query(
-        "INSERT INTO analyses (id, input_text, result, created_at) VALUES ($1, $2, $3, $4)",
+        "INSERT INTO analyses
+             (id, user_id, input_text, result, created_at, passive_voice_count,
+              nominalisation_count, presupposition_count, implicature_count, framing_count,
+              naturalised_claim_count)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
     )
     .bind(result.id)
-    .bind(&result.input_text)
+    .bind(user_id)
+    .bind(input_text.as_ref())
     .bind(&analysis_json)
     .bind(result.created_at)
+    .bind(passive_voice as i32)
+    .bind(result.syntactic.nominalisations.len() as i32)
+    .bind(result.semantic.presuppositions.len() as i32)
+    .bind(result.semantic.implicatures.len() as i32)
+    .bind(result.discourse.framing.len() as i32)
+    .bind(result.critical_synthesis.naturalised_claims.len() as i32)
     .execute(&state.db.pg)
     .await?;
 
     Ok(())
 }
+
+/// Load one of the user's stored analyses by ID.
+pub async fn get_analysis(state: &AppState, user_id: Uuid, id: Uuid) -> Result<AnalysisResult> {
+    // Other users' analyses are reported as missing, not forbidden.
+    let row: Option<(serde_json::Value,)> =
+        sqlx::query_as("SELECT result FROM analyses WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&state.db.pg)
+            .await
+            .map_err(|e| NexusError::Database(format!("Failed to load analysis: {e}")))?;
+
+    let Some((result,)) = row else {
+        return Err(NexusError::NotFound(format!("Analysis {id} not found")).into());
+    };
+
+    Ok(serde_json::from_value(result)?)
+}
+
+/// Feature counts aggregated over the user's analyses, optionally only those
+/// created since `since`.
+pub async fn analysis_stats(
+    state: &AppState,
+    user_id: Uuid,
+    since: Option<chrono::DateTime<Utc>>,
+) -> Result<AnalysisStats> {
+    type Row = (
+        i64,
+        i64,
+        i64,
+        i64,
+        i64,
+        i64,
+        i64,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+        i32,
+    );
+    let row: Row = sqlx::query_as(
+        "SELECT COUNT(*),
+                COALESCE(SUM(passive_voice_count), 0)::BIGINT,
+                COALESCE(SUM(nominalisation_count), 0)::BIGINT,
+                COALESCE(SUM(presupposition_count), 0)::BIGINT,
+                COALESCE(SUM(implicature_count), 0)::BIGINT,
+                COALESCE(SUM(framing_count), 0)::BIGINT,
+                COALESCE(SUM(naturalised_claim_count), 0)::BIGINT,
+                COALESCE(MAX(passive_voice_count), 0),
+                COALESCE(MAX(nominalisation_count), 0),
+                COALESCE(MAX(presupposition_count), 0),
+                COALESCE(MAX(implicature_count), 0),
+                COALESCE(MAX(framing_count), 0),
+                COALESCE(MAX(naturalised_claim_count), 0)
+         FROM analyses
+         WHERE user_id = $1 AND ($2::TIMESTAMPTZ IS NULL OR created_at >= $2)",
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_one(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to aggregate analyses: {e}")))?;
+
+    let analyses = row.0;
+    let feature = |total: i64, max: i32| FeatureStats {
+        total,
+        mean: if analyses > 0 {
+            total as f64 / analyses as f64
+        } else {
+            0.0
+        },
+        max: max as i64,
+    };
+    Ok(AnalysisStats {
+        analyses,
+        passive_voice: feature(row.1, row.7),
+        nominalisations: feature(row.2, row.8),
+        presuppositions: feature(row.3, row.9),
+        implicatures: feature(row.4, row.10),
+        framings: feature(row.5, row.11),
+        naturalised_claims: feature(row.6, row.12),
+    })
+}
+
+/// Rank a user's past analyses against a web-style query (`"quoted phrases"`,
+/// `or`, `-excluded`), best first, with the matching part of each input
+/// highlighted.
+pub async fn search_analyses(
+    state: &AppState,
+    user_id: Uuid,
+    query_text: &str,
+    limit: i64,
+) -> Result<Vec<AnalysisMatch>> {
+    let rows: Vec<(Uuid, chrono::DateTime<Utc>, f32, String)> = sqlx::query_as(
+        "SELECT id, created_at, ts_rank(search_vector, query),
+                ts_headline('english', input_text, query,
+                            'StartSel=<mark>, StopSel=</mark>, MaxFragments=2')
+         FROM analyses, websearch_to_tsquery('english', $2) AS query
+         WHERE user_id = $1 AND search_vector @@ query
+         ORDER BY 3 DESC, created_at DESC
+         LIMIT $3",
+    )
+    .bind(user_id)
+    .bind(query_text)
+    .bind(limit)
+    .fetch_all(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to search analyses: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, created_at, rank, snippet)| AnalysisMatch {
+            id,
+            snippet,
+            rank,
+            created_at,
+        })
+        .collect())
+}
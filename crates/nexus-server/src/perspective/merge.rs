@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+
+use crate::perspective::syntactic;
+
+use nexus_common::types::{
+    AlternativeFraming, AnalysisResult, BeneficiaryAnalysis, CollocationPattern, CriticalSynthesis,
+    DiscourseAnalysis, FramingInstance, HiddenContext, Implicature, IntertextualityMarker,
+    LexicalField, ModalityInstance, NaturalisedClaim, Nominalisation, PowerHierarchy,
+    Presupposition, SemanticAnalysis, SentenceComplexity, StrategicOmission, SyntacticAnalysis,
+    TransitivityInstance, VoiceInstance,
+};
+
+// Merging the per-chunk results of a layer: entries are concatenated in chunk
+// order, dropping any whose identifying text (compared case-insensitively) an
+// earlier chunk already reported. Kept entries are tagged with their chunk.
+// Filtering by confidence lives here too, since it walks the same entries.
+
+pub fn merge_syntactic(into: &mut SyntacticAnalysis, part: SyntacticAnalysis, chunk: usize) {
+    extend_unique(&mut into.voice_analysis, part.voice_analysis, chunk, |v| {
+        v.sentence.clone()
+    });
+    extend_unique(
+        &mut into.sentence_complexity,
+        part.sentence_complexity,
+        chunk,
+        |s| s.sentence.clone(),
+    );
+    // The same word recurring across chunks is one nominalisation used more often.
+    for mut nominalisation in part.nominalisations {
+        let existing = into
+            .nominalisations
+            .iter_mut()
+            .find(|n| n.original.eq_ignore_ascii_case(&nominalisation.original));
+        match existing {
+            Some(existing) => existing.frequency += nominalisation.frequency,
+            None => {
+                nominalisation.set_chunk_index(chunk);
+                into.nominalisations.push(nominalisation);
+            }
+        }
+    }
+    syntactic::rank_nominalisations(&mut into.nominalisations);
+    extend_unique(&mut into.transitivity, part.transitivity, chunk, |t| {
+        t.sentence.clone()
+    });
+    extend_unique(&mut into.modality, part.modality, chunk, |m| {
+        format!("{}\n{}", m.marker, m.sentence)
+    });
+}
+
+pub fn merge_semantic(into: &mut SemanticAnalysis, part: SemanticAnalysis, chunk: usize) {
+    extend_unique(
+        &mut into.presuppositions,
+        part.presuppositions,
+        chunk,
+        |p| p.presupposed_content.clone(),
+    );
+    extend_unique(&mut into.implicatures, part.implicatures, chunk, |i| {
+        i.implied_meaning.clone()
+    });
+    extend_unique(
+        &mut into.power_hierarchies,
+        part.power_hierarchies,
+        chunk,
+        |h| format!("{}\u{0}{}", h.dominant, h.subordinate),
+    );
+    extend_unique(&mut into.lexical_fields, part.lexical_fields, chunk, |f| {
+        f.field_name.clone()
+    });
+}
+
+pub fn merge_discourse(into: &mut DiscourseAnalysis, part: DiscourseAnalysis, chunk: usize) {
+    extend_unique(&mut into.framing, part.framing, chunk, |f| {
+        f.frame_name.clone()
+    });
+    extend_unique(
+        &mut into.strategic_omissions,
+        part.strategic_omissions,
+        chunk,
+        |o| o.what_is_missing.clone(),
+    );
+    extend_unique(&mut into.collocations, part.collocations, chunk, |c| {
+        c.pattern.clone()
+    });
+    extend_unique(
+        &mut into.intertextuality,
+        part.intertextuality,
+        chunk,
+        |m| m.reference.clone(),
+    );
+}
+
+pub fn merge_synthesis(into: &mut CriticalSynthesis, part: CriticalSynthesis, chunk: usize) {
+    extend_unique(
+        &mut into.naturalised_claims,
+        part.naturalised_claims,
+        chunk,
+        |c| c.claim.clone(),
+    );
+    extend_unique(
+        &mut into.beneficiary_analysis,
+        part.beneficiary_analysis,
+        chunk,
+        |b| format!("{}\u{0}{}", b.who_benefits, b.how),
+    );
+    extend_unique(
+        &mut into.hidden_contexts,
+        part.hidden_contexts,
+        chunk,
+        |c| c.context.clone(),
+    );
+    extend_unique(
+        &mut into.alternative_framings,
+        part.alternative_framings,
+        chunk,
+        |f| f.alternative.clone(),
+    );
+}
+
+/// Drop every layer entry whose confidence is below `min`.
+pub fn retain_confident(analysis: &mut AnalysisResult, min: f64) {
+    fn retain<T: LayerEntry>(items: &mut Vec<T>, min: f64) {
+        items.retain(|item| item.confidence() >= min);
+    }
+
+    let syntactic = &mut analysis.syntactic;
+    retain(&mut syntactic.voice_analysis, min);
+    retain(&mut syntactic.sentence_complexity, min);
+    retain(&mut syntactic.nominalisations, min);
+    retain(&mut syntactic.transitivity, min);
+    retain(&mut syntactic.modality, min);
+
+    let semantic = &mut analysis.semantic;
+    retain(&mut semantic.presuppositions, min);
+    retain(&mut semantic.implicatures, min);
+    retain(&mut semantic.power_hierarchies, min);
+    retain(&mut semantic.lexical_fields, min);
+
+    let discourse = &mut analysis.discourse;
+    retain(&mut discourse.framing, min);
+    retain(&mut discourse.strategic_omissions, min);
+    retain(&mut discourse.collocations, min);
+    retain(&mut discourse.intertextuality, min);
+
+    let synthesis = &mut analysis.critical_synthesis;
+    retain(&mut synthesis.naturalised_claims, min);
+    retain(&mut synthesis.beneficiary_analysis, min);
+    retain(&mut synthesis.hidden_contexts, min);
+    retain(&mut synthesis.alternative_framings, min);
+}
+
+/// Fields shared by every layer entry.
+trait LayerEntry {
+    fn confidence(&self) -> f64;
+    fn set_chunk_index(&mut self, chunk: usize);
+}
+
+macro_rules! impl_layer_entry {
+    ($($ty:ty),* $(,)?) => {
+        $(impl LayerEntry for $ty {
+            fn confidence(&self) -> f64 {
+                self.confidence
+            }
+
+            fn set_chunk_index(&mut self, chunk: usize) {
+                self.chunk_index = Some(chunk);
+            }
+        })*
+    };
+}
+
+impl_layer_entry!(
+    VoiceInstance,
+    SentenceComplexity,
+    Nominalisation,
+    TransitivityInstance,
+    ModalityInstance,
+    Presupposition,
+    Implicature,
+    PowerHierarchy,
+    LexicalField,
+    FramingInstance,
+    StrategicOmission,
+    CollocationPattern,
+    IntertextualityMarker,
+    NaturalisedClaim,
+    BeneficiaryAnalysis,
+    HiddenContext,
+    AlternativeFraming,
+);
+
+/// Append the entries of `more` whose key isn't already present in `items`,
+/// tagging them with `chunk`.
+fn extend_unique<T: LayerEntry>(
+    items: &mut Vec<T>,
+    more: Vec<T>,
+    chunk: usize,
+    key: impl Fn(&T) -> String,
+) {
+    let normalise = |item: &T| key(item).trim().to_lowercase();
+    let mut seen: HashSet<String> = items.iter().map(normalise).collect();
+    for mut item in more {
+        if seen.insert(normalise(&item)) {
+            item.set_chunk_index(chunk);
+            items.push(item);
+        }
+    }
+}
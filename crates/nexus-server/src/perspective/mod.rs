@@ -1,6 +1,9 @@
 pub mod cache;
+pub mod diff;
 pub mod discourse;
 pub mod engine;
+pub mod merge;
+pub mod report;
 pub mod semantic;
 pub mod syntactic;
 pub mod synthesis;
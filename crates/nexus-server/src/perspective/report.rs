@@ -0,0 +1,235 @@
+use std::fmt::Write;
+
+use nexus_common::types::{AnalysisResult, ModalityStrength, VoiceType};
+
+/// Render an analysis as a Markdown report with one section per layer.
+pub fn to_markdown(analysis: &AnalysisResult) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# Perspective Analysis");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "- **ID:** `{}`", analysis.id);
+    let _ = writeln!(
+        out,
+        "- **Created:** {}",
+        analysis.created_at.format("%Y-%m-%d %H:%M UTC")
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Source Text");
+    let _ = writeln!(out);
+    for line in analysis.input_text.lines() {
+        let _ = writeln!(out, "> {line}");
+    }
+
+    // ── Layer 1 ──
+    let syntactic = &analysis.syntactic;
+    heading(&mut out, "1. Syntactic Analysis");
+
+    subheading(&mut out, "Voice");
+    if syntactic.voice_analysis.is_empty() {
+        none_found(&mut out);
+    } else {
+        let _ = writeln!(out, "| Sentence | Voice | Significance |");
+        let _ = writeln!(out, "| --- | --- | --- |");
+        for v in &syntactic.voice_analysis {
+            let voice = match v.voice {
+                VoiceType::Active => "Active",
+                VoiceType::Passive => "Passive",
+            };
+            let _ = writeln!(
+                out,
+                "| {} | {voice} | {} |",
+                cell(&v.sentence),
+                cell(&v.significance)
+            );
+        }
+    }
+
+    subheading(&mut out, "Nominalisations");
+    bullets(
+        &mut out,
+        syntactic.nominalisations.iter().map(|n| {
+            let count = if n.frequency > 1 {
+                format!(" (×{})", n.frequency)
+            } else {
+                String::new()
+            };
+            format!(
+                "**{}**{count} — hides the verb *{}*",
+                n.original, n.verb_form
+            )
+        }),
+    );
+
+    subheading(&mut out, "Transitivity");
+    bullets(
+        &mut out,
+        syntactic.transitivity.iter().map(|t| {
+            format!(
+                "{} → *{}* → {}: {}",
+                t.actor, t.process, t.affected, t.analysis
+            )
+        }),
+    );
+
+    subheading(&mut out, "Modality");
+    bullets(
+        &mut out,
+        syntactic.modality.iter().map(|m| {
+            let kind = match m.strength {
+                ModalityStrength::Hedge => "hedge",
+                ModalityStrength::Strong => "strong",
+            };
+            format!("**{}** ({kind}) — {}", m.marker, m.sentence)
+        }),
+    );
+
+    // ── Layer 2 ──
+    let semantic = &analysis.semantic;
+    heading(&mut out, "2. Semantic Analysis");
+
+    subheading(&mut out, "Presuppositions");
+    bullets(
+        &mut out,
+        semantic.presuppositions.iter().map(|p| {
+            format!(
+                "\"{}\" presupposes: {} — {}",
+                p.trigger, p.presupposed_content, p.significance
+            )
+        }),
+    );
+
+    subheading(&mut out, "Implicatures");
+    bullets(
+        &mut out,
+        semantic.implicatures.iter().map(|i| {
+            format!(
+                "\"{}\" implies: {} ({})",
+                i.statement, i.implied_meaning, i.mechanism
+            )
+        }),
+    );
+
+    subheading(&mut out, "Power Hierarchies");
+    bullets(
+        &mut out,
+        semantic.power_hierarchies.iter().map(|p| {
+            let markers = if p.linguistic_markers.is_empty() {
+                String::new()
+            } else {
+                format!(" (markers: {})", p.linguistic_markers.join(", "))
+            };
+            format!(
+                "**{}** over **{}**{markers}: {}",
+                p.dominant, p.subordinate, p.analysis
+            )
+        }),
+    );
+
+    // ── Layer 3 ──
+    let discourse = &analysis.discourse;
+    heading(&mut out, "3. Discourse Analysis");
+
+    subheading(&mut out, "Framing");
+    bullets(
+        &mut out,
+        discourse.framing.iter().map(|f| {
+            format!(
+                "**{}** — {} (evidence: \"{}\")",
+                f.frame_name, f.effect, f.evidence
+            )
+        }),
+    );
+
+    subheading(&mut out, "Strategic Omissions");
+    bullets(
+        &mut out,
+        discourse.strategic_omissions.iter().map(|o| {
+            format!(
+                "{} — {} (benefits: {})",
+                o.what_is_missing, o.why_it_matters, o.who_benefits
+            )
+        }),
+    );
+
+    // ── Layer 4 ──
+    let synthesis = &analysis.critical_synthesis;
+    heading(&mut out, "4. Critical Synthesis");
+
+    subheading(&mut out, "Naturalised Claims");
+    bullets(
+        &mut out,
+        synthesis.naturalised_claims.iter().map(|c| {
+            format!(
+                "\"{}\" — {}. Counter-evidence: {}",
+                c.claim, c.how_naturalised, c.counter_evidence
+            )
+        }),
+    );
+
+    subheading(&mut out, "Who Benefits");
+    bullets(
+        &mut out,
+        synthesis.beneficiary_analysis.iter().map(|b| {
+            format!(
+                "**{}** benefits {}; disadvantaged: {}",
+                b.who_benefits, b.how, b.who_is_disadvantaged
+            )
+        }),
+    );
+
+    subheading(&mut out, "Hidden Contexts");
+    bullets(
+        &mut out,
+        synthesis.hidden_contexts.iter().map(|h| {
+            format!(
+                "{} — {} (hidden because: {})",
+                h.context, h.relevance, h.why_hidden
+            )
+        }),
+    );
+
+    subheading(&mut out, "Alternative Framings");
+    bullets(
+        &mut out,
+        synthesis.alternative_framings.iter().map(|a| {
+            format!(
+                "Instead of *{}*: {} (same facts: {})",
+                a.original_frame, a.alternative, a.same_facts_used
+            )
+        }),
+    );
+
+    out
+}
+
+fn heading(out: &mut String, title: &str) {
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## {title}");
+}
+
+fn subheading(out: &mut String, title: &str) {
+    let _ = writeln!(out);
+    let _ = writeln!(out, "### {title}");
+    let _ = writeln!(out);
+}
+
+fn bullets(out: &mut String, items: impl Iterator<Item = String>) {
+    let mut any = false;
+    for item in items {
+        let _ = writeln!(out, "- {}", item.replace('\n', " "));
+        any = true;
+    }
+    if !any {
+        none_found(out);
+    }
+}
+
+fn none_found(out: &mut String) {
+    let _ = writeln!(out, "_None found._");
+}
+
+/// Make text safe for a single Markdown table cell.
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
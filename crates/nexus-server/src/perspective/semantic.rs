@@ -2,40 +2,45 @@ use anyhow::Result;
 use serde::Deserialize;
 
 use crate::api::state::AppState;
+use crate::perspective::engine::{confidence, with_context};
 use nexus_common::types::{
     Implicature, LexicalField, PowerHierarchy, Presupposition, SemanticAnalysis,
 };
 
 /// Layer 2: Semantic analysis via a single Ollama call.
-pub async fn analyze(state: &AppState, text: &str) -> Result<SemanticAnalysis> {
+pub async fn analyze(state: &AppState, text: &str, context: &[String]) -> Result<SemanticAnalysis> {
     let system = r#"Perform a comprehensive semantic analysis of the given text. Return a single JSON object with these four arrays:
 
 1. "presuppositions": Linguistic presuppositions (things taken for granted). Each entry:
    - "trigger": the linguistic trigger
    - "presupposed_content": what is presupposed
    - "significance": why this matters
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 2. "implicatures": Conversational implicatures (meanings implied but not stated). Each entry:
    - "statement": the statement
    - "implied_meaning": what is implied
    - "mechanism": how the implicature works
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 3. "hierarchies": Power hierarchies encoded in the text. Each entry:
    - "dominant": who/what holds power
    - "subordinate": who/what is subordinated
    - "linguistic_markers": specific words/phrases that encode this (array)
    - "analysis": brief analysis
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 4. "fields": Lexical fields (semantic clusters of related words). Each entry:
    - "field_name": the domain
    - "terms": array of related words
    - "connotation": what this lexical field implies
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 Limit each array to at most 3 entries. Focus on the most significant findings."#;
 
     let result: CombinedSemanticResponse = state
-        .ollama
-        .generate_json(text, Some(system))
+        .llm
+        .generate_analysis_json(&with_context(context, text), Some(system))
         .await
         .unwrap_or_else(|_| CombinedSemanticResponse::default());
 
@@ -47,6 +52,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 trigger: p.trigger,
                 presupposed_content: p.presupposed_content,
                 significance: p.significance,
+                confidence: confidence(p.confidence),
+                chunk_index: None,
             })
             .collect(),
         implicatures: result
@@ -56,6 +63,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 statement: i.statement,
                 implied_meaning: i.implied_meaning,
                 mechanism: i.mechanism,
+                confidence: confidence(i.confidence),
+                chunk_index: None,
             })
             .collect(),
         power_hierarchies: result
@@ -66,6 +75,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 subordinate: p.subordinate,
                 linguistic_markers: p.linguistic_markers,
                 analysis: p.analysis,
+                confidence: confidence(p.confidence),
+                chunk_index: None,
             })
             .collect(),
         lexical_fields: result
@@ -75,6 +86,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 field_name: f.field_name,
                 terms: f.terms,
                 connotation: f.connotation,
+                confidence: confidence(f.confidence),
+                chunk_index: None,
             })
             .collect(),
     })
@@ -97,6 +110,8 @@ struct PresupEntry {
     trigger: String,
     presupposed_content: String,
     significance: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -104,6 +119,8 @@ struct ImplicatureEntry {
     statement: String,
     implied_meaning: String,
     mechanism: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -112,6 +129,8 @@ struct PowerEntry {
     subordinate: String,
     linguistic_markers: Vec<String>,
     analysis: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -119,4 +138,6 @@ struct LexicalEntry {
     field_name: String,
     terms: Vec<String>,
     connotation: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
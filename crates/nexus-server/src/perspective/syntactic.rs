@@ -1,80 +1,160 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
 use anyhow::Result;
 use regex::Regex;
 use serde::Deserialize;
 
 use crate::api::state::AppState;
+use crate::perspective::engine::{confidence, with_context};
+use crate::shared::text_util::{self, sentence_spans, split_sentences};
 use nexus_common::types::{
-    Nominalisation, SentenceComplexity, SyntacticAnalysis, TransitivityInstance, VoiceInstance,
-    VoiceType,
+    ModalityInstance, ModalityStrength, Nominalisation, SentenceComplexity, SyntacticAnalysis,
+    TransitivityInstance, VoiceInstance, VoiceType,
 };
 
 /// Layer 1: Syntactic analysis.
 /// Uses regex for simple pattern matching (voice, nominalisations)
 /// and a single Ollama call for deeper analysis (transitivity + complexity combined).
-pub async fn analyze(state: &AppState, text: &str) -> Result<SyntacticAnalysis> {
+/// Only the LLM call sees `context`; the regex passes cover `text` alone.
+/// Entry offsets are character offsets into `text`.
+pub async fn analyze(
+    state: &AppState,
+    text: &str,
+    context: &[String],
+) -> Result<SyntacticAnalysis> {
     // Run regex-based analysis locally.
     let voice_analysis = detect_voice(text);
     let nominalisations = detect_nominalisations(text);
+    let modality = detect_modality(text);
 
     // Single combined Ollama call for complexity + transitivity.
-    let (complexity, transitivity) = analyze_combined(state, text).await?;
+    let (complexity, transitivity) = analyze_combined(state, text, context).await?;
 
     Ok(SyntacticAnalysis {
         voice_analysis,
         sentence_complexity: complexity,
         nominalisations,
         transitivity,
+        modality,
     })
 }
 
+/// Move every entry's offsets `by` characters, for a chunk that starts `by`
+/// characters into the full text.
+pub fn shift_offsets(analysis: &mut SyntacticAnalysis, by: usize) {
+    fn shift(start: &mut Option<usize>, end: &mut Option<usize>, by: usize) {
+        for offset in [start, end].into_iter().flatten() {
+            *offset += by;
+        }
+    }
+    if by == 0 {
+        return;
+    }
+    for v in &mut analysis.voice_analysis {
+        shift(&mut v.start, &mut v.end, by);
+    }
+    for s in &mut analysis.sentence_complexity {
+        shift(&mut s.start, &mut s.end, by);
+    }
+    for n in &mut analysis.nominalisations {
+        shift(&mut n.start, &mut n.end, by);
+    }
+    for t in &mut analysis.transitivity {
+        shift(&mut t.start, &mut t.end, by);
+    }
+    for m in &mut analysis.modality {
+        shift(&mut m.start, &mut m.end, by);
+    }
+}
+
+/// Confidence in regex voice detection: the pattern misses irregular
+/// participles and mistakes adjectives ("was excited") for passives.
+const VOICE_CONFIDENCE: f64 = 0.7;
+
 /// Detect active/passive voice using regex patterns.
 fn detect_voice(text: &str) -> Vec<VoiceInstance> {
     let passive_re =
         Regex::new(r"(?i)\b(was|were|is|are|been|being|be)\s+(\w+ed|made|done|given|taken|seen|known|found|told|shown|built|kept|left|held|brought|set|put|run|cut|let|lost|paid|met|hit|shut|hurt|read|thought|felt|bought|caught|taught|fought|sought|spent|sent|lent|bent|dealt|meant|dreamt|learnt|burnt|spoilt|spilt|smelt|built|understood|stood|sat|lay|led|fed|bid|rid|shed|split|spread|thrust|cast|cost|knit)\b")
             .expect("passive voice regex");
 
-    let sentences = split_sentences(text);
-    let mut results = Vec::new();
+    sentence_spans(text)
+        .into_iter()
+        .map(|span| {
+            let sentence = &text[span.clone()];
+            let offsets = text_util::char_range(text, span);
+            let (voice, significance) = if passive_re.is_match(sentence) {
+                (VoiceType::Passive, "Agent is obscured or de-emphasised")
+            } else {
+                (VoiceType::Active, "Clear agent-action relationship")
+            };
+            VoiceInstance {
+                sentence: sentence.to_string(),
+                voice,
+                significance: significance.into(),
+                confidence: VOICE_CONFIDENCE,
+                chunk_index: None,
+                start: Some(offsets.start),
+                end: Some(offsets.end),
+            }
+        })
+        .collect()
+}
 
-    for sentence in &sentences {
-        let trimmed = sentence.trim();
-        if trimmed.is_empty() {
-            continue;
-        }
+/// Epistemic hedges: the speaker signals uncertainty about the claim.
+static HEDGE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(might|may|could|perhaps|maybe|possibly|probably|likely|arguably|apparently|seems?|appears?|suggests?|somewhat|sort of|kind of|i think|i believe|i guess|i suppose|i feel)\b",
+    )
+    .expect("hedge regex")
+});
 
-        if passive_re.is_match(trimmed) {
-            results.push(VoiceInstance {
-                sentence: trimmed.to_string(),
-                voice: VoiceType::Passive,
-                significance: "Agent is obscured or de-emphasised".into(),
-            });
-        } else {
-            results.push(VoiceInstance {
-                sentence: trimmed.to_string(),
-                voice: VoiceType::Active,
-                significance: "Clear agent-action relationship".into(),
-            });
+/// Deontic and absolute markers: the claim admits no doubt or exception.
+static STRONG_MODAL_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(must|always|never|certainly|definitely|undoubtedly|obviously|clearly|cannot|can't|impossible|have to|has to|need to|should|everyone|everybody|no one|nobody|nothing|every time)\b",
+    )
+    .expect("strong modal regex")
+});
+
+/// Confidence in regex modality detection: markers are unambiguous words, but
+/// some have non-modal senses ("May", "clearly visible").
+const MODALITY_CONFIDENCE: f64 = 0.75;
+
+/// Detect hedges and strong modals, each marker reported once per sentence.
+fn detect_modality(text: &str) -> Vec<ModalityInstance> {
+    let mut results = Vec::new();
+    for span in sentence_spans(text) {
+        let trimmed = &text[span.clone()];
+        let mut seen = HashSet::new();
+        for (re, strength) in [
+            (&*HEDGE_RE, ModalityStrength::Hedge),
+            (&*STRONG_MODAL_RE, ModalityStrength::Strong),
+        ] {
+            for m in re.find_iter(trimmed) {
+                let marker = m.as_str().to_lowercase();
+                if seen.insert(marker.clone()) {
+                    let offsets =
+                        text_util::char_range(text, span.start + m.start()..span.start + m.end());
+                    results.push(ModalityInstance {
+                        marker,
+                        strength,
+                        sentence: trimmed.to_string(),
+                        confidence: MODALITY_CONFIDENCE,
+                        chunk_index: None,
+                        start: Some(offsets.start),
+                        end: Some(offsets.end),
+                    });
+                }
+            }
         }
     }
-
     results
 }
 
-/// Detect nominalisations: nouns derived from verbs (e.g., "destruction" from "destroy").
-fn detect_nominalisations(text: &str) -> Vec<Nominalisation> {
-    let patterns = [
-        (r"\b(\w+tion)\b", "tion"),
-        (r"\b(\w+sion)\b", "sion"),
-        (r"\b(\w+ment)\b", "ment"),
-        (r"\b(\w+ance)\b", "ance"),
-        (r"\b(\w+ence)\b", "ence"),
-        (r"\b(\w+ity)\b", "ity"),
-        (r"\b(\w+ness)\b", "ness"),
-        (r"\b(\w+ism)\b", "ism"),
-    ];
-
-    // Common words that are NOT nominalisations.
-    let exceptions = [
+/// Common words with nominal suffixes that are NOT nominalisations.
+static NOMINALISATION_EXCEPTIONS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+    [
         "information",
         "situation",
         "question",
@@ -157,16 +237,77 @@ fn detect_nominalisations(text: &str) -> Vec<Nominalisation> {
         "organism",
         "capitalism",
         "socialism",
+        "action",
+        "fiction",
+        "function",
+        "mission",
+        "passion",
+        "pension",
+        "tension",
+        "vision",
+        "division",
+        "payment",
+        "segment",
+        "fragment",
+        "garment",
+        "torment",
+        "cement",
+        "sentiment",
+        "instrument",
+        "parliament",
+        "balance",
+        "finance",
+        "science",
+        "audience",
+        "sequence",
+        "conscience",
+        "city",
+        "entity",
+        "unity",
+        "charity",
+        "gravity",
+        "witness",
+        "harness",
+        "wilderness",
+        "prism",
+        "tourism",
+        "baptism",
+    ]
+    .into_iter()
+    .collect()
+});
+
+/// Detect nominalisations: nouns derived from verbs (e.g., "destruction" from "destroy").
+///
+/// Each word is reported once with its occurrence count, ranked by
+/// [`rank_nominalisations`].
+fn detect_nominalisations(text: &str) -> Vec<Nominalisation> {
+    let patterns = [
+        (r"(?i)\b(\w+tion)\b", "tion"),
+        (r"(?i)\b(\w+sion)\b", "sion"),
+        (r"(?i)\b(\w+ment)\b", "ment"),
+        (r"(?i)\b(\w+ance)\b", "ance"),
+        (r"(?i)\b(\w+ence)\b", "ence"),
+        (r"(?i)\b(\w+ity)\b", "ity"),
+        (r"(?i)\b(\w+ness)\b", "ness"),
+        (r"(?i)\b(\w+ism)\b", "ism"),
     ];
 
-    let word_lower = text.to_lowercase();
-    let mut results = Vec::new();
+    // Match case-insensitively against the original text so offsets stay valid.
+    let mut results: Vec<Nominalisation> = Vec::new();
+    let mut seen: HashMap<String, usize> = HashMap::new();
 
     for (pattern, suffix) in &patterns {
         let re = Regex::new(pattern).expect("nominalisation regex");
-        for cap in re.captures_iter(&word_lower) {
-            let word = cap[1].to_string();
-            if exceptions.contains(&word.as_str()) {
+        for cap in re.captures_iter(text) {
+            let found = cap.get(1).expect("nominalisation capture");
+            let lower = found.as_str().to_lowercase();
+            let word = lower.as_str();
+            if NOMINALISATION_EXCEPTIONS.contains(word) {
+                continue;
+            }
+            if let Some(&idx) = seen.get(word) {
+                results[idx].frequency += 1;
                 continue;
             }
 
@@ -182,24 +323,55 @@ fn detect_nominalisations(text: &str) -> Vec<Nominalisation> {
                 "ity" => word.trim_end_matches("ity").to_string(),
                 "ness" => word.trim_end_matches("ness").to_string(),
                 "ism" => word.trim_end_matches("ism").to_string(),
-                _ => word.clone(),
+                _ => word.to_string(),
             };
 
+            let offsets = text_util::char_range(text, found.range());
+            seen.insert(word.to_string(), results.len());
             results.push(Nominalisation {
-                original: word.clone(),
+                original: word.to_string(),
                 verb_form,
                 effect: "Converts a process into a thing, hiding who does the action".to_string(),
+                frequency: 1,
+                confidence: 0.0,
+                chunk_index: None,
+                start: Some(offsets.start),
+                end: Some(offsets.end),
             });
         }
     }
 
+    // A recoverable verb is good evidence the word really is a nominalisation.
+    for n in &mut results {
+        n.confidence = if is_reconstructable(n) { 0.8 } else { 0.5 };
+    }
+
+    rank_nominalisations(&mut results);
     results
 }
 
+/// Order nominalisations most significant first: those whose verb form could
+/// be reconstructed, then the least frequent, since a word used throughout a
+/// text is more likely a term of art than a choice to hide an actor.
+pub fn rank_nominalisations(nominalisations: &mut [Nominalisation]) {
+    nominalisations.sort_by(|a, b| {
+        is_reconstructable(b)
+            .cmp(&is_reconstructable(a))
+            .then(a.frequency.cmp(&b.frequency))
+            .then_with(|| a.original.cmp(&b.original))
+    });
+}
+
+/// Whether the verb form looks like a real stem rather than a fragment.
+fn is_reconstructable(n: &Nominalisation) -> bool {
+    n.verb_form.len() >= 3 && n.verb_form != n.original
+}
+
 /// Combined Ollama call for complexity + transitivity analysis.
 async fn analyze_combined(
     state: &AppState,
     text: &str,
+    context: &[String],
 ) -> Result<(Vec<SentenceComplexity>, Vec<TransitivityInstance>)> {
     let system = r#"Perform two analyses on the given text and return a single JSON object with two arrays:
 
@@ -208,6 +380,7 @@ async fn analyze_combined(
    - "score": complexity score 0.0-1.0
    - "clause_count": number of clauses
    - "note": brief note on complexity
+   - "confidence": 0.0-1.0, how certain you are of this finding
    Limit to 5 most notable sentences.
 
 2. "processes": Transitivity analysis (who does what to whom). Each entry has:
@@ -216,51 +389,72 @@ async fn analyze_combined(
    - "process": the action/verb
    - "affected": who/what is affected
    - "analysis": brief note on power/agency
+   - "confidence": 0.0-1.0, how certain you are of this finding
    Limit to 5 most significant processes."#;
 
+    // Give the model our sentence boundaries so it doesn't re-split abbreviations.
+    let numbered: Vec<String> = split_sentences(text)
+        .iter()
+        .enumerate()
+        .map(|(i, s)| format!("{}. {s}", i + 1))
+        .collect();
+    let prompt = format!(
+        "{}\n\nSentences:\n{}",
+        with_context(context, text),
+        numbered.join("\n")
+    );
+
     let result: CombinedSyntacticResponse = state
-        .ollama
-        .generate_json(text, Some(system))
+        .llm
+        .generate_analysis_json(&prompt, Some(system))
         .await
         .unwrap_or_else(|_| CombinedSyntacticResponse {
             sentences: Vec::new(),
             processes: Vec::new(),
         });
 
+    let mut claimed = Vec::new();
     let complexity = result
         .sentences
         .into_iter()
-        .map(|s| SentenceComplexity {
-            sentence: s.sentence,
-            score: s.score,
-            clause_count: s.clause_count,
-            note: s.note,
+        .map(|s| {
+            let offsets = text_util::locate(text, &s.sentence, &mut claimed);
+            SentenceComplexity {
+                sentence: s.sentence,
+                score: s.score,
+                clause_count: s.clause_count,
+                note: s.note,
+                confidence: confidence(s.confidence),
+                chunk_index: None,
+                start: offsets.as_ref().map(|r| r.start),
+                end: offsets.map(|r| r.end),
+            }
         })
         .collect();
 
+    let mut claimed = Vec::new();
     let transitivity = result
         .processes
         .into_iter()
-        .map(|t| TransitivityInstance {
-            sentence: t.sentence,
-            actor: t.actor,
-            process: t.process,
-            affected: t.affected,
-            analysis: t.analysis,
+        .map(|t| {
+            let offsets = text_util::locate(text, &t.sentence, &mut claimed);
+            TransitivityInstance {
+                sentence: t.sentence,
+                actor: t.actor,
+                process: t.process,
+                affected: t.affected,
+                analysis: t.analysis,
+                confidence: confidence(t.confidence),
+                chunk_index: None,
+                start: offsets.as_ref().map(|r| r.start),
+                end: offsets.map(|r| r.end),
+            }
         })
         .collect();
 
     Ok((complexity, transitivity))
 }
 
-fn split_sentences(text: &str) -> Vec<String> {
-    let re = Regex::new(r"[.!?]+\s+|[.!?]+$").expect("sentence split regex");
-    re.split(text)
-        .map(|s| s.trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect()
-}
-
 #[derive(Deserialize)]
 struct CombinedSyntacticResponse {
     #[serde(default)]
@@ -275,6 +469,8 @@ struct ComplexityEntry {
     score: f64,
     clause_count: u32,
     note: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -284,4 +480,76 @@ struct TransitivityEntry {
     process: String,
     affected: String,
     analysis: String,
+    #[serde(default)]
+    confidence: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nominalisations_rank_least_frequent_first() {
+        let text = "The organisation's decision followed the organisation's investigation.";
+        let found: Vec<(String, usize)> = detect_nominalisations(text)
+            .into_iter()
+            .map(|n| (n.original, n.frequency))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("decision".to_string(), 1),
+                ("investigation".to_string(), 1),
+                ("organisation".to_string(), 2),
+            ]
+        );
+    }
+
+    fn modality(text: &str) -> Vec<(String, ModalityStrength)> {
+        detect_modality(text)
+            .into_iter()
+            .map(|m| (m.marker, m.strength))
+            .collect()
+    }
+
+    #[test]
+    fn modality_markers_are_classified() {
+        assert_eq!(
+            modality("I think we must leave. Perhaps it is always like this."),
+            [
+                ("i think".to_string(), ModalityStrength::Hedge),
+                ("must".to_string(), ModalityStrength::Strong),
+                ("perhaps".to_string(), ModalityStrength::Hedge),
+                ("always".to_string(), ModalityStrength::Strong),
+            ]
+        );
+        assert!(modality("The meeting starts at noon.").is_empty());
+    }
+
+    #[test]
+    fn modality_markers_are_reported_once_per_sentence() {
+        let found: Vec<(String, String, Option<usize>)> =
+            detect_modality("You must, you must, you must. You must not.")
+                .into_iter()
+                .map(|m| (m.marker, m.sentence, m.start))
+                .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "must".to_string(),
+                    "You must, you must, you must.".to_string(),
+                    Some(4)
+                ),
+                ("must".to_string(), "You must not.".to_string(), Some(34)),
+            ]
+        );
+    }
+
+    #[test]
+    fn modality_offsets_count_characters() {
+        let found = detect_modality("Café owners should open early.");
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].start, found[0].end), (Some(12), Some(18)));
+    }
 }
@@ -2,40 +2,57 @@ use anyhow::Result;
 use serde::Deserialize;
 
 use crate::api::state::AppState;
+use crate::perspective::engine::{confidence, with_context};
 use nexus_common::types::{
     AlternativeFraming, BeneficiaryAnalysis, CriticalSynthesis, HiddenContext, NaturalisedClaim,
 };
 
-/// Layer 4: Critical synthesis via a single Ollama call.
-/// This layer produces the highest-level critical insights.
-pub async fn analyze(state: &AppState, text: &str) -> Result<CriticalSynthesis> {
-    let system = r#"Perform a critical synthesis of the given text. Return a single JSON object with these four arrays:
+/// Intensity used when a request doesn't set one: a balanced critique.
+pub const DEFAULT_INTENSITY: f64 = 0.5;
+
+const SYSTEM_PROMPT: &str = r#"Perform a critical synthesis of the given text. Return a single JSON object with these four arrays:
 
 1. "claims": Naturalised claims — claims presented as natural/obvious but actually contestable. Each entry:
    - "claim": the naturalised claim
    - "how_naturalised": how it's made to seem natural
    - "counter_evidence": evidence that challenges this claim
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 2. "beneficiaries": Who benefits and who is disadvantaged by the framing. Each entry:
    - "who_benefits": who gains from this framing
    - "how": how they benefit
    - "who_is_disadvantaged": who loses or is marginalized
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 3. "contexts": Hidden contexts — background information not mentioned but significant. Each entry:
    - "context": the hidden context
    - "relevance": why it's relevant
    - "why_hidden": why this context might be omitted
+   - "confidence": 0.0-1.0, how certain you are of this finding
 
 4. "framings": Alternative framings using the same facts. Each entry:
    - "original_frame": how it's currently framed
    - "alternative": the alternative framing
    - "same_facts_used": which facts from the original are used
+   - "confidence": 0.0-1.0, how certain you are of this finding"#;
 
-Limit each array to at most 3 entries. Focus on the most significant findings."#;
+/// Layer 4: Critical synthesis via a single Ollama call.
+/// This layer produces the highest-level critical insights.
+///
+/// `intensity` (0–1) sets how hard it plays devil's advocate: from a gentle,
+/// educational reading with few alternatives to an adversarial audit that
+/// demands more counter-framings and doubts every claim.
+pub async fn analyze(
+    state: &AppState,
+    text: &str,
+    context: &[String],
+    intensity: f64,
+) -> Result<CriticalSynthesis> {
+    let system = format!("{SYSTEM_PROMPT}\n\n{}", intensity_instructions(intensity));
 
     let result: CombinedSynthesisResponse = state
-        .ollama
-        .generate_json(text, Some(system))
+        .llm
+        .generate_analysis_json(&with_context(context, text), Some(&system))
         .await
         .unwrap_or_else(|_| CombinedSynthesisResponse::default());
 
@@ -47,6 +64,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 claim: c.claim,
                 how_naturalised: c.how_naturalised,
                 counter_evidence: c.counter_evidence,
+                confidence: confidence(c.confidence),
+                chunk_index: None,
             })
             .collect(),
         beneficiary_analysis: result
@@ -56,6 +75,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 who_benefits: b.who_benefits,
                 how: b.how,
                 who_is_disadvantaged: b.who_is_disadvantaged,
+                confidence: confidence(b.confidence),
+                chunk_index: None,
             })
             .collect(),
         hidden_contexts: result
@@ -65,6 +86,8 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 context: c.context,
                 relevance: c.relevance,
                 why_hidden: c.why_hidden,
+                confidence: confidence(c.confidence),
+                chunk_index: None,
             })
             .collect(),
         alternative_framings: result
@@ -74,11 +97,42 @@ Limit each array to at most 3 entries. Focus on the most significant findings."#
                 original_frame: f.original_frame,
                 alternative: f.alternative,
                 same_facts_used: f.same_facts_used,
+                confidence: confidence(f.confidence),
+                chunk_index: None,
             })
             .collect(),
     })
 }
 
+/// Tone and volume of the critique for an intensity. At the default this asks
+/// for up to three of each finding, as the prompt always did.
+fn intensity_instructions(intensity: f64) -> String {
+    let intensity = if intensity.is_finite() {
+        intensity.clamp(0.0, 1.0)
+    } else {
+        DEFAULT_INTENSITY
+    };
+    let per_array = 1 + (intensity * 4.0).round() as usize;
+    let framings = if intensity >= 0.75 {
+        format!("Give at least {per_array} alternative framings.")
+    } else {
+        format!("Give up to {per_array} alternative framings.")
+    };
+    let tone = if intensity < 0.34 {
+        "Be measured and explanatory, as for a student: point out only claims that are clearly \
+         contestable, and acknowledge where the text's framing is reasonable."
+    } else if intensity < 0.75 {
+        "Be even-handed: challenge contestable claims without treating every statement as suspect."
+    } else {
+        "Act as an adversarial auditor and devil's advocate: treat every claim as contestable \
+         until shown otherwise, and press hardest on who benefits from the framing."
+    };
+    format!(
+        "{framings} Limit the other arrays to at most {per_array} entries. {tone} \
+         Focus on the most significant findings."
+    )
+}
+
 #[derive(Default, Deserialize)]
 struct CombinedSynthesisResponse {
     #[serde(default)]
@@ -96,6 +150,8 @@ struct NaturalisedEntry {
     claim: String,
     how_naturalised: String,
     counter_evidence: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -103,6 +159,8 @@ struct BeneficiaryEntry {
     who_benefits: String,
     how: String,
     who_is_disadvantaged: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -110,6 +168,8 @@ struct ContextEntry {
     context: String,
     relevance: String,
     why_hidden: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
 
 #[derive(Deserialize)]
@@ -117,4 +177,6 @@ struct FramingEntry {
     original_frame: String,
     alternative: String,
     same_facts_used: String,
+    #[serde(default)]
+    confidence: Option<f64>,
 }
@@ -0,0 +1,188 @@
+use anyhow::Result;
+use chrono::{DateTime, SubsecRound, Utc};
+use sha2::{Digest, Sha256};
+use std::fmt::Display;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use nexus_common::error::NexusError;
+use nexus_common::types::{BeliefAuditAction, BeliefAuditEntry};
+
+/// A belief change to append to the audit log.
+#[derive(Debug, Clone)]
+pub struct AuditRecord<'a> {
+    pub belief_id: Uuid,
+    pub user_id: Uuid,
+    pub action: BeliefAuditAction,
+    pub old_confidence: Option<f64>,
+    pub new_confidence: Option<f64>,
+    pub source_message_id: Option<Uuid>,
+    /// Who made the change; `None` for the system.
+    pub actor_id: Option<Uuid>,
+    pub reason: &'a str,
+}
+
+/// Append `record` to its user's log, chained to their latest entry.
+///
+/// Runs on the caller's transaction so the entry commits or rolls back with
+/// the change it describes. A per-user advisory lock, held until the
+/// transaction ends, keeps concurrent entries from chaining to the same parent.
+pub async fn record(conn: &mut sqlx::PgConnection, record: &AuditRecord<'_>) -> Result<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(format!("belief_audit:{}", record.user_id))
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to lock belief audit log: {e}")))?;
+
+    let prev: Option<(String,)> =
+        sqlx::query_as("SELECT hash FROM belief_audit WHERE user_id = $1 ORDER BY id DESC LIMIT 1")
+            .bind(record.user_id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(|e| NexusError::Database(format!("Failed to read belief audit log: {e}")))?;
+    let prev_hash = prev.map(|(hash,)| hash);
+
+    // Postgres keeps microseconds; hash what will be stored.
+    let created_at = Utc::now().trunc_subsecs(6);
+    let hash = entry_hash(prev_hash.as_deref(), record, created_at);
+
+    sqlx::query(
+        "INSERT INTO belief_audit
+             (belief_id, user_id, action, old_confidence, new_confidence, source_message_id,
+              actor_id, reason, prev_hash, hash, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+    )
+    .bind(record.belief_id)
+    .bind(record.user_id)
+    .bind(record.action.as_str())
+    .bind(record.old_confidence)
+    .bind(record.new_confidence)
+    .bind(record.source_message_id)
+    .bind(record.actor_id)
+    .bind(record.reason)
+    .bind(&prev_hash)
+    .bind(&hash)
+    .bind(created_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to write belief audit log: {e}")))?;
+
+    Ok(())
+}
+
+/// Record changes that were already made, e.g. deletions whose ids are only
+/// known once the graph write returns.
+pub async fn record_all(state: &AppState, records: &[AuditRecord<'_>]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    let mut tx = state
+        .db
+        .pg
+        .begin()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+    for r in records {
+        record(&mut tx, r).await?;
+    }
+    tx.commit()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to write belief audit log: {e}")))?;
+    Ok(())
+}
+
+/// A page of the user's log, newest first, starting below entry `before`.
+pub async fn list(
+    state: &AppState,
+    user_id: Uuid,
+    before: Option<i64>,
+    limit: usize,
+) -> Result<Vec<BeliefAuditEntry>> {
+    type Row = (
+        i64,
+        Uuid,
+        Uuid,
+        String,
+        Option<f64>,
+        Option<f64>,
+        Option<Uuid>,
+        Option<Uuid>,
+        String,
+        Option<String>,
+        String,
+        DateTime<Utc>,
+    );
+    let rows: Vec<Row> = sqlx::query_as(
+        "SELECT id, belief_id, user_id, action, old_confidence, new_confidence,
+                source_message_id, actor_id, reason, prev_hash, hash, created_at
+         FROM belief_audit
+         WHERE user_id = $1 AND ($2::BIGINT IS NULL OR id < $2)
+         ORDER BY id DESC
+         LIMIT $3",
+    )
+    .bind(user_id)
+    .bind(before)
+    .bind(limit as i64)
+    .fetch_all(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to read belief audit log: {e}")))?;
+
+    Ok(rows
+        .into_iter()
+        .map(
+            |(
+                id,
+                belief_id,
+                user_id,
+                action,
+                old_confidence,
+                new_confidence,
+                source_message_id,
+                actor_id,
+                reason,
+                prev_hash,
+                hash,
+                created_at,
+            )| BeliefAuditEntry {
+                id,
+                belief_id,
+                user_id,
+                action,
+                old_confidence,
+                new_confidence,
+                source_message_id,
+                actor_id,
+                reason,
+                prev_hash,
+                hash,
+                created_at,
+            },
+        )
+        .collect())
+}
+
+/// Hex SHA-256 over the entry's fields and its predecessor's hash.
+fn entry_hash(
+    prev_hash: Option<&str>,
+    record: &AuditRecord<'_>,
+    created_at: DateTime<Utc>,
+) -> String {
+    let fields = [
+        opt(prev_hash),
+        record.belief_id.to_string(),
+        record.user_id.to_string(),
+        record.action.as_str().to_string(),
+        opt(record.old_confidence),
+        opt(record.new_confidence),
+        opt(record.source_message_id),
+        opt(record.actor_id),
+        record.reason.to_string(),
+        created_at.to_rfc3339(),
+    ];
+    let digest = Sha256::digest(fields.join("|").as_bytes());
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn opt<T: Display>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::db::redis::record_backend_error;
+use crate::river::beliefs;
+use nexus_common::types::{
+    BeliefCategoryCount, BeliefEdgeKind, BeliefGraph, BeliefSummary, ConfidenceBucket,
+};
+
+/// Summaries are keyed by belief-set hash, so this only bounds how long a
+/// stale entry lingers after the beliefs change.
+const CACHE_TTL_SECS: u64 = 86400;
+
+/// Number of equal-width confidence buckets between 0.0 and 1.0.
+const CONFIDENCE_BUCKETS: usize = 5;
+
+/// Beliefs and tensions included in the summary prompt, strongest first.
+const PROMPT_BELIEFS: usize = 40;
+const PROMPT_TENSIONS: usize = 10;
+
+/// Summarize a user's belief network: confidence histogram and categories
+/// computed locally, plus a short LLM-written narrative of the worldview and
+/// its tensions. Cached until the belief set changes.
+pub async fn summarize(state: &AppState, user_id: Uuid) -> Result<BeliefSummary> {
+    let graph = beliefs::get_belief_graph(state, user_id).await?;
+    let key = cache_key(user_id, &graph);
+
+    if let Some(cached) = get_cached(state, &key).await {
+        return Ok(cached);
+    }
+
+    let narrative = if graph.nodes.is_empty() {
+        "No beliefs have been recorded yet.".to_string()
+    } else {
+        narrate(state, &graph).await?
+    };
+
+    let summary = BeliefSummary {
+        total: graph.nodes.len(),
+        confidence_histogram: confidence_histogram(&graph),
+        categories: category_counts(&graph),
+        tensions: tensions(&graph).len(),
+        narrative,
+    };
+
+    set_cached(state, &key, &summary).await;
+    Ok(summary)
+}
+
+/// Ask the LLM for a short narrative of the belief network.
+async fn narrate(state: &AppState, graph: &BeliefGraph) -> Result<String> {
+    let mut strongest: Vec<_> = graph.nodes.iter().collect();
+    strongest.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let belief_lines: Vec<String> = strongest
+        .iter()
+        .take(PROMPT_BELIEFS)
+        .map(|b| {
+            format!(
+                "- \"{}\" (confidence {:.1}, {})",
+                b.claim,
+                b.confidence,
+                b.category.as_deref().unwrap_or("uncategorized")
+            )
+        })
+        .collect();
+    let tension_lines: Vec<String> = tensions(graph)
+        .into_iter()
+        .take(PROMPT_TENSIONS)
+        .map(|(a, b, why)| format!("- \"{a}\" vs \"{b}\": {why}"))
+        .collect();
+
+    let system = r#"You are summarizing a person's belief network for them. In one or two short paragraphs, describe the main themes of their worldview, where their confidence is strongest and weakest, and the most significant internal tensions. Write in the second person ("You tend to..."). Be descriptive and neutral — do not judge or argue with the beliefs."#;
+
+    let mut prompt = format!("Beliefs:\n{}", belief_lines.join("\n"));
+    if !tension_lines.is_empty() {
+        prompt.push_str(&format!(
+            "\n\nContradictions:\n{}",
+            tension_lines.join("\n")
+        ));
+    }
+
+    let narrative = state
+        .llm
+        .generate(&prompt, Some(system))
+        .await
+        .context("Failed to generate belief summary")?;
+
+    Ok(narrative.trim().to_string())
+}
+
+/// Contradicting claim pairs with their explanation, most severe first.
+fn tensions(graph: &BeliefGraph) -> Vec<(&str, &str, &str)> {
+    let claim = |id: Uuid| {
+        graph
+            .nodes
+            .iter()
+            .find(|b| b.id == id)
+            .map(|b| b.claim.as_str())
+    };
+
+    let mut edges: Vec<_> = graph
+        .edges
+        .iter()
+        .filter(|e| matches!(e.kind, BeliefEdgeKind::Contradicts))
+        .collect();
+    edges.sort_by(|a, b| {
+        b.severity
+            .unwrap_or(0.0)
+            .total_cmp(&a.severity.unwrap_or(0.0))
+    });
+
+    edges
+        .into_iter()
+        .filter_map(|e| {
+            Some((
+                claim(e.source)?,
+                claim(e.target)?,
+                e.explanation.as_deref().unwrap_or(""),
+            ))
+        })
+        .collect()
+}
+
+fn confidence_histogram(graph: &BeliefGraph) -> Vec<ConfidenceBucket> {
+    let width = 1.0 / CONFIDENCE_BUCKETS as f64;
+    let mut buckets: Vec<ConfidenceBucket> = (0..CONFIDENCE_BUCKETS)
+        .map(|i| ConfidenceBucket {
+            min: i as f64 * width,
+            max: (i + 1) as f64 * width,
+            count: 0,
+        })
+        .collect();
+
+    for b in &graph.nodes {
+        // 1.0 belongs in the top bucket rather than one past it.
+        let i = ((b.confidence.clamp(0.0, 1.0) / width) as usize).min(CONFIDENCE_BUCKETS - 1);
+        buckets[i].count += 1;
+    }
+    buckets
+}
+
+/// Beliefs per category, largest first.
+fn category_counts(graph: &BeliefGraph) -> Vec<BeliefCategoryCount> {
+    let mut counts: BTreeMap<&str, i64> = BTreeMap::new();
+    for b in &graph.nodes {
+        *counts
+            .entry(b.category.as_deref().unwrap_or("uncategorized"))
+            .or_default() += 1;
+    }
+
+    let mut categories: Vec<BeliefCategoryCount> = counts
+        .into_iter()
+        .map(|(category, count)| BeliefCategoryCount {
+            category: category.to_string(),
+            count,
+        })
+        .collect();
+    categories.sort_by(|a, b| b.count.cmp(&a.count));
+    categories
+}
+
+/// Key on everything the summary depends on, so any change to the beliefs or
+/// their contradictions produces a fresh summary.
+fn cache_key(user_id: Uuid, graph: &BeliefGraph) -> String {
+    let mut nodes: Vec<_> = graph
+        .nodes
+        .iter()
+        .map(|b| (b.id, b.confidence.to_bits(), b.updated_at))
+        .collect();
+    nodes.sort();
+    let mut edges: Vec<_> = graph.edges.iter().map(|e| (e.source, e.target)).collect();
+    edges.sort();
+
+    // Fixed-width fields behind a count, so no two graphs hash the same bytes.
+    let mut hasher = Sha256::new();
+    hasher.update((nodes.len() as u64).to_le_bytes());
+    for (id, confidence, updated_at) in nodes {
+        hasher.update(id.as_bytes());
+        hasher.update(confidence.to_le_bytes());
+        hasher.update(updated_at.timestamp_micros().to_le_bytes());
+    }
+    for (source, target) in edges {
+        hasher.update(source.as_bytes());
+        hasher.update(target.as_bytes());
+    }
+    let digest: String = hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    format!("belief_summary:{user_id}:{digest}")
+}
+
+async fn get_cached(state: &AppState, key: &str) -> Option<BeliefSummary> {
+    let mut conn = state.db.redis.clone();
+    let raw: Option<String> = redis::cmd("GET")
+        .arg(key)
+        .query_async(&mut conn)
+        .await
+        .inspect_err(|e| record_backend_error("belief_summary_get", e))
+        .ok()?;
+    serde_json::from_str(&raw?).ok()
+}
+
+async fn set_cached(state: &AppState, key: &str, summary: &BeliefSummary) {
+    let Ok(json) = serde_json::to_string(summary) else {
+        return;
+    };
+    let mut conn = state.db.redis.clone();
+    let _ = redis::cmd("SET")
+        .arg(key)
+        .arg(json)
+        .arg("EX")
+        .arg(CACHE_TTL_SECS)
+        .query_async::<()>(&mut conn)
+        .await
+        .inspect_err(|e| record_backend_error("belief_summary_set", e));
+}
@@ -1,11 +1,99 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
 use chrono::Utc;
 use neo4rs::query;
-use serde::Deserialize;
+use qdrant_client::qdrant::point_id::PointIdOptions;
+use qdrant_client::qdrant::{
+    Condition, CreateCollectionBuilder, DeletePointsBuilder, Filter, PointId, PointStruct,
+    PointsIdsList, SearchPointsBuilder, UpsertPointsBuilder, VectorParamsBuilder,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use uuid::Uuid;
 
+use crate::api::outbox::{self, OutboxOp};
 use crate::api::state::AppState;
-use nexus_common::types::{Belief, Contradiction};
+use crate::db::qdrant;
+use crate::river::belief_audit::{self, AuditRecord};
+use crate::river::contradictions;
+use crate::shared::llm::GenerateParams;
+use nexus_common::cursor::Cursor;
+use nexus_common::error::NexusError;
+use nexus_common::types::{
+    BELIEF_SNAPSHOT_VERSION, Belief, BeliefAuditAction, BeliefCategoryCount, BeliefEdge,
+    BeliefEdgeKind, BeliefGraph, BeliefSnapshot, Contradiction, ScoredBelief,
+};
+
+const COLLECTION_NAME: &str = "beliefs";
+
+/// Attempts before giving up on a belief revision that keeps losing races.
+const REVISION_ATTEMPTS: usize = 5;
+
+/// Base delay before retrying a revision that lost a race. Each retry waits a
+/// random 0.5–1.5× this, times the attempt number, so racing writers spread out.
+const REVISION_BACKOFF: Duration = Duration::from_millis(20);
+
+/// Category reported for beliefs extracted before categories existed.
+const UNCATEGORIZED: &str = "uncategorized";
+
+/// Ensure the belief embedding collection exists in Qdrant.
+pub async fn ensure_collection(state: &AppState) -> Result<()> {
+    let collections = state
+        .db
+        .qdrant
+        .list_collections()
+        .await
+        .map_err(NexusError::from)?;
+
+    let mut exists = collections
+        .collections
+        .iter()
+        .any(|c| c.name == COLLECTION_NAME);
+
+    let dim = state.embeddings.dimension();
+    if exists
+        && qdrant::drop_on_vector_mismatch(
+            &state.db.qdrant,
+            &state.config.qdrant,
+            COLLECTION_NAME,
+            "",
+            dim,
+        )
+        .await?
+    {
+        exists = false;
+    }
+
+    if !exists {
+        state
+            .db
+            .qdrant
+            .create_collection(
+                CreateCollectionBuilder::new(COLLECTION_NAME)
+                    .vectors_config(VectorParamsBuilder::new(dim, state.config.qdrant.distance)),
+            )
+            .await
+            .map_err(|e| {
+                NexusError::VectorStore(format!("Failed to create belief collection: {e}"))
+            })?;
+
+        tracing::info!("Created Qdrant collection: {COLLECTION_NAME}");
+    }
+
+    qdrant::ensure_keyword_indexes(&state.db.qdrant, COLLECTION_NAME, &["user_id"]).await
+}
+
+/// Sampling parameters for belief extraction and contradiction checks, from
+/// `BELIEF_EXTRACTION_TEMPERATURE` and `BELIEF_EXTRACTION_SEED`.
+fn extraction_params(state: &AppState) -> GenerateParams {
+    GenerateParams {
+        temperature: state.config.belief_extraction_temperature,
+        seed: state.config.belief_extraction_seed,
+        ..GenerateParams::JSON
+    }
+}
 
 /// Extract claims/beliefs from a user message using Ollama.
 pub async fn extract_beliefs(state: &AppState, message: &str) -> Result<Vec<ExtractedClaim>> {
@@ -13,18 +101,32 @@ pub async fn extract_beliefs(state: &AppState, message: &str) -> Result<Vec<Extr
 - "claim": the belief statement
 - "confidence": how confidently the user holds it (0.0-1.0)
 - "is_explicit": whether they directly stated it (true) or it's implied (false)
+- "category": a short lowercase topic for the claim, e.g. "politics", "self", "ethics", "relationships", "work", "science"
 
 Only extract genuine belief claims, not questions or meta-commentary. If there are no claims, return {"claims": []}."#;
 
     let prompt = format!("Extract beliefs from this message:\n\n\"{message}\"");
 
     let result: ClaimsResponse = state
-        .ollama
-        .generate_json(&prompt, Some(system))
+        .llm
+        .generate_json_with(&prompt, Some(system), extraction_params(state))
         .await
         .context("Failed to extract beliefs")?;
 
-    Ok(result.claims)
+    Ok(result
+        .claims
+        .into_iter()
+        .map(|mut c| {
+            c.category = c.category.as_deref().and_then(normalize_category);
+            c
+        })
+        .collect())
+}
+
+/// Trim and lowercase a category, treating blanks as absent.
+pub fn normalize_category(category: &str) -> Option<String> {
+    let category = category.trim().to_lowercase();
+    (!category.is_empty()).then_some(category)
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,12 +134,14 @@ struct ClaimsResponse {
     claims: Vec<ExtractedClaim>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedClaim {
     pub claim: String,
     pub confidence: f64,
     #[serde(default)]
     pub is_explicit: bool,
+    #[serde(default)]
+    pub category: Option<String>,
 }
 
 /// Store a belief in Neo4j and return the Belief struct.
@@ -57,8 +161,10 @@ pub async fn store_belief(
              claim: $claim,
              confidence: $confidence,
              source_message_id: $source_msg_id,
+             category: $category,
              created_at: $created_at,
-             updated_at: $updated_at
+             updated_at: $updated_at,
+             version: 0
          })
          CREATE (u)-[:HOLDS]->(b)
          RETURN b.id AS id",
@@ -68,47 +174,426 @@ pub async fn store_belief(
     .param("claim", claim.claim.clone())
     .param("confidence", claim.confidence)
     .param("source_msg_id", source_message_id.to_string())
+    .param("category", claim.category.clone())
     .param("created_at", now.to_rfc3339())
     .param("updated_at", now.to_rfc3339());
 
+    // The audit entry commits only once the graph write has succeeded.
+    let mut tx = state
+        .db
+        .pg
+        .begin()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+    let audit = AuditRecord {
+        belief_id,
+        user_id,
+        action: BeliefAuditAction::Created,
+        old_confidence: None,
+        new_confidence: Some(claim.confidence),
+        source_message_id: Some(source_message_id),
+        actor_id: Some(user_id),
+        reason: "Extracted from a message",
+    };
+    belief_audit::record(&mut tx, &audit).await?;
+
     state
         .db
         .neo4j
         .run(q)
         .await
-        .context("Failed to store belief in Neo4j")?;
+        .map_err(|e| NexusError::Neo4j(format!("Failed to store belief in Neo4j: {e}")))?;
+    tx.commit()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to write belief audit log: {e}")))?;
 
-    Ok(Belief {
+    let belief = Belief {
         id: belief_id,
         user_id,
         claim: claim.claim.clone(),
         confidence: claim.confidence,
         source_message_id,
+        category: claim.category.clone(),
         created_at: now,
         updated_at: now,
-    })
+    };
+
+    index_belief_or_queue(state, &belief).await;
+
+    if let Err(e) = evict_excess_beliefs(state, user_id, belief_id).await {
+        tracing::warn!(%user_id, "Failed to evict excess beliefs: {e}");
+    }
+
+    Ok(belief)
 }
 
-/// Retrieve all beliefs for a user from Neo4j.
-pub async fn get_user_beliefs(state: &AppState, user_id: Uuid) -> Result<Vec<Belief>> {
+/// Index a belief's embedding, queueing the write in the outbox if it fails.
+async fn index_belief_or_queue(state: &AppState, belief: &Belief) {
+    if let Err(e) = index_belief(state, belief).await {
+        tracing::warn!(belief_id = %belief.id, "Failed to index belief embedding, queueing retry: {e}");
+        let op = OutboxOp::IndexBelief {
+            belief: belief.clone(),
+        };
+        if let Err(e) = outbox::enqueue(&state.db.pg, &op).await {
+            tracing::error!(belief_id = %belief.id, "Failed to queue belief indexing: {e:#}");
+        }
+    }
+}
+
+/// Enforce `MAX_BELIEFS_PER_USER` by deleting the lowest-confidence beliefs,
+/// oldest-updated first. The belief just stored (`keep`) is never evicted.
+async fn evict_excess_beliefs(state: &AppState, user_id: Uuid, keep: Uuid) -> Result<()> {
     let q = query(
         "MATCH (u:User {id: $user_id})-[:HOLDS]->(b:Belief)
-         RETURN b.id AS id, b.claim AS claim, b.confidence AS confidence,
-                b.source_message_id AS source_message_id,
-                b.created_at AS created_at, b.updated_at AS updated_at
-         ORDER BY b.created_at DESC",
+         WITH u, count(b) - $max AS excess
+         WHERE excess > 0
+         MATCH (u)-[:HOLDS]->(b:Belief)
+         WHERE b.id <> $keep
+         WITH b, excess
+         ORDER BY b.confidence ASC, b.updated_at ASC
+         WITH excess, collect(b) AS ranked
+         UNWIND ranked[..excess] AS evicted
+         WITH evicted, evicted.id AS id, evicted.confidence AS confidence,
+              evicted.source_message_id AS source_message_id
+         DETACH DELETE evicted
+         RETURN id, confidence, source_message_id",
     )
-    .param("user_id", user_id.to_string());
+    .param("user_id", user_id.to_string())
+    .param("max", state.config.max_beliefs_per_user as i64)
+    .param("keep", keep.to_string());
 
     let mut result = state
         .db
         .neo4j
         .execute(q)
         .await
-        .context("Failed to query beliefs from Neo4j")?;
+        .map_err(|e| NexusError::Neo4j(format!("Failed to evict beliefs from Neo4j: {e}")))?;
+
+    let mut evicted: Vec<PointId> = Vec::new();
+    let mut audits = Vec::new();
+    while let Some(row) = result.next().await.map_err(NexusError::from)? {
+        let id: String = row.get("id").unwrap_or_default();
+        audits.push(deletion_audit(
+            user_id,
+            &row,
+            None,
+            "Evicted over the per-user belief cap",
+        ));
+        evicted.push(id.into());
+    }
+    if evicted.is_empty() {
+        return Ok(());
+    }
+
+    tracing::info!(%user_id, count = evicted.len(), "Evicted beliefs over the per-user cap");
+    if let Err(e) = belief_audit::record_all(state, &audits).await {
+        tracing::error!(%user_id, "Failed to audit evicted beliefs: {e:#}");
+    }
+    forget_contradictions(state, &audits).await;
+
+    state
+        .db
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(COLLECTION_NAME)
+                .points(PointsIdsList { ids: evicted })
+                .wait(true),
+        )
+        .await
+        .map_err(|e| {
+            NexusError::VectorStore(format!("Failed to delete evicted belief embeddings: {e}"))
+        })?;
+
+    Ok(())
+}
+
+/// Delete the beliefs a user's message produced, with their relationships and
+/// embeddings, e.g. when the message is edited and reprocessed. Returns how
+/// many were removed.
+pub async fn delete_message_beliefs(
+    state: &AppState,
+    user_id: Uuid,
+    message_id: Uuid,
+) -> Result<usize> {
+    let q = query(
+        "MATCH (:User {id: $user_id})-[:HOLDS]->(b:Belief {source_message_id: $message_id})
+         WITH b, b.id AS id, b.confidence AS confidence, b.source_message_id AS source_message_id
+         DETACH DELETE b
+         RETURN id, confidence, source_message_id",
+    )
+    .param("user_id", user_id.to_string())
+    .param("message_id", message_id.to_string());
+
+    let mut result = state.db.neo4j.execute(q).await.map_err(|e| {
+        NexusError::Neo4j(format!("Failed to delete message beliefs from Neo4j: {e}"))
+    })?;
+    let mut audits = Vec::new();
+    while let Some(row) = result.next().await.map_err(NexusError::from)? {
+        audits.push(deletion_audit(
+            user_id,
+            &row,
+            Some(user_id),
+            "Source message was edited",
+        ));
+    }
+    let deleted = audits.len();
+    if let Err(e) = belief_audit::record_all(state, &audits).await {
+        tracing::error!(%user_id, %message_id, "Failed to audit superseded beliefs: {e:#}");
+    }
+    forget_contradictions(state, &audits).await;
+
+    // By payload rather than id, so embeddings indexed under any id go too.
+    let filter = Filter::must([
+        Condition::matches("user_id", user_id.to_string()),
+        Condition::matches("source_message_id", message_id.to_string()),
+    ]);
+    state
+        .db
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(COLLECTION_NAME)
+                .points(filter)
+                .wait(true),
+        )
+        .await
+        .map_err(|e| {
+            NexusError::VectorStore(format!("Failed to delete message belief embeddings: {e}"))
+        })?;
+
+    tracing::info!(%user_id, %message_id, deleted, "Deleted beliefs from superseded message");
+    Ok(deleted)
+}
+
+/// Drop stored contradictions involving the beliefs these deletion audits
+/// describe. Failures are logged: the beliefs are already gone.
+async fn forget_contradictions(state: &AppState, deletions: &[AuditRecord<'_>]) {
+    let ids: Vec<Uuid> = deletions.iter().map(|a| a.belief_id).collect();
+    if let Err(e) = contradictions::forget(state, &ids).await {
+        tracing::warn!(
+            count = ids.len(),
+            "Failed to delete stored contradictions: {e:#}"
+        );
+    }
+}
+
+/// Delete these users' belief graphs, user nodes included, and their belief
+/// embeddings, for accounts being removed.
+pub async fn delete_user_beliefs(state: &AppState, user_ids: &[Uuid]) -> Result<()> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<String> = user_ids.iter().map(Uuid::to_string).collect();
+
+    let q = query(
+        "MATCH (u:User) WHERE u.id IN $user_ids
+         OPTIONAL MATCH (u)-[:HOLDS]->(b:Belief)
+         DETACH DELETE b, u",
+    )
+    .param("user_ids", ids.clone());
+    state
+        .db
+        .neo4j
+        .run(q)
+        .await
+        .map_err(|e| NexusError::Neo4j(format!("Failed to delete user beliefs from Neo4j: {e}")))?;
+
+    state
+        .db
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(COLLECTION_NAME)
+                .points(Filter::must([Condition::matches("user_id", ids)]))
+                .wait(true),
+        )
+        .await
+        .map_err(|e| {
+            NexusError::VectorStore(format!("Failed to delete user belief embeddings: {e}"))
+        })?;
+    Ok(())
+}
+
+/// Audit record for a deleted belief, from a row with its `id`, `confidence`
+/// and `source_message_id`.
+fn deletion_audit<'a>(
+    user_id: Uuid,
+    row: &neo4rs::Row,
+    actor_id: Option<Uuid>,
+    reason: &'a str,
+) -> AuditRecord<'a> {
+    let id: String = row.get("id").unwrap_or_default();
+    let source: String = row.get("source_message_id").unwrap_or_default();
+    AuditRecord {
+        belief_id: id.parse().unwrap_or(Uuid::nil()),
+        user_id,
+        action: BeliefAuditAction::Deleted,
+        old_confidence: row.get("confidence").ok(),
+        new_confidence: None,
+        source_message_id: source.parse().ok(),
+        actor_id,
+        reason,
+    }
+}
+
+/// Store a belief's embedding in Qdrant, keyed by belief id.
+pub(crate) async fn index_belief(state: &AppState, belief: &Belief) -> Result<()> {
+    let embedding = state.embeddings.embed(&belief.claim).await.map_err(|e| {
+        NexusError::Embedding(format!("Failed to generate embedding for belief: {e:#}"))
+    })?;
+
+    let payload: serde_json::Map<String, serde_json::Value> = serde_json::from_value(json!({
+        "user_id": belief.user_id.to_string(),
+        "claim": belief.claim,
+        "confidence": belief.confidence,
+        "source_message_id": belief.source_message_id.to_string(),
+        "category": belief.category,
+        "created_at": belief.created_at.to_rfc3339(),
+        "updated_at": belief.updated_at.to_rfc3339(),
+    }))?;
+
+    let point = PointStruct::new(belief.id.to_string(), embedding, payload);
+
+    state
+        .db
+        .qdrant
+        .upsert_points(UpsertPointsBuilder::new(COLLECTION_NAME, vec![point]))
+        .await
+        .map_err(|e| NexusError::VectorStore(format!("Failed to store belief embedding: {e}")))?;
+
+    Ok(())
+}
+
+/// Find the user's beliefs most semantically similar to the query text.
+pub async fn search_beliefs(
+    state: &AppState,
+    user_id: Uuid,
+    query_text: &str,
+    limit: u64,
+) -> Result<Vec<ScoredBelief>> {
+    let query_embedding =
+        state.embeddings.embed(query_text).await.map_err(|e| {
+            NexusError::Embedding(format!("Failed to generate query embedding: {e:#}"))
+        })?;
+
+    let filter = Filter::must([Condition::matches("user_id", user_id.to_string())]);
+
+    let results = state
+        .db
+        .qdrant
+        .search_points(
+            SearchPointsBuilder::new(COLLECTION_NAME, query_embedding, limit)
+                .filter(filter)
+                .with_payload(true),
+        )
+        .await
+        .map_err(|e| NexusError::VectorStore(format!("Failed to search beliefs: {e}")))?;
+
+    let matches = results
+        .result
+        .into_iter()
+        .filter_map(|point| {
+            let id = match point.id?.point_id_options? {
+                PointIdOptions::Uuid(s) => s.parse().ok()?,
+                PointIdOptions::Num(_) => return None,
+            };
+            let payload = &point.payload;
+            let claim = payload.get("claim")?.as_str()?.to_string();
+            let confidence = payload
+                .get("confidence")
+                .and_then(|v| v.as_double())
+                .unwrap_or(0.5);
+            let source_message_id = payload
+                .get("source_message_id")
+                .and_then(|v| v.as_str())
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(Uuid::nil());
+
+            Some(ScoredBelief {
+                belief: Belief {
+                    id,
+                    user_id,
+                    claim,
+                    confidence,
+                    source_message_id,
+                    category: payload
+                        .get("category")
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string()),
+                    created_at: parse_payload_time(payload.get("created_at")),
+                    updated_at: parse_payload_time(payload.get("updated_at")),
+                },
+                score: point.score,
+            })
+        })
+        .collect();
+
+    Ok(matches)
+}
+
+fn parse_payload_time(value: Option<&qdrant_client::qdrant::Value>) -> chrono::DateTime<Utc> {
+    value
+        .and_then(|v| v.as_str())
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now)
+}
+
+/// Retrieve all beliefs for a user from Neo4j, optionally limited to one category.
+pub async fn get_user_beliefs(
+    state: &AppState,
+    user_id: Uuid,
+    category: Option<&str>,
+) -> Result<Vec<Belief>> {
+    get_user_beliefs_page(state, user_id, category, None, None).await
+}
+
+/// A page of a user's beliefs, newest first: at most `limit` of those after
+/// `after`. Pass the last belief's [`Cursor`] to fetch the next page.
+pub async fn get_user_beliefs_page(
+    state: &AppState,
+    user_id: Uuid,
+    category: Option<&str>,
+    after: Option<&Cursor>,
+    limit: Option<usize>,
+) -> Result<Vec<Belief>> {
+    let after_clause = match after {
+        Some(_) => format!("AND {}", Cursor::cypher_predicate("b")),
+        None => String::new(),
+    };
+    let limit_clause = match limit {
+        Some(_) => "LIMIT $limit",
+        None => "",
+    };
+    let mut q = query(&format!(
+        "MATCH (u:User {{id: $user_id}})-[:HOLDS]->(b:Belief)
+         WHERE ($category IS NULL OR coalesce(b.category, $uncategorized) = $category)
+               {after_clause}
+         RETURN b.id AS id, b.claim AS claim, b.confidence AS confidence,
+                b.source_message_id AS source_message_id, b.category AS category,
+                b.created_at AS created_at, b.updated_at AS updated_at
+         ORDER BY datetime(b.created_at) DESC, b.id DESC
+         {limit_clause}"
+    ))
+    .param("user_id", user_id.to_string())
+    .param("category", category.and_then(normalize_category))
+    .param("uncategorized", UNCATEGORIZED);
+    if let Some(cursor) = after {
+        q = q
+            .param("cursor_created_at", cursor.created_at_param())
+            .param("cursor_id", cursor.id.to_string());
+    }
+    if let Some(limit) = limit {
+        q = q.param("limit", limit as i64);
+    }
+
+    let mut result = state
+        .db
+        .neo4j
+        .execute_read(q)
+        .await
+        .map_err(|e| NexusError::Neo4j(format!("Failed to query beliefs from Neo4j: {e}")))?;
 
     let mut beliefs = Vec::new();
-    while let Some(row) = result.next().await? {
+    while let Some(row) = result.next().await.map_err(NexusError::from)? {
         let id_str: String = row.get("id").unwrap_or_default();
         let claim: String = row.get("claim").unwrap_or_default();
         let confidence: f64 = row.get("confidence").unwrap_or(0.5);
@@ -122,6 +607,7 @@ pub async fn get_user_beliefs(state: &AppState, user_id: Uuid) -> Result<Vec<Bel
             claim,
             confidence,
             source_message_id: source_str.parse().unwrap_or(Uuid::nil()),
+            category: row.get("category").ok(),
             created_at: chrono::DateTime::parse_from_rfc3339(&created_str)
                 .map(|dt| dt.with_timezone(&Utc))
                 .unwrap_or_else(|_| Utc::now()),
@@ -134,13 +620,697 @@ pub async fn get_user_beliefs(state: &AppState, user_id: Uuid) -> Result<Vec<Bel
     Ok(beliefs)
 }
 
+/// Count a user's beliefs per category, largest first. Beliefs without a
+/// category are counted as "uncategorized".
+pub async fn get_belief_categories(
+    state: &AppState,
+    user_id: Uuid,
+) -> Result<Vec<BeliefCategoryCount>> {
+    let q = query(
+        "MATCH (u:User {id: $user_id})-[:HOLDS]->(b:Belief)
+         RETURN coalesce(b.category, $uncategorized) AS category, count(b) AS count
+         ORDER BY count DESC, category",
+    )
+    .param("user_id", user_id.to_string())
+    .param("uncategorized", UNCATEGORIZED);
+
+    let mut result = state.db.neo4j.execute_read(q).await.map_err(|e| {
+        NexusError::Neo4j(format!("Failed to query belief categories from Neo4j: {e}"))
+    })?;
+
+    let mut categories = Vec::new();
+    while let Some(row) = result.next().await.map_err(NexusError::from)? {
+        categories.push(BeliefCategoryCount {
+            category: row.get("category").unwrap_or_default(),
+            count: row.get("count").unwrap_or(0),
+        });
+    }
+
+    Ok(categories)
+}
+
+/// Adjust a belief's confidence by `delta`, clamped to 0.0–1.0.
+///
+/// Uses optimistic concurrency on the node's `version` property: the write only
+/// applies if the version is unchanged since the read, and is retried otherwise,
+/// so concurrent revisions from different devices are never lost.
+pub async fn revise_belief(
+    state: &AppState,
+    user_id: Uuid,
+    belief_id: Uuid,
+    delta: f64,
+) -> Result<Belief> {
+    for attempt in 1..=REVISION_ATTEMPTS {
+        let read = query(
+            "MATCH (:User {id: $user_id})-[:HOLDS]->(b:Belief {id: $belief_id})
+             RETURN coalesce(b.version, 0) AS version, b.confidence AS confidence,
+                    b.source_message_id AS source_message_id",
+        )
+        .param("user_id", user_id.to_string())
+        .param("belief_id", belief_id.to_string());
+
+        let mut rows = state
+            .db
+            .neo4j
+            .execute_read(read)
+            .await
+            .map_err(|e| NexusError::Neo4j(format!("Failed to read belief from Neo4j: {e}")))?;
+        let Some(row) = rows.next().await.map_err(NexusError::from)? else {
+            return Err(NexusError::NotFound(format!("Belief {belief_id} not found")).into());
+        };
+        let version: i64 = row.get("version").unwrap_or(0);
+        let confidence: f64 = row.get("confidence").unwrap_or(0.5);
+        let revised = (confidence + delta).clamp(0.0, 1.0);
+        let source: String = row.get("source_message_id").unwrap_or_default();
+        let now = Utc::now();
+
+        // Rolled back with the attempt if the belief changed under us.
+        let mut tx = state
+            .db
+            .pg
+            .begin()
+            .await
+            .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+        let reason = format!("Confidence revised by {delta:+.2}");
+        let audit = AuditRecord {
+            belief_id,
+            user_id,
+            action: BeliefAuditAction::Revised,
+            old_confidence: Some(confidence),
+            new_confidence: Some(revised),
+            source_message_id: source.parse().ok(),
+            actor_id: Some(user_id),
+            reason: &reason,
+        };
+        belief_audit::record(&mut tx, &audit).await?;
+
+        // Setting and removing a dummy property takes the node's write lock
+        // before the version check, so the check-and-set is atomic.
+        let write = query(
+            "MATCH (:User {id: $user_id})-[:HOLDS]->(b:Belief {id: $belief_id})
+             SET b._lock = true
+             REMOVE b._lock
+             WITH b
+             WHERE coalesce(b.version, 0) = $version
+             SET b.confidence = $confidence, b.version = $version + 1, b.updated_at = $now
+             RETURN b.claim AS claim, b.source_message_id AS source_message_id,
+                    b.category AS category, b.created_at AS created_at",
+        )
+        .param("user_id", user_id.to_string())
+        .param("belief_id", belief_id.to_string())
+        .param("version", version)
+        .param("confidence", revised)
+        .param("now", now.to_rfc3339());
+
+        let mut rows = state
+            .db
+            .neo4j
+            .execute(write)
+            .await
+            .map_err(|e| NexusError::Neo4j(format!("Failed to revise belief in Neo4j: {e}")))?;
+
+        if let Some(row) = rows.next().await.map_err(NexusError::from)? {
+            tx.commit().await.map_err(|e| {
+                NexusError::Database(format!("Failed to write belief audit log: {e}"))
+            })?;
+            let claim: String = row.get("claim").unwrap_or_default();
+            let source_str: String = row.get("source_message_id").unwrap_or_default();
+            let created_str: String = row.get("created_at").unwrap_or_default();
+
+            return Ok(Belief {
+                id: belief_id,
+                user_id,
+                claim,
+                confidence: revised,
+                source_message_id: source_str.parse().unwrap_or(Uuid::nil()),
+                category: row.get("category").ok(),
+                created_at: chrono::DateTime::parse_from_rfc3339(&created_str)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .unwrap_or_else(|_| Utc::now()),
+                updated_at: now,
+            });
+        }
+
+        tracing::debug!(%belief_id, attempt, "Belief changed during revision, retrying");
+        if attempt < REVISION_ATTEMPTS {
+            tokio::time::sleep(revision_backoff(attempt)).await;
+        }
+    }
+
+    Err(NexusError::Conflict(format!(
+        "Belief {belief_id} is being revised concurrently, try again"
+    ))
+    .into())
+}
+
+/// Jittered delay before revision attempt `attempt + 1`. A v4 UUID is random
+/// enough for jitter.
+fn revision_backoff(attempt: usize) -> Duration {
+    let jitter = (Uuid::new_v4().as_u128() % 1000) as u32;
+    REVISION_BACKOFF * attempt as u32 * (500 + jitter) / 1000
+}
+
+/// Cypher moving `merged`'s relationships onto `keep`, in both directions.
+/// Properties are copied, and an edge `keep` already has is updated rather
+/// than duplicated.
+const REPOINT_RELATIONSHIPS: &str = "
+    CALL {
+        WITH keep, merged
+        MATCH (merged)-[r:CONTRADICTS]->(other:Belief) WHERE other <> keep
+        MERGE (keep)-[n:CONTRADICTS]->(other) SET n += properties(r)
+        RETURN count(r) AS contradicts_out
+    }
+    CALL {
+        WITH keep, merged
+        MATCH (other:Belief)-[r:CONTRADICTS]->(merged) WHERE other <> keep
+        MERGE (other)-[n:CONTRADICTS]->(keep) SET n += properties(r)
+        RETURN count(r) AS contradicts_in
+    }
+    CALL {
+        WITH keep, merged
+        MATCH (merged)-[r:REVISED]->(other:Belief) WHERE other <> keep
+        MERGE (keep)-[n:REVISED]->(other) SET n += properties(r)
+        RETURN count(r) AS revised_out
+    }
+    CALL {
+        WITH keep, merged
+        MATCH (other:Belief)-[r:REVISED]->(merged) WHERE other <> keep
+        MERGE (other)-[n:REVISED]->(keep) SET n += properties(r)
+        RETURN count(r) AS revised_in
+    }";
+
+/// A merge of two beliefs: the surviving belief and how many relationships
+/// moved onto it.
+#[derive(Debug, Clone)]
+pub struct MergedBelief {
+    pub belief: Belief,
+    pub relationships_moved: i64,
+}
+
+/// Fold the user's belief `merge_id` into `keep_id`: its contradiction and
+/// revision relationships move to the kept belief, which takes the higher
+/// of the two confidences, and the merged belief is deleted. Relationships
+/// between the two are dropped rather than turned into self-loops.
+pub async fn merge_beliefs(
+    state: &AppState,
+    user_id: Uuid,
+    keep_id: Uuid,
+    merge_id: Uuid,
+) -> Result<MergedBelief> {
+    if keep_id == merge_id {
+        return Err(NexusError::Validation("Cannot merge a belief into itself".into()).into());
+    }
+    let now = Utc::now();
+
+    // One statement, so the merge is applied whole or not at all. Touching a
+    // property on both nodes takes their write locks before anything is read.
+    let q = query(&format!(
+        "MATCH (u:User {{id: $user_id}})-[:HOLDS]->(keep:Belief {{id: $keep_id}})
+         MATCH (u)-[:HOLDS]->(merged:Belief {{id: $merge_id}})
+         SET keep._lock = true, merged._lock = true
+         REMOVE keep._lock, merged._lock
+         WITH keep, merged
+         {REPOINT_RELATIONSHIPS}
+         WITH keep, merged,
+              contradicts_out + contradicts_in + revised_out + revised_in AS moved,
+              keep.confidence AS keep_confidence,
+              merged.confidence AS merged_confidence,
+              merged.source_message_id AS merged_source
+         DETACH DELETE merged
+         SET keep.confidence = CASE WHEN merged_confidence > keep_confidence
+                                    THEN merged_confidence ELSE keep_confidence END,
+             keep.version = coalesce(keep.version, 0) + 1,
+             keep.updated_at = $now
+         RETURN keep.claim AS claim, keep.confidence AS confidence,
+                keep.source_message_id AS source_message_id, keep.category AS category,
+                keep.created_at AS created_at, keep_confidence, merged_confidence,
+                merged_source, moved"
+    ))
+    .param("user_id", user_id.to_string())
+    .param("keep_id", keep_id.to_string())
+    .param("merge_id", merge_id.to_string())
+    .param("now", now.to_rfc3339());
+
+    let mut rows = state
+        .db
+        .neo4j
+        .execute(q)
+        .await
+        .map_err(|e| NexusError::Neo4j(format!("Failed to merge beliefs in Neo4j: {e}")))?;
+    let Some(row) = rows.next().await.map_err(NexusError::from)? else {
+        return Err(NexusError::NotFound("Belief not found".into()).into());
+    };
+
+    let confidence: f64 = row.get("confidence").unwrap_or(0.5);
+    let keep_confidence: f64 = row.get("keep_confidence").unwrap_or(confidence);
+    let merged_source: String = row.get("merged_source").unwrap_or_default();
+    let source_str: String = row.get("source_message_id").unwrap_or_default();
+    let created_str: String = row.get("created_at").unwrap_or_default();
+    let relationships_moved: i64 = row.get("moved").unwrap_or(0);
+
+    let kept_reason = format!("Merged with belief {merge_id}");
+    let merged_reason = format!("Merged into belief {keep_id}");
+    let audits = [
+        AuditRecord {
+            belief_id: keep_id,
+            user_id,
+            action: BeliefAuditAction::Revised,
+            old_confidence: Some(keep_confidence),
+            new_confidence: Some(confidence),
+            source_message_id: source_str.parse().ok(),
+            actor_id: Some(user_id),
+            reason: &kept_reason,
+        },
+        AuditRecord {
+            belief_id: merge_id,
+            user_id,
+            action: BeliefAuditAction::Deleted,
+            old_confidence: row.get("merged_confidence").ok(),
+            new_confidence: None,
+            source_message_id: merged_source.parse().ok(),
+            actor_id: Some(user_id),
+            reason: &merged_reason,
+        },
+    ];
+    if let Err(e) = belief_audit::record_all(state, &audits).await {
+        tracing::error!(%user_id, %keep_id, %merge_id, "Failed to audit belief merge: {e:#}");
+    }
+    if let Err(e) = contradictions::repoint(state, keep_id, merge_id).await {
+        tracing::warn!(%keep_id, %merge_id, "Failed to move stored contradictions: {e:#}");
+    }
+
+    state
+        .db
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(COLLECTION_NAME)
+                .points(PointsIdsList {
+                    ids: vec![merge_id.to_string().into()],
+                })
+                .wait(true),
+        )
+        .await
+        .map_err(|e| {
+            NexusError::VectorStore(format!("Failed to delete merged belief embedding: {e}"))
+        })?;
+
+    tracing::info!(%user_id, %keep_id, %merge_id, relationships_moved, "Merged beliefs");
+    Ok(MergedBelief {
+        belief: Belief {
+            id: keep_id,
+            user_id,
+            claim: row.get("claim").unwrap_or_default(),
+            confidence,
+            source_message_id: source_str.parse().unwrap_or(Uuid::nil()),
+            category: row.get("category").ok(),
+            created_at: chrono::DateTime::parse_from_rfc3339(&created_str)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            updated_at: now,
+        },
+        relationships_moved,
+    })
+}
+
+/// Retrieve the user's belief graph: all belief nodes plus the
+/// CONTRADICTS and REVISED relationships between them.
+pub async fn get_belief_graph(state: &AppState, user_id: Uuid) -> Result<BeliefGraph> {
+    let nodes = get_user_beliefs(state, user_id, None).await?;
+
+    let q = query(
+        "MATCH (u:User {id: $user_id})-[:HOLDS]->(a:Belief)-[r:CONTRADICTS|REVISED]->(b:Belief)<-[:HOLDS]-(u)
+         RETURN a.id AS source, b.id AS target, type(r) AS kind,
+                r.explanation AS explanation, r.severity AS severity",
+    )
+    .param("user_id", user_id.to_string());
+
+    let mut result =
+        state.db.neo4j.execute_read(q).await.map_err(|e| {
+            NexusError::Neo4j(format!("Failed to query belief graph from Neo4j: {e}"))
+        })?;
+
+    let mut edges = Vec::new();
+    while let Some(row) = result.next().await.map_err(NexusError::from)? {
+        let source_str: String = row.get("source").unwrap_or_default();
+        let target_str: String = row.get("target").unwrap_or_default();
+        let kind_str: String = row.get("kind").unwrap_or_default();
+
+        let kind = match kind_str.as_str() {
+            "CONTRADICTS" => BeliefEdgeKind::Contradicts,
+            "REVISED" => BeliefEdgeKind::Revised,
+            _ => continue,
+        };
+
+        edges.push(BeliefEdge {
+            source: source_str.parse().unwrap_or(Uuid::nil()),
+            target: target_str.parse().unwrap_or(Uuid::nil()),
+            kind,
+            explanation: row.get("explanation").ok(),
+            severity: row.get("severity").ok(),
+        });
+    }
+
+    Ok(BeliefGraph { nodes, edges })
+}
+
+/// Export the user's beliefs and the relationships between them as a
+/// versioned snapshot that [`import_beliefs`] can load on another instance.
+pub async fn export_beliefs(state: &AppState, user_id: Uuid) -> Result<BeliefSnapshot> {
+    let graph = get_belief_graph(state, user_id).await?;
+    Ok(BeliefSnapshot {
+        version: BELIEF_SNAPSHOT_VERSION,
+        exported_at: Utc::now(),
+        beliefs: graph.nodes,
+        edges: graph.edges,
+    })
+}
+
+/// Cypher creating the snapshot's edges of one relationship type from the
+/// parallel `$<prefix>_*` lists.
+fn create_edges(rel: &str, prefix: &str) -> String {
+    format!(
+        "CALL {{
+            UNWIND range(0, size(${prefix}_source) - 1) AS i
+            MATCH (a:Belief {{id: ${prefix}_source[i]}}), (b:Belief {{id: ${prefix}_target[i]}})
+            CREATE (a)-[:{rel} {{explanation: ${prefix}_explanation[i],
+                                 severity: ${prefix}_severity[i], detected_at: $now}}]->(b)
+            RETURN count(*) AS {prefix}
+        }}"
+    )
+}
+
+/// Beliefs recreated from a snapshot, with their new ids, and how many
+/// relationships were recreated between them.
+#[derive(Debug, Clone)]
+pub struct ImportedBeliefs {
+    pub beliefs: Vec<Belief>,
+    pub relationships: i64,
+}
+
+/// Recreate a [`BeliefSnapshot`] in the user's graph. Every belief gets a new
+/// id; claims, confidences, categories, timestamps and the relationships
+/// between the snapshot's beliefs are preserved. The import is one statement,
+/// so it applies whole or not at all.
+pub async fn import_beliefs(
+    state: &AppState,
+    user_id: Uuid,
+    snapshot: &BeliefSnapshot,
+) -> Result<ImportedBeliefs> {
+    if snapshot.version != BELIEF_SNAPSHOT_VERSION {
+        return Err(NexusError::Validation(format!(
+            "Unsupported belief snapshot version {} (expected {BELIEF_SNAPSHOT_VERSION})",
+            snapshot.version
+        ))
+        .into());
+    }
+    let max = state.config.max_beliefs_per_user;
+    if snapshot.beliefs.len() > max {
+        return Err(NexusError::Validation(format!(
+            "Snapshot has {} beliefs; at most {max} can be imported",
+            snapshot.beliefs.len()
+        ))
+        .into());
+    }
+
+    let now = Utc::now();
+    let mut new_ids = HashMap::new();
+    let mut beliefs = Vec::with_capacity(snapshot.beliefs.len());
+    for b in &snapshot.beliefs {
+        if b.claim.trim().is_empty() {
+            return Err(
+                NexusError::Validation(format!("Belief {} has an empty claim", b.id)).into(),
+            );
+        }
+        if !(0.0..=1.0).contains(&b.confidence) {
+            return Err(NexusError::Validation(format!(
+                "Belief {} has confidence outside 0.0-1.0",
+                b.id
+            ))
+            .into());
+        }
+        let id = Uuid::new_v4();
+        if new_ids.insert(b.id, id).is_some() {
+            return Err(
+                NexusError::Validation(format!("Belief {} appears more than once", b.id)).into(),
+            );
+        }
+        beliefs.push(Belief {
+            id,
+            user_id,
+            category: b.category.as_deref().and_then(normalize_category),
+            ..b.clone()
+        });
+    }
+
+    let mut contradicts = EdgeLists::default();
+    let mut revised = EdgeLists::default();
+    let mut stored_contradictions = Vec::new();
+    for e in &snapshot.edges {
+        let (Some(source), Some(target)) = (new_ids.get(&e.source), new_ids.get(&e.target)) else {
+            return Err(NexusError::Validation(format!(
+                "Relationship {} -> {} refers to a belief not in the snapshot",
+                e.source, e.target
+            ))
+            .into());
+        };
+        let lists = match e.kind {
+            BeliefEdgeKind::Contradicts => {
+                stored_contradictions.push((*source, *target, e));
+                &mut contradicts
+            }
+            BeliefEdgeKind::Revised => &mut revised,
+        };
+        lists.source.push(source.to_string());
+        lists.target.push(target.to_string());
+        lists.explanation.push(e.explanation.clone());
+        lists.severity.push(e.severity);
+    }
+
+    let q = query(&format!(
+        "MERGE (u:User {{id: $user_id}})
+         WITH u
+         CALL {{
+             WITH u
+             UNWIND range(0, size($ids) - 1) AS i
+             CREATE (b:Belief {{
+                 id: $ids[i],
+                 claim: $claims[i],
+                 confidence: $confidences[i],
+                 source_message_id: $sources[i],
+                 category: $categories[i],
+                 created_at: $created_at[i],
+                 updated_at: $updated_at[i],
+                 version: 0
+             }})
+             CREATE (u)-[:HOLDS]->(b)
+             RETURN count(b) AS created
+         }}
+         {}
+         {}
+         RETURN created, contradicts + revised AS relationships",
+        create_edges("CONTRADICTS", "contradicts"),
+        create_edges("REVISED", "revised"),
+    ))
+    .param("user_id", user_id.to_string())
+    .param(
+        "ids",
+        beliefs.iter().map(|b| b.id.to_string()).collect::<Vec<_>>(),
+    )
+    .param(
+        "claims",
+        beliefs.iter().map(|b| b.claim.clone()).collect::<Vec<_>>(),
+    )
+    .param(
+        "confidences",
+        beliefs.iter().map(|b| b.confidence).collect::<Vec<_>>(),
+    )
+    .param(
+        "sources",
+        beliefs
+            .iter()
+            .map(|b| b.source_message_id.to_string())
+            .collect::<Vec<_>>(),
+    )
+    .param(
+        "categories",
+        beliefs
+            .iter()
+            .map(|b| b.category.clone())
+            .collect::<Vec<_>>(),
+    )
+    .param(
+        "created_at",
+        beliefs
+            .iter()
+            .map(|b| b.created_at.to_rfc3339())
+            .collect::<Vec<_>>(),
+    )
+    .param(
+        "updated_at",
+        beliefs
+            .iter()
+            .map(|b| b.updated_at.to_rfc3339())
+            .collect::<Vec<_>>(),
+    )
+    .param("now", now.to_rfc3339());
+    let q = contradicts.bind(q, "contradicts");
+    let q = revised.bind(q, "revised");
+
+    let mut rows = state
+        .db
+        .neo4j
+        .execute(q)
+        .await
+        .map_err(|e| NexusError::Neo4j(format!("Failed to import beliefs into Neo4j: {e}")))?;
+    let relationships: i64 = match rows.next().await.map_err(NexusError::from)? {
+        Some(row) => row.get("relationships").unwrap_or(0),
+        None => 0,
+    };
+
+    let audits: Vec<AuditRecord> = beliefs
+        .iter()
+        .map(|b| AuditRecord {
+            belief_id: b.id,
+            user_id,
+            action: BeliefAuditAction::Created,
+            old_confidence: None,
+            new_confidence: Some(b.confidence),
+            source_message_id: Some(b.source_message_id),
+            actor_id: Some(user_id),
+            reason: "Imported from a belief snapshot",
+        })
+        .collect();
+    if let Err(e) = belief_audit::record_all(state, &audits).await {
+        tracing::error!(%user_id, "Failed to audit imported beliefs: {e:#}");
+    }
+
+    for (source, target, edge) in stored_contradictions {
+        let explanation = edge.explanation.as_deref().unwrap_or_default();
+        let severity = edge.severity.unwrap_or_default();
+        if let Err(e) =
+            contradictions::record(state, user_id, source, target, explanation, severity, now).await
+        {
+            tracing::warn!(%user_id, "Failed to store imported contradiction: {e:#}");
+        }
+    }
+
+    for belief in &beliefs {
+        index_belief_or_queue(state, belief).await;
+    }
+
+    if let Err(e) = evict_excess_beliefs(state, user_id, Uuid::nil()).await {
+        tracing::warn!(%user_id, "Failed to evict excess beliefs: {e}");
+    }
+
+    tracing::info!(%user_id, count = beliefs.len(), relationships, "Imported belief snapshot");
+    Ok(ImportedBeliefs {
+        beliefs,
+        relationships,
+    })
+}
+
+/// Parallel parameter lists for one relationship type in an import.
+#[derive(Default)]
+struct EdgeLists {
+    source: Vec<String>,
+    target: Vec<String>,
+    explanation: Vec<Option<String>>,
+    severity: Vec<Option<f64>>,
+}
+
+impl EdgeLists {
+    fn bind(self, q: neo4rs::Query, prefix: &str) -> neo4rs::Query {
+        q.param(&format!("{prefix}_source"), self.source)
+            .param(&format!("{prefix}_target"), self.target)
+            .param(&format!("{prefix}_explanation"), self.explanation)
+            .param(&format!("{prefix}_severity"), self.severity)
+    }
+}
+
+/// Render a belief graph as a GraphML document.
+pub fn to_graphml(graph: &BeliefGraph) -> String {
+    let mut out = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="claim" for="node" attr.name="claim" attr.type="string"/>
+  <key id="confidence" for="node" attr.name="confidence" attr.type="double"/>
+  <key id="created_at" for="node" attr.name="created_at" attr.type="string"/>
+  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>
+  <key id="explanation" for="edge" attr.name="explanation" attr.type="string"/>
+  <key id="severity" for="edge" attr.name="severity" attr.type="double"/>
+  <graph id="beliefs" edgedefault="directed">
+"#,
+    );
+
+    for b in &graph.nodes {
+        out.push_str(&format!("    <node id=\"{}\">\n", b.id));
+        push_data(&mut out, "claim", &xml_escape(&b.claim));
+        push_data(&mut out, "confidence", &b.confidence.to_string());
+        push_data(&mut out, "created_at", &b.created_at.to_rfc3339());
+        out.push_str("    </node>\n");
+    }
+
+    for (i, e) in graph.edges.iter().enumerate() {
+        let kind = match e.kind {
+            BeliefEdgeKind::Contradicts => "contradicts",
+            BeliefEdgeKind::Revised => "revised",
+        };
+        out.push_str(&format!(
+            "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\">\n",
+            e.source, e.target
+        ));
+        push_data(&mut out, "kind", kind);
+        if let Some(explanation) = &e.explanation {
+            push_data(&mut out, "explanation", &xml_escape(explanation));
+        }
+        if let Some(severity) = e.severity {
+            push_data(&mut out, "severity", &severity.to_string());
+        }
+        out.push_str("    </edge>\n");
+    }
+
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+fn push_data(out: &mut String, key: &str, value: &str) {
+    out.push_str(&format!("      <data key=\"{key}\">{value}</data>\n"));
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 /// Detect contradictions between a new claim and existing beliefs.
+///
+/// Only the `CONTRADICTION_CANDIDATES` beliefs most similar to the claim are
+/// compared, which keeps the prompt bounded however many beliefs the user holds.
+/// When the search fails or finds nothing (beliefs stored before they were
+/// indexed), the most recent beliefs are compared instead.
 pub async fn detect_contradictions(
     state: &AppState,
     user_id: Uuid,
     new_claim: &str,
 ) -> Result<Vec<Contradiction>> {
-    let existing = get_user_beliefs(state, user_id).await?;
+    let limit = state.config.contradiction_candidates;
+    let similar: Vec<Belief> = match search_beliefs(state, user_id, new_claim, limit).await {
+        Ok(scored) => scored.into_iter().map(|s| s.belief).collect(),
+        Err(e) => {
+            tracing::warn!("Belief similarity search failed, using recent beliefs: {e}");
+            Vec::new()
+        }
+    };
+    let existing = if similar.is_empty() {
+        let mut recent = get_user_beliefs(state, user_id, None).await?;
+        recent.truncate(limit as usize);
+        recent
+    } else {
+        similar
+    };
     if existing.is_empty() {
         return Ok(Vec::new());
     }
@@ -158,8 +1328,8 @@ If no contradictions exist, return {"contradictions": []}."#;
     let prompt = format!("New claim: \"{new_claim}\"\n\nExisting beliefs:\n{existing_json}");
 
     let result: ContradictionResponse = state
-        .ollama
-        .generate_json(&prompt, Some(system))
+        .llm
+        .generate_json_with(&prompt, Some(system), extraction_params(state))
         .await
         .unwrap_or_else(|_| ContradictionResponse {
             contradictions: Vec::new(),
@@ -176,11 +1346,13 @@ If no contradictions exist, return {"contradictions": []}."#;
                     claim: new_claim.to_string(),
                     confidence: 0.5,
                     source_message_id: Uuid::nil(),
+                    category: None,
                     created_at: Utc::now(),
                     updated_at: Utc::now(),
                 },
                 explanation: c.explanation,
                 severity: c.severity,
+                below_threshold: c.severity < state.config.contradiction_min_severity,
             });
         }
     }
@@ -188,7 +1360,9 @@ If no contradictions exist, return {"contradictions": []}."#;
     Ok(found)
 }
 
-/// Create CONTRADICTS relationship in Neo4j between two beliefs.
+/// Create CONTRADICTS relationship in Neo4j between two beliefs, and store it
+/// in the `contradictions` table. Idempotent, so it can be retried from the
+/// outbox.
 pub async fn link_contradiction(
     state: &AppState,
     belief_a_id: Uuid,
@@ -196,26 +1370,123 @@ pub async fn link_contradiction(
     explanation: &str,
     severity: f64,
 ) -> Result<()> {
+    let now = Utc::now();
     let q = query(
-        "MATCH (a:Belief {id: $a_id}), (b:Belief {id: $b_id})
-         CREATE (a)-[:CONTRADICTS {explanation: $explanation, severity: $severity, detected_at: $now}]->(b)",
+        "MATCH (u:User)-[:HOLDS]->(a:Belief {id: $a_id}), (b:Belief {id: $b_id})
+         MERGE (a)-[r:CONTRADICTS]->(b)
+         SET r.explanation = $explanation, r.severity = $severity, r.detected_at = $now
+         RETURN u.id AS user_id",
     )
     .param("a_id", belief_a_id.to_string())
     .param("b_id", belief_b_id.to_string())
     .param("explanation", explanation.to_string())
     .param("severity", severity)
-    .param("now", Utc::now().to_rfc3339());
+    .param("now", now.to_rfc3339());
 
-    state
+    let mut rows = state
         .db
         .neo4j
-        .run(q)
+        .execute(q)
         .await
-        .context("Failed to create contradiction link")?;
+        .map_err(|e| NexusError::Neo4j(format!("Failed to create contradiction link: {e}")))?;
+    // Nothing to store if either belief has since been deleted.
+    let Some(row) = rows.next().await.map_err(NexusError::from)? else {
+        return Ok(());
+    };
+    let user_id: String = row.get("user_id").unwrap_or_default();
+    let Ok(user_id) = user_id.parse() else {
+        return Ok(());
+    };
+    contradictions::record(
+        state,
+        user_id,
+        belief_a_id,
+        belief_b_id,
+        explanation,
+        severity,
+        now,
+    )
+    .await?;
 
     Ok(())
 }
 
+/// Check each extracted claim for contradictions. Failed checks are skipped
+/// and noted in `warnings`.
+pub async fn detect_all_contradictions(
+    state: &AppState,
+    user_id: Uuid,
+    claims: &[ExtractedClaim],
+    warnings: &mut Vec<String>,
+) -> Vec<Contradiction> {
+    let mut found = Vec::new();
+    for claim in claims {
+        match detect_contradictions(state, user_id, &claim.claim).await {
+            Ok(contras) => found.extend(contras),
+            Err(e) => {
+                tracing::warn!("Contradiction check failed: {e:#}");
+                warnings.push(format!(
+                    "Contradiction check failed for \"{}\"",
+                    claim.claim
+                ));
+            }
+        }
+    }
+    found
+}
+
+/// Link each contradiction to the newly stored belief it involves. Writes
+/// that fail are queued for retry; ones that can't even be queued are noted
+/// in `warnings`.
+pub async fn link_contradictions(
+    state: &AppState,
+    contradictions: &[&Contradiction],
+    stored: &[Belief],
+    warnings: &mut Vec<String>,
+) {
+    for contra in contradictions {
+        let Some(new_b) = stored.iter().find(|b| b.claim == contra.belief_b.claim) else {
+            continue;
+        };
+        if let Err(e) = link_contradiction_or_queue(
+            state,
+            contra.belief_a.id,
+            new_b.id,
+            &contra.explanation,
+            contra.severity,
+        )
+        .await
+        {
+            tracing::error!("Failed to record contradiction link: {e:#}");
+            warnings.push("A contradiction could not be recorded".into());
+        }
+    }
+}
+
+/// Link a contradiction, queueing the write in the outbox if Neo4j fails.
+/// Errors only if the write could be neither applied nor queued.
+pub async fn link_contradiction_or_queue(
+    state: &AppState,
+    belief_a_id: Uuid,
+    belief_b_id: Uuid,
+    explanation: &str,
+    severity: f64,
+) -> Result<()> {
+    let Err(e) = link_contradiction(state, belief_a_id, belief_b_id, explanation, severity).await
+    else {
+        return Ok(());
+    };
+    tracing::warn!("Failed to link contradiction, queueing retry: {e:#}");
+
+    let op = OutboxOp::LinkContradiction {
+        belief_a: belief_a_id,
+        belief_b: belief_b_id,
+        explanation: explanation.to_string(),
+        severity,
+    };
+    outbox::enqueue(&state.db.pg, &op).await
+}
+
 #[derive(Deserialize)]
 struct ContradictionResponse {
     contradictions: Vec<ContradictionEntry>,
@@ -227,3 +1498,92 @@ struct ContradictionEntry {
     explanation: String,
     severity: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn claim(text: &str) -> ExtractedClaim {
+        ExtractedClaim {
+            claim: text.into(),
+            confidence: 0.8,
+            is_explicit: true,
+            category: None,
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the services in .env"]
+    async fn integrated_turns_link_contradictions_in_the_graph() {
+        let state = test_support::live_state().await;
+        let user_id = Uuid::new_v4();
+        let old = store_belief(
+            &state,
+            user_id,
+            &claim("Working from home hurts my focus"),
+            Uuid::new_v4(),
+        )
+        .await
+        .unwrap();
+
+        let turn = crate::river::integrated::process_integrated(
+            &state,
+            Uuid::new_v4(),
+            user_id,
+            "Working from home has really helped my focus; I get far more done without the office interruptions.",
+        )
+        .await;
+        let graph = get_belief_graph(&state, user_id).await;
+        let belief_ids: Vec<Uuid> = graph
+            .as_ref()
+            .map(|g| g.nodes.iter().map(|b| b.id).collect())
+            .unwrap_or_default();
+        delete_user_beliefs(&state, &[user_id]).await.unwrap();
+        contradictions::forget(&state, &belief_ids).await.unwrap();
+
+        let turn = turn.unwrap();
+        let contradiction = turn
+            .contradictions
+            .iter()
+            .find(|c| c.belief_a.id == old.id && !c.below_threshold)
+            .expect("the new claim should contradict the stored belief");
+        let edge = graph
+            .unwrap()
+            .edges
+            .into_iter()
+            .find(|e| e.kind == BeliefEdgeKind::Contradicts)
+            .expect("CONTRADICTS edge");
+        assert_eq!(edge.source, old.id);
+        assert!(turn.beliefs.iter().any(|b| b.id == edge.target));
+        assert_eq!(
+            edge.explanation.as_deref(),
+            Some(contradiction.explanation.as_str())
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs the services in .env"]
+    async fn concurrent_revisions_are_both_applied() {
+        let state = test_support::live_state().await;
+        let user_id = Uuid::new_v4();
+        let mut claim = claim("Cities are better places to live");
+        claim.confidence = 0.5;
+        let belief = store_belief(&state, user_id, &claim, Uuid::new_v4())
+            .await
+            .unwrap();
+
+        let (first, second) = tokio::join!(
+            revise_belief(&state, user_id, belief.id, 0.1),
+            revise_belief(&state, user_id, belief.id, 0.2),
+        );
+        let stored = get_user_beliefs(&state, user_id, None).await;
+        delete_user_beliefs(&state, &[user_id]).await.unwrap();
+
+        first.unwrap();
+        second.unwrap();
+        let stored = stored.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert!((stored[0].confidence - 0.8).abs() < 1e-9, "{stored:?}");
+    }
+}
@@ -1,33 +1,86 @@
-use anyhow::{Context, Result};
-use chrono::Utc;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use influxdb2::models::DataPoint;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::api::state::AppState;
-use nexus_common::types::ConsciousnessState;
+use crate::river::beliefs;
+use nexus_common::error::NexusError;
+use nexus_common::types::{BeliefEdgeKind, ConsciousnessState};
 
-/// Log a consciousness metrics snapshot to InfluxDB.
-pub async fn log_metrics(state: &AppState, metrics: &ConsciousnessState) -> Result<()> {
-    use influxdb2::models::DataPoint;
-
-    let point = DataPoint::builder("consciousness")
+/// Build the InfluxDB point for a snapshot. Backfilled points carry a
+/// `backfill` tag, the id of the message they were computed up to and that
+/// message's timestamp, so replaying the same history overwrites them instead
+/// of adding duplicates.
+fn data_point(metrics: &ConsciousnessState, backfilled_to: Option<Uuid>) -> Result<DataPoint> {
+    let mut builder = DataPoint::builder("consciousness")
         .tag("user_id", metrics.user_id.to_string())
-        .tag("session_id", metrics.session_id.to_string())
+        .tag("session_id", metrics.session_id.to_string());
+    if let Some(message_id) = backfilled_to {
+        builder = builder
+            .tag("backfill", "true")
+            .tag("message_id", message_id.to_string());
+        if let Some(nanos) = metrics.timestamp.timestamp_nanos_opt() {
+            builder = builder.timestamp(nanos);
+        }
+    }
+
+    let point = builder
         .field("epistemic_humility", metrics.epistemic_humility)
         .field("belief_volatility", metrics.belief_volatility)
         .field("contradiction_awareness", metrics.contradiction_awareness)
         .field("depth_of_inquiry", metrics.depth_of_inquiry)
         .build()
-        .context("Failed to build InfluxDB data point")?;
+        .map_err(|e| NexusError::TimeSeries(format!("Failed to build InfluxDB data point: {e}")))?;
+    Ok(point)
+}
 
+async fn write_points(state: &AppState, points: Vec<DataPoint>) -> Result<()> {
     state
         .db
         .influx
-        .write(
-            &state.config.influxdb.bucket,
-            futures::stream::iter(vec![point]),
+        .write(&state.config.influxdb.bucket, futures::stream::iter(points))
+        .await
+        .map_err(|e| {
+            NexusError::TimeSeries(format!(
+                "Failed to write consciousness metrics to InfluxDB: {e}"
+            ))
+        })?;
+    Ok(())
+}
+
+/// Delete every consciousness snapshot recorded for these users.
+pub async fn delete_user_metrics(state: &AppState, user_ids: &[Uuid]) -> Result<()> {
+    // Delete predicates can't OR tag values together, so one call per user.
+    for user_id in user_ids {
+        delete_points(
+            state,
+            format!(r#"_measurement="consciousness" AND user_id="{user_id}""#),
         )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Delete every consciousness point matching `predicate`.
+async fn delete_points(state: &AppState, predicate: String) -> Result<()> {
+    let start = DateTime::<Utc>::UNIX_EPOCH.naive_utc();
+    let stop = Utc::now().naive_utc();
+    state
+        .db
+        .influx
+        .delete(&state.config.influxdb.bucket, start, stop, Some(predicate))
         .await
-        .context("Failed to write consciousness metrics to InfluxDB")?;
+        .map_err(|e| {
+            NexusError::TimeSeries(format!("Failed to delete consciousness metrics: {e}"))
+        })?;
+    Ok(())
+}
+
+/// Log a consciousness metrics snapshot to InfluxDB.
+pub async fn log_metrics(state: &AppState, metrics: &ConsciousnessState) -> Result<()> {
+    write_points(state, vec![data_point(metrics, None)?]).await?;
 
     tracing::debug!(
         user_id = %metrics.user_id,
@@ -120,6 +173,31 @@ pub async fn compute_metrics(
     questions_asked: usize,
     beliefs_revised: usize,
 ) -> Result<ConsciousnessState> {
+    let metrics = metrics_from_counts(
+        user_id,
+        session_id,
+        beliefs_count,
+        contradictions_count,
+        questions_asked,
+        beliefs_revised,
+        Utc::now(),
+    );
+
+    log_metrics(state, &metrics).await?;
+
+    Ok(metrics)
+}
+
+/// The four metrics for the given activity counts.
+fn metrics_from_counts(
+    user_id: Uuid,
+    session_id: Uuid,
+    beliefs_count: usize,
+    contradictions_count: usize,
+    questions_asked: usize,
+    beliefs_revised: usize,
+    timestamp: DateTime<Utc>,
+) -> ConsciousnessState {
     let epistemic_humility = if beliefs_count > 0 {
         ((questions_asked + beliefs_revised) as f64 / beliefs_count as f64).min(1.0)
     } else {
@@ -140,17 +218,94 @@ pub async fn compute_metrics(
 
     let depth_of_inquiry = (questions_asked as f64 / 10.0).min(1.0);
 
-    let metrics = ConsciousnessState {
+    ConsciousnessState {
         user_id,
         session_id,
         epistemic_humility,
         belief_volatility,
         contradiction_awareness,
         depth_of_inquiry,
-        timestamp: Utc::now(),
-    };
+        timestamp,
+    }
+}
 
-    log_metrics(state, &metrics).await?;
+/// Recompute one metrics snapshot per session from the user's stored history
+/// and write them to InfluxDB as backfilled points.
+///
+/// Messages come from Postgres and beliefs and contradictions from Neo4j, so
+/// this picks up formula changes and fills gaps in the live trajectory. Each
+/// session's snapshot is keyed by its last message's id and stamped with its
+/// time. Earlier backfilled points are replaced, so a rerun after a session
+/// has grown doesn't leave its old snapshot behind. Per session:
+/// - questions asked: assistant replies outside Analysis mode
+/// - beliefs: all beliefs created by the end of the session
+/// - contradictions: links whose newer belief was created during the session
+/// - revisions: beliefs last revised during the session
+pub async fn recompute_metrics(state: &AppState, user_id: Uuid) -> Result<Vec<ConsciousnessState>> {
+    let sessions: Vec<(Uuid, Uuid, DateTime<Utc>, DateTime<Utc>, i64)> = sqlx::query_as(
+        "SELECT session_id, (ARRAY_AGG(id ORDER BY created_at DESC, id DESC))[1],
+                MIN(created_at), MAX(created_at),
+                COUNT(*) FILTER (WHERE role = 'assistant' AND mode <> 'analysis')
+         FROM messages
+         WHERE user_id = $1
+         GROUP BY session_id
+         ORDER BY MIN(created_at)",
+    )
+    .bind(user_id)
+    .fetch_all(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to load message history: {e}")))?;
 
-    Ok(metrics)
+    let graph = beliefs::get_belief_graph(state, user_id).await?;
+    let created: HashMap<Uuid, DateTime<Utc>> =
+        graph.nodes.iter().map(|b| (b.id, b.created_at)).collect();
+    // A contradiction is dated by the later of its two beliefs.
+    let contradiction_times: Vec<DateTime<Utc>> = graph
+        .edges
+        .iter()
+        .filter(|e| e.kind == BeliefEdgeKind::Contradicts)
+        .filter_map(|e| Some(*created.get(&e.source)?.max(created.get(&e.target)?)))
+        .collect();
+
+    let (last_messages, snapshots): (Vec<Uuid>, Vec<ConsciousnessState>) = sessions
+        .into_iter()
+        .map(|(session_id, last_message, started, ended, replies)| {
+            let during = |t: &DateTime<Utc>| (started..=ended).contains(t);
+            let beliefs_count = graph.nodes.iter().filter(|b| b.created_at <= ended).count();
+            let beliefs_revised = graph
+                .nodes
+                .iter()
+                .filter(|b| b.updated_at > b.created_at && during(&b.updated_at))
+                .count();
+            let contradictions_count = contradiction_times.iter().filter(|t| during(t)).count();
+
+            let snapshot = metrics_from_counts(
+                user_id,
+                session_id,
+                beliefs_count,
+                contradictions_count,
+                replies.max(0) as usize,
+                beliefs_revised,
+                ended,
+            );
+            (last_message, snapshot)
+        })
+        .unzip();
+
+    delete_points(
+        state,
+        format!(r#"_measurement="consciousness" AND user_id="{user_id}" AND backfill="true""#),
+    )
+    .await?;
+    if !snapshots.is_empty() {
+        let points = snapshots
+            .iter()
+            .zip(last_messages)
+            .map(|(m, message_id)| data_point(m, Some(message_id)))
+            .collect::<Result<Vec<_>>>()?;
+        write_points(state, points).await?;
+    }
+
+    tracing::info!(%user_id, sessions = snapshots.len(), "Backfilled consciousness metrics");
+    Ok(snapshots)
 }
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use nexus_common::error::NexusError;
+use nexus_common::types::ContradictionRecord;
+
+type Row = (
+    Uuid,
+    Uuid,
+    Uuid,
+    String,
+    f64,
+    DateTime<Utc>,
+    Option<DateTime<Utc>>,
+);
+
+const COLUMNS: &str =
+    "belief_a_id, belief_b_id, user_id, explanation, severity, detected_at, resolved_at";
+
+/// Store a contradiction linked in the graph. Idempotent like the graph link:
+/// a repeat updates the details and leaves any resolution in place.
+pub async fn record(
+    state: &AppState,
+    user_id: Uuid,
+    belief_a_id: Uuid,
+    belief_b_id: Uuid,
+    explanation: &str,
+    severity: f64,
+    detected_at: DateTime<Utc>,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO contradictions
+             (belief_a_id, belief_b_id, user_id, explanation, severity, detected_at)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         ON CONFLICT (belief_a_id, belief_b_id) DO UPDATE
+         SET explanation = EXCLUDED.explanation,
+             severity = EXCLUDED.severity,
+             detected_at = EXCLUDED.detected_at",
+    )
+    .bind(belief_a_id)
+    .bind(belief_b_id)
+    .bind(user_id)
+    .bind(explanation)
+    .bind(severity)
+    .bind(detected_at)
+    .execute(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to store contradiction: {e}")))?;
+    Ok(())
+}
+
+/// The user's contradictions, most recently detected first, optionally only
+/// those not yet resolved.
+pub async fn list(
+    state: &AppState,
+    user_id: Uuid,
+    unresolved_only: bool,
+    limit: usize,
+) -> Result<Vec<ContradictionRecord>> {
+    let rows: Vec<Row> = sqlx::query_as(&format!(
+        "SELECT {COLUMNS}
+         FROM contradictions
+         WHERE user_id = $1 AND (NOT $2 OR resolved_at IS NULL)
+         ORDER BY detected_at DESC, belief_a_id, belief_b_id
+         LIMIT $3"
+    ))
+    .bind(user_id)
+    .bind(unresolved_only)
+    .bind(limit as i64)
+    .fetch_all(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to list contradictions: {e}")))?;
+
+    Ok(rows.into_iter().map(to_record).collect())
+}
+
+/// Mark the user's contradiction between two beliefs resolved. One already
+/// resolved keeps its original time.
+pub async fn resolve(
+    state: &AppState,
+    user_id: Uuid,
+    belief_a_id: Uuid,
+    belief_b_id: Uuid,
+) -> Result<ContradictionRecord> {
+    let row: Option<Row> = sqlx::query_as(&format!(
+        "UPDATE contradictions
+         SET resolved_at = COALESCE(resolved_at, NOW())
+         WHERE belief_a_id = $1 AND belief_b_id = $2 AND user_id = $3
+         RETURNING {COLUMNS}"
+    ))
+    .bind(belief_a_id)
+    .bind(belief_b_id)
+    .bind(user_id)
+    .fetch_optional(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to resolve contradiction: {e}")))?;
+
+    row.map(to_record).ok_or_else(|| {
+        NexusError::NotFound(format!(
+            "No contradiction between beliefs {belief_a_id} and {belief_b_id}"
+        ))
+        .into()
+    })
+}
+
+/// Drop contradictions involving deleted beliefs.
+pub async fn forget(state: &AppState, belief_ids: &[Uuid]) -> Result<()> {
+    if belief_ids.is_empty() {
+        return Ok(());
+    }
+    sqlx::query("DELETE FROM contradictions WHERE belief_a_id = ANY($1) OR belief_b_id = ANY($1)")
+        .bind(belief_ids)
+        .execute(&state.db.pg)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to delete contradictions: {e}")))?;
+    Ok(())
+}
+
+/// Move `merged`'s contradictions onto `keep`, as a belief merge does in the
+/// graph. Ones between the two beliefs are dropped.
+pub async fn repoint(state: &AppState, keep: Uuid, merged: Uuid) -> Result<()> {
+    let mut tx = state
+        .db
+        .pg
+        .begin()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to start transaction: {e}")))?;
+    sqlx::query(
+        "INSERT INTO contradictions
+             (belief_a_id, belief_b_id, user_id, explanation, severity, detected_at, resolved_at)
+         SELECT CASE WHEN belief_a_id = $2 THEN $1 ELSE belief_a_id END,
+                CASE WHEN belief_b_id = $2 THEN $1 ELSE belief_b_id END,
+                user_id, explanation, severity, detected_at, resolved_at
+         FROM contradictions
+         WHERE (belief_a_id = $2 AND belief_b_id <> $1)
+            OR (belief_b_id = $2 AND belief_a_id <> $1)
+         ON CONFLICT (belief_a_id, belief_b_id) DO UPDATE
+         SET explanation = EXCLUDED.explanation,
+             severity = EXCLUDED.severity,
+             detected_at = EXCLUDED.detected_at",
+    )
+    .bind(keep)
+    .bind(merged)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to move contradictions: {e}")))?;
+    sqlx::query("DELETE FROM contradictions WHERE belief_a_id = $1 OR belief_b_id = $1")
+        .bind(merged)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to move contradictions: {e}")))?;
+    tx.commit()
+        .await
+        .map_err(|e| NexusError::Database(format!("Failed to move contradictions: {e}")))?;
+    Ok(())
+}
+
+fn to_record(
+    (belief_a_id, belief_b_id, user_id, explanation, severity, detected_at, resolved_at): Row,
+) -> ContradictionRecord {
+    ContradictionRecord {
+        belief_a_id,
+        belief_b_id,
+        user_id,
+        explanation,
+        severity,
+        detected_at,
+        resolved_at,
+    }
+}
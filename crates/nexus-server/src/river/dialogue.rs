@@ -1,9 +1,227 @@
 use anyhow::{Context, Result};
+use futures::future::BoxFuture;
 use uuid::Uuid;
 
 use crate::api::state::AppState;
+use crate::db::redis::record_backend_error;
+use crate::river::episodic::RecallScope;
 use crate::river::{beliefs, consciousness, episodic};
+use crate::shared::chat_engine::{ChatEngine, ChatTurn};
+use crate::shared::llm::GenerateParams;
 use crate::shared::ollama::ChatMessage;
+use crate::shared::text_util;
+use crate::shared::tokens;
+use nexus_common::types::{Belief, ConsciousnessState, Contradiction};
+
+/// Conversation mode: River's Socratic dialogue.
+pub struct ConversationEngine;
+
+impl ChatEngine for ConversationEngine {
+    fn process<'a>(
+        &'a self,
+        state: &'a AppState,
+        session_id: Uuid,
+        user_id: Uuid,
+        message: &'a str,
+        opts: TurnOptions<'a>,
+    ) -> BoxFuture<'a, Result<ChatTurn>> {
+        Box::pin(async move {
+            let result = process_message_with(state, session_id, user_id, message, opts).await?;
+            Ok(ChatTurn {
+                response: result.response,
+                analysis: None,
+                contradictions: Some(result.contradictions),
+                beliefs: Some(result.beliefs),
+                consciousness: result.consciousness,
+                memories_recalled: Some(result.memories_recalled),
+                rationale: result.rationale,
+                warnings: result.warnings,
+                remember: true,
+            })
+        })
+    }
+}
+
+/// Outcome of a River dialogue turn.
+#[derive(Debug, Clone)]
+pub struct DialogueResult {
+    pub response: String,
+    pub contradictions: Vec<Contradiction>,
+    pub beliefs: Vec<Belief>,
+    /// Metrics computed for this turn, if they could be computed.
+    pub consciousness: Option<ConsciousnessState>,
+    /// Number of episodic memories recalled as context.
+    pub memories_recalled: usize,
+    /// What prompted the question, if requested with [`TurnOptions::explain`].
+    pub rationale: Option<String>,
+    /// Steps that failed without failing the turn.
+    pub warnings: Vec<String>,
+}
+
+/// Reply to a message with nothing to respond to, sent without calling the LLM.
+pub const CLARIFYING_PROMPT: &str =
+    "I didn't catch anything to respond to there. What's on your mind?";
+
+/// Sampling temperature for regenerated replies, so they differ from the original.
+const REGENERATE_TEMPERATURE: f32 = 1.0;
+
+/// How many questions a Socratic reply asks, and how they're framed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuestionStyle {
+    /// One focused question.
+    #[default]
+    Single,
+    /// Two or three questions, each digging beneath the one before.
+    Layered,
+    /// A brief framing sentence, then one question.
+    Guided,
+}
+
+impl QuestionStyle {
+    /// The style's name as used in requests and stored in reply metadata.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::Layered => "layered",
+            Self::Guided => "guided",
+        }
+    }
+
+    /// Parse a style stored in reply metadata; replies from before styles
+    /// were recorded, or with unknown values, get the default.
+    pub fn from_stored(value: Option<&str>) -> Self {
+        match value {
+            Some("layered") => Self::Layered,
+            Some("guided") => Self::Guided,
+            _ => Self::Single,
+        }
+    }
+
+    /// The system prompt guideline for this style.
+    pub(crate) fn guideline(self) -> &'static str {
+        match self {
+            Self::Single => "Ask ONE focused question at a time",
+            Self::Layered => {
+                "Ask two or three layered questions, each building on the one before and digging beneath it toward the underlying assumption"
+            }
+            Self::Guided => {
+                "Open with one brief sentence framing what seems worth examining, then ask ONE focused question"
+            }
+        }
+    }
+}
+
+/// Per-turn switches for the dialogue and integrated engines.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TurnOptions<'a> {
+    /// Set when regenerating the reply to a message that was already
+    /// processed; holds the previous reply so the model can take another
+    /// angle. Beliefs, contradiction links, memories and metrics are left
+    /// untouched so the message isn't counted twice.
+    pub previous_response: Option<&'a str>,
+    /// Ask the model for a short rationale citing the context that prompted
+    /// its question.
+    pub explain: bool,
+    /// The stored message being processed, recorded as the source of the
+    /// beliefs extracted from it.
+    pub source_message_id: Option<Uuid>,
+    /// Recall memories from every session or only this one.
+    pub recall_scope: RecallScope,
+    /// Reprocess a message without recording anything, as when replaying it
+    /// through another mode: like a regeneration, beliefs, contradiction
+    /// links and metrics are left untouched.
+    pub read_only: bool,
+    /// Question count and framing for Socratic replies.
+    pub question_style: QuestionStyle,
+}
+
+impl TurnOptions<'_> {
+    pub fn is_regeneration(&self) -> bool {
+        self.previous_response.is_some()
+    }
+
+    /// Whether the turn records beliefs, contradiction links and metrics.
+    pub fn persists(&self) -> bool {
+        !self.read_only && !self.is_regeneration()
+    }
+
+    /// Sampling parameters for the reply.
+    pub fn params(&self) -> GenerateParams {
+        if self.is_regeneration() {
+            GenerateParams {
+                temperature: REGENERATE_TEMPERATURE,
+                ..GenerateParams::TEXT
+            }
+        } else {
+            GenerateParams::TEXT
+        }
+    }
+
+    /// `[kind:id] ` when explaining, so the rationale can cite a context line.
+    pub fn ref_tag(&self, kind: &str, id: impl std::fmt::Display) -> String {
+        if self.explain {
+            format!("[{kind}:{id}] ")
+        } else {
+            String::new()
+        }
+    }
+
+    /// System prompt addendum asking for a different angle, if regenerating.
+    pub fn angle_hint(&self) -> String {
+        match self.previous_response {
+            // Nothing to steer away from; the higher temperature still varies it.
+            None | Some("") => String::new(),
+            Some(previous) => format!(
+                "\n\nYou already replied to this message with:\n\"{previous}\"\nThe user wants a different question. Approach their statement from a different angle and do not repeat or rephrase that reply."
+            ),
+        }
+    }
+}
+
+/// System prompt addendum for explained replies.
+const EXPLAIN_HINT: &str = r#"
+
+Also say what prompted your question. Respond with a JSON object: {"question": "<your question>", "rationale": "<one sentence>"}. In the rationale, cite what prompted the question by its tag, e.g. [contradiction:...] or [belief:...], or by the timestamp of a past conversation or the discourse finding it is based on."#;
+
+#[derive(serde::Deserialize)]
+struct ExplainedReply {
+    question: String,
+    #[serde(default)]
+    rationale: Option<String>,
+}
+
+/// Generate the reply to `messages`, with a rationale if `opts.explain` is
+/// set. If the model doesn't return a usable explained reply, a plain one is
+/// generated instead and a warning recorded.
+pub(crate) async fn generate_reply(
+    state: &AppState,
+    messages: &[ChatMessage],
+    opts: TurnOptions<'_>,
+    warnings: &mut Vec<String>,
+) -> Result<(String, Option<String>)> {
+    if opts.explain {
+        let mut explained = messages.to_vec();
+        if let Some(system) = explained.first_mut() {
+            system.content.push_str(EXPLAIN_HINT);
+        }
+        match state
+            .llm
+            .chat_json_with::<ExplainedReply>(&explained, opts.params())
+            .await
+        {
+            Ok(reply) if !reply.question.trim().is_empty() => {
+                return Ok((reply.question, reply.rationale));
+            }
+            Ok(_) => tracing::warn!("Explained reply had no question"),
+            Err(e) => tracing::warn!("Explained reply failed: {e:#}"),
+        }
+        warnings.push("Could not explain this question; returning it without a rationale".into());
+    }
+
+    let response = state.llm.chat_with(messages, opts.params()).await?;
+    Ok((response, None))
+}
 
 /// Process a user message through the River epistemic dialogue engine.
 ///
@@ -11,122 +229,184 @@ use crate::shared::ollama::ChatMessage;
 /// 1. Recall relevant past memories
 /// 2. Extract beliefs from the current message
 /// 3. Check for contradictions against existing beliefs
-/// 4. Store new beliefs and memory
+/// 4. Store new beliefs
 /// 5. Generate a Socratic response using all context
 /// 6. Update consciousness metrics
+///
+/// The caller records both messages as episodic memories, through the outbox,
+/// when it persists the turn.
 pub async fn process_message(
     state: &AppState,
     session_id: Uuid,
     user_id: Uuid,
     message: &str,
-) -> Result<String> {
-    let message_id = Uuid::new_v4();
+) -> Result<DialogueResult> {
+    process_message_with(state, session_id, user_id, message, TurnOptions::default()).await
+}
+
+/// [`process_message`] with explicit turn options.
+pub async fn process_message_with(
+    state: &AppState,
+    session_id: Uuid,
+    user_id: Uuid,
+    message: &str,
+    opts: TurnOptions<'_>,
+) -> Result<DialogueResult> {
+    if !text_util::has_min_words(message, state.config.min_input_words) {
+        return Ok(DialogueResult {
+            response: CLARIFYING_PROMPT.into(),
+            contradictions: Vec::new(),
+            beliefs: Vec::new(),
+            consciousness: None,
+            memories_recalled: 0,
+            rationale: None,
+            warnings: Vec::new(),
+        });
+    }
+
+    let message_id = opts.source_message_id.unwrap_or_else(Uuid::new_v4);
+    let mut warnings = Vec::new();
 
     // 1. Recall relevant past conversations.
-    let memories = episodic::recall_similar(state, user_id, message, 5)
-        .await
-        .unwrap_or_default();
+    let mut memories = episodic::recall_relevant(
+        state,
+        user_id,
+        opts.recall_scope.session(session_id),
+        message,
+        5,
+    )
+    .await
+    .unwrap_or_else(|e| {
+        tracing::warn!("Memory recall failed: {e:#}");
+        warnings.push("Memory recall failed; past conversations were not used".into());
+        Vec::new()
+    });
 
-    let memory_context = if memories.is_empty() {
-        String::new()
-    } else {
-        let mem_texts: Vec<String> = memories
-            .iter()
-            .map(|m| format!("[{}] {}: {}", m.timestamp, m.role, m.content))
-            .collect();
-        format!("\n\nRelevant past conversations:\n{}", mem_texts.join("\n"))
-    };
+    let memories_recalled = memories.len();
+
+    // Most recent first, so truncation drops the oldest memories.
+    memories.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    let memory_lines: Vec<String> = memories
+        .iter()
+        .map(|m| format!("[{}] {}: {}", m.timestamp, m.role, m.content))
+        .collect();
 
     // 2. Extract beliefs from the message.
     let extracted = beliefs::extract_beliefs(state, message)
         .await
-        .unwrap_or_default();
+        .unwrap_or_else(|e| {
+            tracing::warn!("Belief extraction failed: {e:#}");
+            warnings.push("Belief extraction failed; no beliefs were recorded".into());
+            Vec::new()
+        });
 
     // 3. Check for contradictions.
-    let mut all_contradictions = Vec::new();
-    for claim in &extracted {
-        let contras = beliefs::detect_contradictions(state, user_id, &claim.claim)
-            .await
-            .unwrap_or_default();
-        all_contradictions.extend(contras);
-    }
+    let all_contradictions =
+        beliefs::detect_all_contradictions(state, user_id, &extracted, &mut warnings).await;
 
-    let contradiction_context = if all_contradictions.is_empty() {
-        String::new()
-    } else {
-        let contra_texts: Vec<String> = all_contradictions
-            .iter()
-            .map(|c| {
-                format!(
-                    "- Current: \"{}\" contradicts previous: \"{}\" ({})",
-                    c.belief_b.claim, c.belief_a.claim, c.explanation
-                )
-            })
-            .collect();
-        format!("\n\nContradictions detected:\n{}", contra_texts.join("\n"))
-    };
+    // Only contradictions above the severity threshold are raised or linked.
+    // Most severe first, so truncation drops the weakest contradictions.
+    let mut ranked_contradictions: Vec<&Contradiction> = all_contradictions
+        .iter()
+        .filter(|c| !c.below_threshold)
+        .collect();
+    ranked_contradictions.sort_by(|a, b| b.severity.total_cmp(&a.severity));
+    let contradiction_lines: Vec<String> = ranked_contradictions
+        .iter()
+        .map(|c| {
+            format!(
+                "- {}Current: \"{}\" contradicts previous: \"{}\" ({})",
+                opts.ref_tag(
+                    "contradiction",
+                    format_args!("{}/{}", c.belief_a.id, c.belief_b.id)
+                ),
+                c.belief_b.claim,
+                c.belief_a.claim,
+                c.explanation
+            )
+        })
+        .collect();
 
-    // 4. Store new beliefs.
+    // 4. Store new beliefs (already stored if regenerating).
     let mut stored_beliefs = Vec::new();
-    for claim in &extracted {
+    let to_store = if !opts.persists() {
+        &[][..]
+    } else {
+        &extracted[..]
+    };
+    for claim in to_store {
         match beliefs::store_belief(state, user_id, claim, message_id).await {
             Ok(b) => stored_beliefs.push(b),
-            Err(e) => tracing::warn!("Failed to store belief: {e}"),
+            Err(e) => {
+                tracing::warn!("Failed to store belief: {e}");
+                warnings.push(format!("Failed to store belief \"{}\"", claim.claim));
+            }
         }
     }
 
     // Link contradictions in Neo4j.
-    for contra in &all_contradictions {
-        let new_belief = stored_beliefs
-            .iter()
-            .find(|b| b.claim == contra.belief_b.claim);
-        if let Some(new_b) = new_belief {
-            let _ = beliefs::link_contradiction(
-                state,
-                contra.belief_a.id,
-                new_b.id,
-                &contra.explanation,
-                contra.severity,
-            )
-            .await;
-        }
-    }
-
-    // 5. Store this message as episodic memory.
-    let _ = episodic::store_memory(state, user_id, session_id, message_id, message, "user").await;
+    beliefs::link_contradictions(
+        state,
+        &ranked_contradictions,
+        &stored_beliefs,
+        &mut warnings,
+    )
+    .await;
 
-    // 6. Retrieve existing beliefs for context.
-    let existing_beliefs = beliefs::get_user_beliefs(state, user_id)
+    // 6. Retrieve existing beliefs; those most similar to this message go into context.
+    let existing_beliefs = beliefs::get_user_beliefs(state, user_id, None)
         .await
         .unwrap_or_default();
+    let context_beliefs = select_context_beliefs(state, user_id, message, &existing_beliefs).await;
 
-    let beliefs_context = if existing_beliefs.is_empty() {
-        String::new()
-    } else {
-        let belief_texts: Vec<String> = existing_beliefs
-            .iter()
-            .take(20)
-            .map(|b| format!("- \"{}\" (confidence: {:.1})", b.claim, b.confidence))
-            .collect();
-        format!(
-            "\n\nUser's current belief network:\n{}",
-            belief_texts.join("\n")
-        )
-    };
+    let belief_lines: Vec<String> = context_beliefs
+        .iter()
+        .map(|b| {
+            format!(
+                "- {}\"{}\" (confidence: {:.1})",
+                opts.ref_tag("belief", b.id),
+                b.claim,
+                b.confidence
+            )
+        })
+        .collect();
+
+    // Fit the context sections into the token budget, in priority order:
+    // contradictions, then recent memories, then the belief network.
+    let mut budget = state.config.max_context_tokens;
+    let offered = contradiction_lines.len() + memory_lines.len() + belief_lines.len();
+    let contradiction_lines = tokens::take_within_budget(contradiction_lines, &mut budget);
+    let memory_lines = tokens::take_within_budget(memory_lines, &mut budget);
+    let belief_lines = tokens::take_within_budget(belief_lines, &mut budget);
+    let kept = contradiction_lines.len() + memory_lines.len() + belief_lines.len();
+    if kept < offered {
+        tracing::info!(
+            %session_id,
+            dropped = offered - kept,
+            max_tokens = state.config.max_context_tokens,
+            "Truncated Socratic prompt context to fit token budget"
+        );
+    }
+
+    let memory_context = context_section("Relevant past conversations", &memory_lines);
+    let beliefs_context = context_section("User's current belief network", &belief_lines);
+    let contradiction_context = context_section("Contradictions detected", &contradiction_lines);
 
     // 7. Generate Socratic response.
     let system_prompt = format!(
         r#"You are a Socratic dialogue partner focused on epistemic exploration. Your role is NOT to provide answers but to ask questions that help the user examine their own beliefs, assumptions, and reasoning.
 
 Guidelines:
-- Ask ONE focused question at a time
+- {question_guideline}
 - Target the user's actual epistemic gaps — what they haven't considered, not what they already know
 - When contradictions are detected, gently surface them without judgment
 - Reference past conversations when relevant to show continuity of thought
 - Never lecture or give opinions — only ask questions
 - Be genuinely curious, not rhetorical
 - If the user makes a universal claim, probe the boundaries
-- If the user uses loaded language, ask them to define their terms{memory_context}{beliefs_context}{contradiction_context}"#
+- If the user uses loaded language, ask them to define their terms{memory_context}{beliefs_context}{contradiction_context}{angle_hint}"#,
+        question_guideline = opts.question_style.guideline(),
+        angle_hint = opts.angle_hint(),
     );
 
     let messages = vec![
@@ -140,37 +420,77 @@ Guidelines:
         },
     ];
 
-    let response = state
-        .ollama
-        .chat(&messages)
+    let (response, rationale) = generate_reply(state, &messages, opts, &mut warnings)
         .await
         .context("Failed to generate Socratic response")?;
 
-    // Store assistant response as memory too.
-    let response_id = Uuid::new_v4();
-    let _ = episodic::store_memory(
-        state,
-        user_id,
-        session_id,
-        response_id,
-        &response,
-        "assistant",
-    )
-    .await;
+    if !opts.persists() {
+        return Ok(DialogueResult {
+            response,
+            contradictions: all_contradictions,
+            beliefs: stored_beliefs,
+            consciousness: None,
+            memories_recalled,
+            rationale,
+            warnings,
+        });
+    }
 
     // 8. Update consciousness metrics.
-    let _ = consciousness::compute_metrics(
+    let consciousness = consciousness::compute_metrics(
         state,
         user_id,
         session_id,
         existing_beliefs.len() + stored_beliefs.len(),
-        all_contradictions.len(),
+        ranked_contradictions.len(),
         1, // This message counts as engagement.
         0, // Beliefs revised is tracked separately.
     )
-    .await;
+    .await
+    .inspect_err(|e| tracing::warn!("Failed to compute consciousness metrics: {e}"))
+    .ok();
 
-    Ok(response)
+    Ok(DialogueResult {
+        response,
+        contradictions: all_contradictions,
+        beliefs: stored_beliefs,
+        consciousness,
+        memories_recalled,
+        rationale,
+        warnings,
+    })
+}
+
+/// Pick the `CONTEXT_BELIEFS` beliefs most similar to `message`, ranked by
+/// their stored embeddings. Falls back to the newest when search fails; when
+/// everything fits, no search is needed.
+async fn select_context_beliefs(
+    state: &AppState,
+    user_id: Uuid,
+    message: &str,
+    existing: &[Belief],
+) -> Vec<Belief> {
+    let limit = state.config.context_beliefs;
+    if existing.len() <= limit {
+        return existing.to_vec();
+    }
+
+    match beliefs::search_beliefs(state, user_id, message, limit as u64).await {
+        Ok(matches) => matches.into_iter().map(|m| m.belief).collect(),
+        Err(e) => {
+            tracing::warn!("Belief similarity search failed, using newest beliefs: {e:#}");
+            existing.iter().take(limit).cloned().collect()
+        }
+    }
+}
+
+/// Render a titled prompt section, or nothing if there are no lines.
+fn context_section(title: &str, lines: &[String]) -> String {
+    if lines.is_empty() {
+        String::new()
+    } else {
+        format!("\n\n{title}:\n{}", lines.join("\n"))
+    }
 }
 
 /// Load session context from Redis for continuity.
@@ -182,7 +502,10 @@ pub async fn get_session_context(state: &AppState, session_id: Uuid) -> Result<V
         .arg(&key)
         .query_async(&mut conn)
         .await
-        .unwrap_or(None);
+        .unwrap_or_else(|e| {
+            record_backend_error("session_context_get", &e);
+            None
+        });
 
     match raw {
         Some(json) => {
@@ -211,6 +534,7 @@ pub async fn save_session_context(
         .arg(86400)
         .query_async::<()>(&mut conn)
         .await
+        .inspect_err(|e| record_backend_error("session_context_set", e))
         .context("Failed to save session to Redis")?;
 
     Ok(())
@@ -0,0 +1,209 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use sqlx::types::Json;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::river::beliefs;
+use nexus_common::error::NexusError;
+use nexus_common::types::{Belief, BeliefEdgeKind, Digest, DigestContradiction};
+
+/// Beliefs and contradictions included in the reflection prompt.
+const PROMPT_BELIEFS: usize = 30;
+const PROMPT_CONTRADICTIONS: usize = 10;
+
+/// Every `interval`, write digests for the previous UTC day for users who
+/// were active then and don't have one yet.
+pub async fn run_scheduler(state: AppState, interval: Duration) {
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let Some(yesterday) = Utc::now().date_naive().checked_sub_days(Days::new(1)) else {
+            continue;
+        };
+        match generate_missing(&state, yesterday).await {
+            Ok(0) => {}
+            Ok(written) => tracing::info!(%yesterday, written, "Wrote daily digests"),
+            Err(e) => tracing::warn!("Daily digest generation failed: {e:#}"),
+        }
+    }
+}
+
+/// Write digests for `date` for every user active that day without one.
+async fn generate_missing(state: &AppState, date: NaiveDate) -> Result<usize> {
+    let (start, end) = day_bounds(date);
+    let users: Vec<(Uuid,)> = sqlx::query_as(
+        "SELECT DISTINCT m.user_id FROM messages m
+         WHERE m.created_at >= $1 AND m.created_at < $2
+           AND NOT EXISTS (
+               SELECT 1 FROM digests d WHERE d.user_id = m.user_id AND d.digest_date = $3
+           )",
+    )
+    .bind(start)
+    .bind(end)
+    .bind(date)
+    .fetch_all(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to find active users: {e}")))?;
+
+    let mut written = 0;
+    for (user_id,) in users {
+        match generate(state, user_id, date).await {
+            Ok(_) => written += 1,
+            Err(e) => tracing::warn!(%user_id, %date, "Failed to generate digest: {e:#}"),
+        }
+    }
+    Ok(written)
+}
+
+/// Aggregate the user's activity on `date` and store it as a digest,
+/// replacing any digest already stored for that day.
+pub async fn generate(state: &AppState, user_id: Uuid, date: NaiveDate) -> Result<Digest> {
+    let (start, end) = day_bounds(date);
+    let on_day = |t: &DateTime<Utc>| (start..end).contains(t);
+
+    let graph = beliefs::get_belief_graph(state, user_id).await?;
+    let new_beliefs: Vec<Belief> = graph
+        .nodes
+        .iter()
+        .filter(|b| on_day(&b.created_at))
+        .cloned()
+        .collect();
+
+    // Contradiction links aren't timestamped; date them by the newer belief.
+    let belief = |id: Uuid| graph.nodes.iter().find(|b| b.id == id);
+    let contradictions: Vec<DigestContradiction> = graph
+        .edges
+        .iter()
+        .filter(|e| e.kind == BeliefEdgeKind::Contradicts)
+        .filter_map(|e| {
+            let (a, b) = (belief(e.source)?, belief(e.target)?);
+            on_day(&a.created_at.max(b.created_at)).then(|| DigestContradiction {
+                claim_a: a.claim.clone(),
+                claim_b: b.claim.clone(),
+                explanation: e.explanation.clone(),
+            })
+        })
+        .collect();
+
+    let reflection = reflect(state, &new_beliefs, &contradictions).await?;
+
+    let digest = Digest {
+        user_id,
+        date,
+        new_beliefs,
+        contradictions,
+        reflection,
+        created_at: Utc::now(),
+    };
+
+    sqlx::query(
+        "INSERT INTO digests (id, user_id, digest_date, new_beliefs, contradictions, reflection, created_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (user_id, digest_date) DO UPDATE SET
+             new_beliefs = EXCLUDED.new_beliefs,
+             contradictions = EXCLUDED.contradictions,
+             reflection = EXCLUDED.reflection,
+             created_at = EXCLUDED.created_at",
+    )
+    .bind(Uuid::new_v4())
+    .bind(user_id)
+    .bind(date)
+    .bind(Json(&digest.new_beliefs))
+    .bind(Json(&digest.contradictions))
+    .bind(&digest.reflection)
+    .bind(digest.created_at)
+    .execute(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to store digest: {e}")))?;
+
+    Ok(digest)
+}
+
+/// The stored digest for `date`, if one was generated.
+pub async fn get_digest(
+    state: &AppState,
+    user_id: Uuid,
+    date: NaiveDate,
+) -> Result<Option<Digest>> {
+    type DigestRow = (
+        Json<Vec<Belief>>,
+        Json<Vec<DigestContradiction>>,
+        String,
+        DateTime<Utc>,
+    );
+    let row: Option<DigestRow> = sqlx::query_as(
+        "SELECT new_beliefs, contradictions, reflection, created_at
+         FROM digests WHERE user_id = $1 AND digest_date = $2",
+    )
+    .bind(user_id)
+    .bind(date)
+    .fetch_optional(&state.db.pg)
+    .await
+    .map_err(|e| NexusError::Database(format!("Failed to load digest: {e}")))?;
+
+    Ok(row.map(
+        |(Json(new_beliefs), Json(contradictions), reflection, created_at)| Digest {
+            user_id,
+            date,
+            new_beliefs,
+            contradictions,
+            reflection,
+            created_at,
+        },
+    ))
+}
+
+/// Ask the LLM for a one-paragraph reflection on the day.
+async fn reflect(
+    state: &AppState,
+    new_beliefs: &[Belief],
+    contradictions: &[DigestContradiction],
+) -> Result<String> {
+    if new_beliefs.is_empty() && contradictions.is_empty() {
+        return Ok("No new beliefs or contradictions surfaced today.".to_string());
+    }
+
+    let belief_lines: Vec<String> = new_beliefs
+        .iter()
+        .take(PROMPT_BELIEFS)
+        .map(|b| format!("- \"{}\" (confidence {:.1})", b.claim, b.confidence))
+        .collect();
+    let contradiction_lines: Vec<String> = contradictions
+        .iter()
+        .take(PROMPT_CONTRADICTIONS)
+        .map(|c| {
+            format!(
+                "- \"{}\" vs \"{}\": {}",
+                c.claim_a,
+                c.claim_b,
+                c.explanation.as_deref().unwrap_or("")
+            )
+        })
+        .collect();
+
+    let system = r#"You are writing the end-of-day entry in a person's thinking journal. In one short paragraph, reflect on the beliefs they formed today and any contradictions that surfaced, and end with one open question worth sitting with. Write in the second person ("Today you..."). Be warm and neutral — do not judge or argue with the beliefs."#;
+
+    let mut prompt = format!("New beliefs today:\n{}", belief_lines.join("\n"));
+    if !contradiction_lines.is_empty() {
+        prompt.push_str(&format!(
+            "\n\nContradictions surfaced:\n{}",
+            contradiction_lines.join("\n")
+        ));
+    }
+
+    let reflection = state
+        .llm
+        .generate(&prompt, Some(system))
+        .await
+        .context("Failed to generate digest reflection")?;
+
+    Ok(reflection.trim().to_string())
+}
+
+/// Start (inclusive) and end (exclusive) of `date` in UTC.
+fn day_bounds(date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start = date.and_time(chrono::NaiveTime::MIN).and_utc();
+    (start, start + chrono::Duration::days(1))
+}
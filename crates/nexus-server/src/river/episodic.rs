@@ -1,39 +1,180 @@
 use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
 use qdrant_client::qdrant::{
-    CreateCollectionBuilder, Distance, PointStruct, SearchPointsBuilder, UpsertPointsBuilder,
-    VectorParamsBuilder,
+    Condition, DeletePointsBuilder, Filter, NamedVectors, PointId, PointStruct, PointsIdsList,
+    Range, ScrollPointsBuilder, SearchPointsBuilder, SetPayloadPointsBuilder, UpsertPointsBuilder,
+    Value, point_id::PointIdOptions, vectors_config,
 };
+use serde::Deserialize;
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::api::state::AppState;
+use crate::db::qdrant;
+use nexus_common::error::NexusError;
 
 const COLLECTION_NAME: &str = "episodic_memory";
 
+/// Name of the dense embedding vector. Further representations (e.g. sparse
+/// keyword vectors) can be added alongside it without recreating the collection.
+const DENSE_VECTOR: &str = "dense";
+
+/// Set when the collection predates named vectors and only has a single
+/// unnamed vector; reads and writes then fall back to the unnamed vector.
+static LEGACY_UNNAMED_VECTOR: AtomicBool = AtomicBool::new(false);
+
+fn uses_named_vectors() -> bool {
+    !LEGACY_UNNAMED_VECTOR.load(Ordering::Relaxed)
+}
+
+/// Reciprocal Rank Fusion constant; 60 is the value from the original paper.
+const RRF_K: f32 = 60.0;
+
+/// How memories are matched against a query, selected by `MEMORY_SEARCH_MODE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Embedding similarity only.
+    Vector,
+    /// Exact term matches on the memory text only.
+    Keyword,
+    /// Both, merged with Reciprocal Rank Fusion.
+    Hybrid,
+}
+
+impl FromStr for SearchMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "vector" => Ok(Self::Vector),
+            "keyword" => Ok(Self::Keyword),
+            "hybrid" => Ok(Self::Hybrid),
+            other => anyhow::bail!(
+                "Unknown MEMORY_SEARCH_MODE '{other}' (expected vector, keyword or hybrid)"
+            ),
+        }
+    }
+}
+
+/// Which of a user's memories recall draws on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecallScope {
+    /// Every session, for continuity across conversations.
+    #[default]
+    User,
+    /// Only the current session, for focused deep-dives.
+    Session,
+}
+
+impl RecallScope {
+    /// The session recall is limited to, if any.
+    pub fn session(self, session_id: Uuid) -> Option<Uuid> {
+        match self {
+            Self::User => None,
+            Self::Session => Some(session_id),
+        }
+    }
+}
+
 /// Ensure the episodic memory collection exists in Qdrant.
 pub async fn ensure_collection(state: &AppState) -> Result<()> {
-    let collections = state.db.qdrant.list_collections().await?;
+    let collections = state
+        .db
+        .qdrant
+        .list_collections()
+        .await
+        .map_err(NexusError::from)?;
 
-    let exists = collections
+    let mut exists = collections
         .collections
         .iter()
         .any(|c| c.name == COLLECTION_NAME);
 
+    let dim = state.embeddings.dimension();
+    if exists
+        && qdrant::drop_on_vector_mismatch(
+            &state.db.qdrant,
+            &state.config.qdrant,
+            COLLECTION_NAME,
+            DENSE_VECTOR,
+            dim,
+        )
+        .await?
+    {
+        exists = false;
+    }
+
     if !exists {
-        let dim = state.embeddings.dimension();
-        state
-            .db
-            .qdrant
-            .create_collection(
-                CreateCollectionBuilder::new(COLLECTION_NAME)
-                    .vectors_config(VectorParamsBuilder::new(dim, Distance::Cosine)),
-            )
-            .await
-            .context("Failed to create episodic memory collection")?;
+        qdrant::create_collection(
+            &state.db.qdrant,
+            COLLECTION_NAME,
+            DENSE_VECTOR,
+            dim,
+            state.config.qdrant.distance,
+            state.config.qdrant.scalar_quantization,
+        )
+        .await
+        .map_err(|e| {
+            NexusError::VectorStore(format!(
+                "Failed to create episodic memory collection: {e:#}"
+            ))
+        })?;
 
         tracing::info!("Created Qdrant collection: {COLLECTION_NAME}");
+        return ensure_indexes(state).await;
+    }
+
+    let info = state
+        .db
+        .qdrant
+        .collection_info(COLLECTION_NAME)
+        .await
+        .map_err(|e| {
+            NexusError::VectorStore(format!("Failed to inspect episodic memory collection: {e}"))
+        })?;
+    let legacy = matches!(
+        info.result
+            .and_then(|r| r.config)
+            .and_then(|c| c.params)
+            .and_then(|p| p.vectors_config)
+            .and_then(|v| v.config),
+        Some(vectors_config::Config::Params(_))
+    );
+
+    if legacy {
+        LEGACY_UNNAMED_VECTOR.store(true, Ordering::Relaxed);
+        tracing::warn!(
+            "Qdrant collection {COLLECTION_NAME} uses an unnamed vector; \
+             falling back to it. Recreate the collection to enable named vectors."
+        );
     }
 
+    ensure_indexes(state).await
+}
+
+/// Payload indexes for the filters recall applies: keyword indexes on
+/// `user_id` and `session_id`, and a full-text index on `content` for keyword
+/// search. Creating an index that already exists is a no-op in Qdrant.
+async fn ensure_indexes(state: &AppState) -> Result<()> {
+    qdrant::ensure_keyword_indexes(
+        &state.db.qdrant,
+        COLLECTION_NAME,
+        &["user_id", "session_id"],
+    )
+    .await?;
+
+    qdrant::ensure_text_index(&state.db.qdrant, COLLECTION_NAME, "content")
+        .await
+        .map_err(|e| {
+            NexusError::VectorStore(format!(
+                "Failed to create episodic memory text index: {e:#}"
+            ))
+        })?;
     Ok(())
 }
 
@@ -46,81 +187,482 @@ pub async fn store_memory(
     content: &str,
     role: &str,
 ) -> Result<()> {
-    let embedding = state
-        .embeddings
-        .embed(content)
-        .await
-        .context("Failed to generate embedding for memory")?;
+    // The embedding is stored too, so it's taken from the redacted text.
+    let content = state.redactor.redact(content);
+    let embedding = state.embeddings.embed(&content).await.map_err(|e| {
+        NexusError::Embedding(format!("Failed to generate embedding for memory: {e:#}"))
+    })?;
 
+    let now = chrono::Utc::now();
     let payload: serde_json::Map<String, serde_json::Value> = serde_json::from_value(json!({
         "user_id": user_id.to_string(),
         "session_id": session_id.to_string(),
         "message_id": message_id.to_string(),
         "content": content,
         "role": role,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "timestamp": now.to_rfc3339(),
+        // Numeric copy of the timestamp so retention pruning can use a range filter.
+        "timestamp_unix": now.timestamp(),
     }))?;
 
-    let point = PointStruct::new(message_id.to_string(), embedding, payload);
+    let point = if uses_named_vectors() {
+        PointStruct::new(
+            message_id.to_string(),
+            NamedVectors::default().add_vector(DENSE_VECTOR, embedding),
+            payload,
+        )
+    } else {
+        PointStruct::new(message_id.to_string(), embedding, payload)
+    };
 
     state
         .db
         .qdrant
         .upsert_points(UpsertPointsBuilder::new(COLLECTION_NAME, vec![point]))
         .await
-        .context("Failed to store episodic memory")?;
+        .map_err(|e| NexusError::VectorStore(format!("Failed to store episodic memory: {e}")))?;
+
+    Ok(())
+}
+
+/// Delete the memories stored for these messages, keyed by message id.
+pub async fn delete_memories(state: &AppState, message_ids: &[Uuid]) -> Result<()> {
+    if message_ids.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<PointId> = message_ids.iter().map(|id| id.to_string().into()).collect();
+    state
+        .db
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(COLLECTION_NAME)
+                .points(PointsIdsList { ids })
+                .wait(true),
+        )
+        .await
+        .map_err(|e| NexusError::VectorStore(format!("Failed to delete episodic memories: {e}")))?;
+    Ok(())
+}
 
+/// Delete every memory of these users.
+pub async fn delete_user_memories(state: &AppState, user_ids: &[Uuid]) -> Result<()> {
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+    let ids: Vec<String> = user_ids.iter().map(Uuid::to_string).collect();
+    state
+        .db
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(COLLECTION_NAME)
+                .points(Filter::must([Condition::matches("user_id", ids)]))
+                .wait(true),
+        )
+        .await
+        .map_err(|e| NexusError::VectorStore(format!("Failed to delete user memories: {e}")))?;
     Ok(())
 }
 
-/// Search for relevant past memories using semantic similarity.
+/// Delete memories older than the retention window. Points stored before
+/// `timestamp_unix` existed get it first, from their `timestamp`.
+pub async fn prune_memories(state: &AppState, retention_days: u64) -> Result<()> {
+    backfill_timestamp_unix(state).await?;
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(retention_days as i64);
+
+    let filter = Filter::must([Condition::range(
+        "timestamp_unix",
+        Range {
+            lt: Some(cutoff.timestamp() as f64),
+            ..Default::default()
+        },
+    )]);
+
+    state
+        .db
+        .qdrant
+        .delete_points(
+            DeletePointsBuilder::new(COLLECTION_NAME)
+                .points(filter)
+                .wait(true),
+        )
+        .await
+        .map_err(|e| NexusError::VectorStore(format!("Failed to prune episodic memory: {e}")))?;
+
+    tracing::info!(%cutoff, "Pruned episodic memories older than {retention_days} days");
+    Ok(())
+}
+
+/// Set `timestamp_unix` on points that lack it, parsed from their RFC 3339
+/// `timestamp`, so the retention range filter can see them. Points without a
+/// parseable `timestamp` are left alone.
+async fn backfill_timestamp_unix(state: &AppState) -> Result<()> {
+    let missing = Filter::must([Condition::is_empty("timestamp_unix")]);
+    let mut offset: Option<PointId> = None;
+    let mut backfilled = 0usize;
+
+    loop {
+        let mut scroll = ScrollPointsBuilder::new(COLLECTION_NAME)
+            .filter(missing.clone())
+            .limit(256)
+            .with_payload(true);
+        if let Some(offset) = offset.take() {
+            scroll = scroll.offset(offset);
+        }
+        let page =
+            state.db.qdrant.scroll(scroll).await.map_err(|e| {
+                NexusError::VectorStore(format!("Failed to scan episodic memory: {e}"))
+            })?;
+
+        for point in page.result {
+            let (Some(id), Some(timestamp)) = (
+                point.id,
+                point
+                    .payload
+                    .get("timestamp")
+                    .and_then(|v| v.as_str())
+                    .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok()),
+            ) else {
+                continue;
+            };
+            let payload: serde_json::Map<String, serde_json::Value> =
+                serde_json::from_value(json!({ "timestamp_unix": timestamp.timestamp() }))?;
+            state
+                .db
+                .qdrant
+                .set_payload(
+                    SetPayloadPointsBuilder::new(COLLECTION_NAME, payload)
+                        .points_selector(PointsIdsList { ids: vec![id] })
+                        .wait(true),
+                )
+                .await
+                .map_err(|e| {
+                    NexusError::VectorStore(format!("Failed to backfill memory timestamp: {e}"))
+                })?;
+            backfilled += 1;
+        }
+
+        match page.next_page_offset {
+            Some(next) => offset = Some(next),
+            None => break,
+        }
+    }
+
+    if backfilled > 0 {
+        tracing::info!(backfilled, "Backfilled timestamp_unix on episodic memories");
+    }
+    Ok(())
+}
+
+/// Prune expired memories every `interval`. Runs until the process exits.
+pub async fn run_pruning(state: AppState, retention_days: u64, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = prune_memories(&state, retention_days).await {
+            tracing::warn!("Episodic memory pruning failed: {e}");
+        }
+    }
+}
+
+/// Search for relevant past memories.
+///
+/// `Vector` ranks by embedding similarity, `Keyword` by how many query terms
+/// appear verbatim, and `Hybrid` runs both and merges them with Reciprocal
+/// Rank Fusion, so exact names, dates and jargon surface even when the
+/// embedding doesn't rank them highly.
 pub async fn recall_similar(
     state: &AppState,
     user_id: Uuid,
+    session_id: Option<Uuid>,
     query_text: &str,
     limit: u64,
+    mode: SearchMode,
 ) -> Result<Vec<MemoryResult>> {
-    let query_embedding = state
-        .embeddings
-        .embed(query_text)
-        .await
-        .context("Failed to generate query embedding")?;
+    let ranked = match mode {
+        SearchMode::Vector => vector_search(state, user_id, session_id, query_text, limit).await?,
+        SearchMode::Keyword => {
+            keyword_search(state, user_id, session_id, query_text, limit).await?
+        }
+        SearchMode::Hybrid => {
+            let (vector, keyword) = tokio::join!(
+                vector_search(state, user_id, session_id, query_text, limit),
+                keyword_search(state, user_id, session_id, query_text, limit),
+            );
+            // Either side may fail (e.g. embedding backend down); use whatever we got.
+            let vector = vector.unwrap_or_else(|e| {
+                tracing::warn!("Vector memory search failed: {e}");
+                Vec::new()
+            });
+            let keyword = keyword.unwrap_or_else(|e| {
+                tracing::warn!("Keyword memory search failed: {e}");
+                Vec::new()
+            });
+            reciprocal_rank_fusion(vec![vector, keyword], limit as usize)
+        }
+    };
 
-    let filter = qdrant_client::qdrant::Filter::must([qdrant_client::qdrant::Condition::matches(
-        "user_id",
-        user_id.to_string(),
-    )]);
+    Ok(ranked.into_iter().map(|(_, m)| m).collect())
+}
+
+/// Nearest neighbours by embedding, keyed by point ID.
+async fn vector_search(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Option<Uuid>,
+    query_text: &str,
+    limit: u64,
+) -> Result<Vec<(String, MemoryResult)>> {
+    let query_embedding =
+        state.embeddings.embed(query_text).await.map_err(|e| {
+            NexusError::Embedding(format!("Failed to generate query embedding: {e:#}"))
+        })?;
 
+    let filter = recall_filter(user_id, session_id);
+
+    let mut search = SearchPointsBuilder::new(COLLECTION_NAME, query_embedding, limit)
+        .filter(filter)
+        .with_payload(true);
+    if uses_named_vectors() {
+        search = search.vector_name(DENSE_VECTOR);
+    }
+
+    let results =
+        state.db.qdrant.search_points(search).await.map_err(|e| {
+            NexusError::VectorStore(format!("Failed to search episodic memory: {e}"))
+        })?;
+
+    Ok(results
+        .result
+        .into_iter()
+        .filter_map(|point| {
+            let memory = to_memory(&point.payload, point.score)?;
+            Some((point_key(point.id)?, memory))
+        })
+        .collect())
+}
+
+/// Memories containing any of the query's terms, ranked by how many distinct
+/// terms they contain.
+async fn keyword_search(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Option<Uuid>,
+    query_text: &str,
+    limit: u64,
+) -> Result<Vec<(String, MemoryResult)>> {
+    let terms = query_terms(query_text);
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut filter = recall_filter(user_id, session_id);
+    filter.should = terms
+        .iter()
+        .map(|t| Condition::matches_text("content", t.clone()))
+        .collect();
+
+    // Fetch a wider pool than needed, since Qdrant doesn't rank scroll results.
     let results = state
         .db
         .qdrant
-        .search_points(
-            SearchPointsBuilder::new(COLLECTION_NAME, query_embedding, limit)
+        .scroll(
+            ScrollPointsBuilder::new(COLLECTION_NAME)
                 .filter(filter)
+                .limit((limit * 4) as u32)
                 .with_payload(true),
         )
         .await
-        .context("Failed to search episodic memory")?;
+        .map_err(|e| {
+            NexusError::VectorStore(format!("Failed to keyword search episodic memory: {e}"))
+        })?;
 
-    let memories = results
+    let mut matches: Vec<(String, MemoryResult)> = results
         .result
         .into_iter()
         .filter_map(|point| {
-            let payload = &point.payload;
-            let content = payload.get("content")?.as_str()?.to_string();
-            let role = payload.get("role")?.as_str()?.to_string();
-            let timestamp = payload.get("timestamp")?.as_str()?.to_string();
-
-            Some(MemoryResult {
-                content,
-                role,
-                timestamp,
-                score: point.score,
-            })
+            let mut memory = to_memory(&point.payload, 0.0)?;
+            let content = memory.content.to_lowercase();
+            let hits = terms
+                .iter()
+                .filter(|t| content.contains(t.as_str()))
+                .count();
+            memory.score = hits as f32 / terms.len() as f32;
+            Some((point_key(point.id)?, memory))
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.1.score
+            .total_cmp(&a.1.score)
+            .then_with(|| b.1.timestamp.cmp(&a.1.timestamp))
+    });
+    matches.truncate(limit as usize);
+    Ok(matches)
+}
+
+/// Memories of `user_id`, narrowed to one session when `session_id` is given.
+fn recall_filter(user_id: Uuid, session_id: Option<Uuid>) -> Filter {
+    let mut conditions = vec![Condition::matches("user_id", user_id.to_string())];
+    if let Some(session_id) = session_id {
+        conditions.push(Condition::matches("session_id", session_id.to_string()));
+    }
+    Filter::must(conditions)
+}
+
+/// Lowercased, deduplicated query words of at least two characters.
+fn query_terms(query_text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = query_text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= 2)
+        .map(str::to_lowercase)
+        .collect();
+    terms.sort();
+    terms.dedup();
+    terms
+}
+
+/// Merge ranked lists: each item scores `1 / (k + rank)` per list it appears in.
+fn reciprocal_rank_fusion(
+    lists: Vec<Vec<(String, MemoryResult)>>,
+    limit: usize,
+) -> Vec<(String, MemoryResult)> {
+    let mut fused: HashMap<String, (f32, MemoryResult)> = HashMap::new();
+
+    for list in lists {
+        for (rank, (key, memory)) in list.into_iter().enumerate() {
+            let score = 1.0 / (RRF_K + rank as f32 + 1.0);
+            fused
+                .entry(key)
+                .and_modify(|(total, _)| *total += score)
+                .or_insert((score, memory));
+        }
+    }
+
+    let mut merged: Vec<(String, MemoryResult)> = fused
+        .into_iter()
+        .map(|(key, (score, mut memory))| {
+            memory.score = score;
+            (key, memory)
+        })
+        .collect();
+    merged.sort_by(|a, b| b.1.score.total_cmp(&a.1.score));
+    merged.truncate(limit);
+    merged
+}
+
+fn to_memory(payload: &HashMap<String, Value>, score: f32) -> Option<MemoryResult> {
+    Some(MemoryResult {
+        content: payload.get("content")?.as_str()?.to_string(),
+        role: payload.get("role")?.as_str()?.to_string(),
+        timestamp: payload.get("timestamp")?.as_str()?.to_string(),
+        score,
+    })
+}
+
+fn point_key(id: Option<PointId>) -> Option<String> {
+    match id?.point_id_options? {
+        PointIdOptions::Uuid(uuid) => Some(uuid),
+        PointIdOptions::Num(num) => Some(num.to_string()),
+    }
+}
+
+/// Recall memories for prompt context. When `ENABLE_RERANK` is set, a wider
+/// candidate set is fetched from Qdrant and reranked by the LLM before trimming
+/// to `limit`.
+pub async fn recall_relevant(
+    state: &AppState,
+    user_id: Uuid,
+    session_id: Option<Uuid>,
+    query_text: &str,
+    limit: u64,
+) -> Result<Vec<MemoryResult>> {
+    let mode = state.config.memory_search_mode;
+    if !state.config.enable_rerank {
+        return recall_similar(state, user_id, session_id, query_text, limit, mode).await;
+    }
+
+    let candidates = recall_similar(
+        state,
+        user_id,
+        session_id,
+        query_text,
+        state.config.rerank_candidates.max(limit),
+        mode,
+    )
+    .await?;
+
+    match rerank(state, query_text, candidates.clone()).await {
+        Ok(mut reranked) => {
+            reranked.truncate(limit as usize);
+            Ok(reranked)
+        }
+        Err(e) => {
+            tracing::warn!("Memory reranking failed, using vector order: {e}");
+            Ok(candidates.into_iter().take(limit as usize).collect())
+        }
+    }
+}
+
+/// Ask the LLM to score each candidate's conversational relevance to the query,
+/// and return the candidates ordered by that score (highest first).
+async fn rerank(
+    state: &AppState,
+    query_text: &str,
+    candidates: Vec<MemoryResult>,
+) -> Result<Vec<MemoryResult>> {
+    if candidates.len() < 2 {
+        return Ok(candidates);
+    }
+
+    let system = r#"You are a relevance ranker. Given a user's current message and a numbered list of past conversation snippets, score how useful each snippet is as context for responding to the current message. Topical similarity alone is not enough — prefer snippets that continue the same line of thought. Return a JSON object with a "scores" array. Each entry has:
+- "index": the snippet number
+- "score": relevance from 0.0 to 1.0"#;
+
+    let listing: Vec<String> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, m)| format!("{i}. {}: {}", m.role, m.content))
+        .collect();
+    let prompt = format!(
+        "Current message: \"{query_text}\"\n\nPast snippets:\n{}",
+        listing.join("\n")
+    );
+
+    let result: RerankResponse = state
+        .llm
+        .generate_json(&prompt, Some(system))
+        .await
+        .context("Failed to rerank memories")?;
+
+    let mut scored: Vec<(f64, MemoryResult)> = candidates
+        .into_iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let score = result
+                .scores
+                .iter()
+                .find(|s| s.index == i)
+                .map(|s| s.score)
+                .unwrap_or(0.0);
+            (score, m)
         })
         .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
 
-    Ok(memories)
+    Ok(scored.into_iter().map(|(_, m)| m).collect())
+}
+
+#[derive(Deserialize)]
+struct RerankResponse {
+    #[serde(default)]
+    scores: Vec<RerankScore>,
+}
+
+#[derive(Deserialize)]
+struct RerankScore {
+    index: usize,
+    score: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -130,3 +672,50 @@ pub struct MemoryResult {
     pub timestamp: String,
     pub score: f32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[tokio::test]
+    #[ignore = "needs the services in .env"]
+    async fn hybrid_recall_finds_exact_terms() {
+        let state = test_support::live_state().await;
+        let user_id = Uuid::new_v4();
+        let session_id = Uuid::new_v4();
+        let memories = [
+            "I drink too much coffee before meetings",
+            "Our team meets every Monday to plan the week",
+            "I prefer tea in the afternoon",
+            "The quarterly planning meeting ran long again",
+            "My manager wants shorter status meetings",
+            "The build server is called Zephyrine-7",
+        ];
+        for content in memories {
+            store_memory(&state, user_id, session_id, Uuid::new_v4(), content, "user")
+                .await
+                .unwrap();
+        }
+
+        let query = "zephyrine-7 meeting";
+        let vector_only = recall_similar(&state, user_id, None, query, 3, SearchMode::Vector).await;
+        let recalled = recall_similar(&state, user_id, None, query, 3, SearchMode::Hybrid).await;
+        delete_user_memories(&state, &[user_id]).await.unwrap();
+
+        // The meeting memories crowd the exact term out of a vector-only
+        // search; the keyword half of the hybrid search brings it back.
+        let vector_only = vector_only.unwrap();
+        assert!(
+            !vector_only
+                .iter()
+                .any(|m| m.content.contains("Zephyrine-7")),
+            "{vector_only:?}"
+        );
+        let recalled = recalled.unwrap();
+        assert!(
+            recalled.iter().any(|m| m.content.contains("Zephyrine-7")),
+            "{recalled:?}"
+        );
+    }
+}
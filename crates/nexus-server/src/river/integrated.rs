@@ -1,60 +1,169 @@
 use anyhow::{Context, Result};
+use futures::future::BoxFuture;
 use uuid::Uuid;
 
 use crate::api::state::AppState;
 use crate::perspective::engine as perspective;
+use crate::river::dialogue::{self, CLARIFYING_PROMPT, TurnOptions};
 use crate::river::{beliefs, consciousness, episodic};
+use crate::shared::chat_engine::{ChatEngine, ChatTurn};
 use crate::shared::ollama::ChatMessage;
-use nexus_common::types::AnalysisResult;
+use crate::shared::text_util;
+use nexus_common::types::{
+    AnalysisResult, Belief, ConsciousnessState, Contradiction, ModalityStrength,
+};
+
+/// Integrated mode: a dialogue turn informed by a Perspective analysis.
+pub struct IntegratedEngine;
+
+impl ChatEngine for IntegratedEngine {
+    fn process<'a>(
+        &'a self,
+        state: &'a AppState,
+        session_id: Uuid,
+        user_id: Uuid,
+        message: &'a str,
+        opts: TurnOptions<'a>,
+    ) -> BoxFuture<'a, Result<ChatTurn>> {
+        Box::pin(async move {
+            let result = process_integrated_with(state, session_id, user_id, message, opts).await?;
+            Ok(ChatTurn {
+                response: result.response,
+                analysis: Some(result.analysis),
+                contradictions: Some(result.contradictions),
+                beliefs: Some(result.beliefs),
+                consciousness: result.consciousness,
+                memories_recalled: Some(result.memories_recalled),
+                rationale: result.rationale,
+                warnings: result.warnings,
+                remember: true,
+            })
+        })
+    }
+}
+
+/// Outcome of an integrated (River + Perspective) turn.
+#[derive(Debug, Clone)]
+pub struct IntegratedResult {
+    pub response: String,
+    pub analysis: AnalysisResult,
+    pub contradictions: Vec<Contradiction>,
+    pub beliefs: Vec<Belief>,
+    /// Metrics computed for this turn, if they could be computed.
+    pub consciousness: Option<ConsciousnessState>,
+    /// Number of episodic memories recalled as context.
+    pub memories_recalled: usize,
+    /// What prompted the question, if requested with [`TurnOptions::explain`].
+    pub rationale: Option<String>,
+    /// Steps that failed without failing the turn.
+    pub warnings: Vec<String>,
+}
 
 /// Integrated mode: River + Perspective combined.
 ///
 /// Flow:
 /// 1. Extract claims from the user's message
-/// 2. Run Perspective 4-layer analysis on the claims
+/// 2. Run Perspective analysis, limited to `INTEGRATED_ANALYSIS_LAYERS`
 /// 3. Recall relevant episodic memories
 /// 4. Detect contradictions with existing beliefs
 /// 5. Generate a Socratic question informed by the discourse analysis insights
-/// 6. Store everything and update metrics
+/// 6. Store beliefs and update metrics
+///
+/// The caller records both messages as episodic memories, through the outbox,
+/// when it persists the turn.
 pub async fn process_integrated(
     state: &AppState,
     session_id: Uuid,
     user_id: Uuid,
     message: &str,
-) -> Result<(String, AnalysisResult)> {
-    let message_id = Uuid::new_v4();
+) -> Result<IntegratedResult> {
+    process_integrated_with(state, session_id, user_id, message, TurnOptions::default()).await
+}
+
+/// [`process_integrated`] with explicit turn options.
+pub async fn process_integrated_with(
+    state: &AppState,
+    session_id: Uuid,
+    user_id: Uuid,
+    message: &str,
+    opts: TurnOptions<'_>,
+) -> Result<IntegratedResult> {
+    if !text_util::has_min_words(message, state.config.min_input_words) {
+        return Ok(IntegratedResult {
+            response: CLARIFYING_PROMPT.into(),
+            analysis: perspective::empty_analysis(message),
+            contradictions: Vec::new(),
+            beliefs: Vec::new(),
+            consciousness: None,
+            memories_recalled: 0,
+            rationale: None,
+            warnings: Vec::new(),
+        });
+    }
+
+    let message_id = opts.source_message_id.unwrap_or_else(Uuid::new_v4);
+    let mut warnings = Vec::new();
 
     // Run Perspective analysis and memory recall in parallel.
-    let (analysis_result, memories, extracted_beliefs) = tokio::try_join!(
-        perspective::analyze_text(state, message),
-        async {
-            episodic::recall_similar(state, user_id, message, 5)
-                .await
-                .or_else(|_| Ok(Vec::new()))
-        },
-        async {
-            beliefs::extract_beliefs(state, message)
-                .await
-                .or_else(|_| Ok(Vec::new()))
-        },
-    )?;
+    let (analysis_result, memories, extracted_beliefs) = tokio::join!(
+        perspective::analyze_layers(
+            state,
+            opts.persists().then_some(user_id),
+            message,
+            &state.config.integrated_analysis_layers,
+        ),
+        episodic::recall_relevant(
+            state,
+            user_id,
+            opts.recall_scope.session(session_id),
+            message,
+            5,
+        ),
+        beliefs::extract_beliefs(state, message),
+    );
+    let analysis_result = analysis_result?;
+    let memories = memories.unwrap_or_else(|e| {
+        tracing::warn!("Memory recall failed: {e:#}");
+        warnings.push("Memory recall failed; past conversations were not used".into());
+        Vec::new()
+    });
+    let extracted_beliefs = extracted_beliefs.unwrap_or_else(|e| {
+        tracing::warn!("Belief extraction failed: {e:#}");
+        warnings.push("Belief extraction failed; no beliefs were recorded".into());
+        Vec::new()
+    });
+
+    let memories_recalled = memories.len();
 
     // Detect contradictions for extracted beliefs.
-    let mut contradictions = Vec::new();
-    for claim in &extracted_beliefs {
-        let contras = beliefs::detect_contradictions(state, user_id, &claim.claim)
-            .await
-            .unwrap_or_default();
-        contradictions.extend(contras);
-    }
+    let contradictions =
+        beliefs::detect_all_contradictions(state, user_id, &extracted_beliefs, &mut warnings).await;
 
-    // Store beliefs.
-    for claim in &extracted_beliefs {
-        let _ = beliefs::store_belief(state, user_id, claim, message_id).await;
+    // Store beliefs (already stored if regenerating).
+    let mut stored_beliefs = Vec::new();
+    let to_store = if !opts.persists() {
+        &[][..]
+    } else {
+        &extracted_beliefs[..]
+    };
+    for claim in to_store {
+        match beliefs::store_belief(state, user_id, claim, message_id).await {
+            Ok(b) => stored_beliefs.push(b),
+            Err(e) => {
+                tracing::warn!("Failed to store belief: {e}");
+                warnings.push(format!("Failed to store belief \"{}\"", claim.claim));
+            }
+        }
     }
 
-    // Store episodic memory.
-    let _ = episodic::store_memory(state, user_id, session_id, message_id, message, "user").await;
+    // Only contradictions above the severity threshold are raised or linked.
+    let significant: Vec<&Contradiction> = contradictions
+        .iter()
+        .filter(|c| !c.below_threshold)
+        .collect();
+
+    // Link contradictions in Neo4j.
+    beliefs::link_contradictions(state, &significant, &stored_beliefs, &mut warnings).await;
 
     // Build rich context from Perspective analysis.
     let analysis_insights = build_analysis_context(&analysis_result);
@@ -69,15 +178,21 @@ pub async fn process_integrated(
         format!("\n\nRelevant past conversations:\n{}", mem_texts.join("\n"))
     };
 
-    let contradiction_context = if contradictions.is_empty() {
+    let contradiction_context = if significant.is_empty() {
         String::new()
     } else {
-        let texts: Vec<String> = contradictions
+        let texts: Vec<String> = significant
             .iter()
             .map(|c| {
                 format!(
-                    "- \"{}\" contradicts \"{}\" ({})",
-                    c.belief_b.claim, c.belief_a.claim, c.explanation
+                    "- {}\"{}\" contradicts \"{}\" ({})",
+                    opts.ref_tag(
+                        "contradiction",
+                        format_args!("{}/{}", c.belief_a.id, c.belief_b.id)
+                    ),
+                    c.belief_b.claim,
+                    c.belief_a.claim,
+                    c.explanation
                 )
             })
             .collect();
@@ -95,13 +210,15 @@ DISCOURSE ANALYSIS INSIGHTS:
 
 Your task:
 1. Use the discourse analysis to identify the most significant epistemic gap in the user's statement
-2. Ask ONE precise Socratic question that helps the user examine their own framing
+2. {question_guideline}, helping the user examine their own framing
 3. Reference specific findings (e.g., "You used the word 'always' — what exceptions might exist?")
 4. Do NOT lecture about discourse analysis — use the insights to ask better questions
 5. Be genuinely curious and non-judgmental
 6. If contradictions were found, gently surface the most significant one
 
-The question should be something the user has NOT considered, directly informed by the analysis."#
+Ask about what the user has NOT considered, directly informed by the analysis.{angle_hint}"#,
+        question_guideline = opts.question_style.guideline(),
+        angle_hint = opts.angle_hint(),
     );
 
     let messages = vec![
@@ -115,40 +232,50 @@ The question should be something the user has NOT considered, directly informed
         },
     ];
 
-    let response = state
-        .ollama
-        .chat(&messages)
+    let (response, rationale) = dialogue::generate_reply(state, &messages, opts, &mut warnings)
         .await
         .context("Failed to generate integrated response")?;
 
-    // Store response as memory.
-    let response_id = Uuid::new_v4();
-    let _ = episodic::store_memory(
-        state,
-        user_id,
-        session_id,
-        response_id,
-        &response,
-        "assistant",
-    )
-    .await;
+    if !opts.persists() {
+        return Ok(IntegratedResult {
+            response,
+            analysis: analysis_result,
+            contradictions,
+            beliefs: stored_beliefs,
+            consciousness: None,
+            memories_recalled,
+            rationale,
+            warnings,
+        });
+    }
 
     // Update consciousness metrics.
-    let existing = beliefs::get_user_beliefs(state, user_id)
+    let existing = beliefs::get_user_beliefs(state, user_id, None)
         .await
         .unwrap_or_default();
-    let _ = consciousness::compute_metrics(
+    let consciousness = consciousness::compute_metrics(
         state,
         user_id,
         session_id,
         existing.len(),
-        contradictions.len(),
+        significant.len(),
         1,
         0,
     )
-    .await;
+    .await
+    .inspect_err(|e| tracing::warn!("Failed to compute consciousness metrics: {e}"))
+    .ok();
 
-    Ok((response, analysis_result))
+    Ok(IntegratedResult {
+        response,
+        analysis: analysis_result,
+        contradictions,
+        beliefs: stored_beliefs,
+        consciousness,
+        memories_recalled,
+        rationale,
+        warnings,
+    })
 }
 
 /// Build a human-readable summary of Perspective analysis for the Socratic prompt.
@@ -178,6 +305,29 @@ fn build_analysis_context(analysis: &AnalysisResult) -> String {
         ));
     }
 
+    let mut strong: Vec<String> = Vec::new();
+    let mut hedges: Vec<String> = Vec::new();
+    for m in &analysis.syntactic.modality {
+        let markers = match m.strength {
+            ModalityStrength::Strong => &mut strong,
+            ModalityStrength::Hedge => &mut hedges,
+        };
+        let quoted = format!("'{}'", m.marker);
+        if !markers.contains(&quoted) {
+            markers.push(quoted);
+        }
+    }
+    if !strong.is_empty() {
+        parts.push(format!(
+            "Strong modals used: {} — probe for exceptions (e.g. \"you said {} — what exceptions exist?\")",
+            strong.join(", "),
+            strong[0]
+        ));
+    }
+    if !hedges.is_empty() {
+        parts.push(format!("Hedges used: {}", hedges.join(", ")));
+    }
+
     // Semantic highlights.
     if !analysis.semantic.presuppositions.is_empty() {
         let presups: Vec<String> = analysis
@@ -1,5 +1,9 @@
+pub mod belief_audit;
+pub mod belief_summary;
 pub mod beliefs;
 pub mod consciousness;
+pub mod contradictions;
 pub mod dialogue;
+pub mod digest;
 pub mod episodic;
 pub mod integrated;
@@ -0,0 +1,54 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use uuid::Uuid;
+
+use crate::api::state::AppState;
+use crate::perspective::engine::AnalysisEngine;
+use crate::river::dialogue::{ConversationEngine, TurnOptions};
+use crate::river::integrated::IntegratedEngine;
+use nexus_common::types::{AnalysisResult, Belief, ChatMode, ConsciousnessState, Contradiction};
+
+/// Outcome of a chat turn in any mode.
+#[derive(Debug, Clone)]
+pub struct ChatTurn {
+    pub response: String,
+    pub analysis: Option<AnalysisResult>,
+    /// `None` when the mode doesn't check for contradictions.
+    pub contradictions: Option<Vec<Contradiction>>,
+    /// `None` when the mode doesn't extract beliefs.
+    pub beliefs: Option<Vec<Belief>>,
+    pub consciousness: Option<ConsciousnessState>,
+    /// Number of episodic memories recalled as context, if the mode recalls any.
+    pub memories_recalled: Option<usize>,
+    pub rationale: Option<String>,
+    pub warnings: Vec<String>,
+    /// Whether both messages of the turn are stored as episodic memories.
+    pub remember: bool,
+}
+
+/// Processes a user message in one chat mode. REST and WebSocket turns both
+/// go through [`for_mode`], so a mode behaves the same over either.
+pub trait ChatEngine: Send + Sync {
+    fn process<'a>(
+        &'a self,
+        state: &'a AppState,
+        session_id: Uuid,
+        user_id: Uuid,
+        message: &'a str,
+        opts: TurnOptions<'a>,
+    ) -> BoxFuture<'a, Result<ChatTurn>>;
+
+    /// Whether a reply can be regenerated with [`TurnOptions::previous_response`].
+    fn can_regenerate(&self) -> bool {
+        true
+    }
+}
+
+/// The engine serving `mode`.
+pub fn for_mode(mode: ChatMode) -> &'static dyn ChatEngine {
+    match mode {
+        ChatMode::Conversation => &ConversationEngine,
+        ChatMode::Analysis => &AnalysisEngine,
+        ChatMode::Integrated => &IntegratedEngine,
+    }
+}
@@ -0,0 +1,208 @@
+use anyhow::Result;
+use std::sync::atomic::{AtomicU8, AtomicU32, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use nexus_common::error::NexusError;
+
+/// Circuit breaker thresholds for the LLM backend.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit; 0 disables the breaker.
+    pub failure_threshold: u32,
+    /// How long an open circuit fails fast before letting a probe through.
+    pub cooldown_secs: u64,
+}
+
+const CLOSED: u8 = 0;
+const OPEN: u8 = 1;
+const HALF_OPEN: u8 = 2;
+
+/// Stops calling a backend that keeps failing. Closed, calls go through and
+/// consecutive failures are counted; at the threshold the circuit opens and
+/// calls fail at once for the cooldown. After it, a single probe is let
+/// through (half-open): success closes the circuit, failure reopens it.
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: AtomicU8,
+    failures: AtomicU32,
+    /// When the circuit last opened or let a probe through, in ms since `epoch`.
+    changed_at_ms: AtomicU64,
+    epoch: Instant,
+}
+
+impl CircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: AtomicU8::new(CLOSED),
+            failures: AtomicU32::new(0),
+            changed_at_ms: AtomicU64::new(0),
+            epoch: Instant::now(),
+        }
+    }
+
+    /// Run `call` unless the circuit is open, recording how it went.
+    pub async fn call<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        if !self.try_acquire() {
+            return Err(NexusError::Llm("circuit open".into()).into());
+        }
+        let outcome = call.await;
+        match &outcome {
+            Ok(_) => self.record_success(),
+            Err(_) => self.record_failure(),
+        }
+        outcome
+    }
+
+    /// `closed`, `open` or `half_open`, for health reporting.
+    pub fn state_name(&self) -> &'static str {
+        match self.state.load(Ordering::Acquire) {
+            OPEN => "open",
+            HALF_OPEN => "half_open",
+            _ => "closed",
+        }
+    }
+
+    fn try_acquire(&self) -> bool {
+        if self.config.failure_threshold == 0 {
+            return true;
+        }
+        match self.state.load(Ordering::Acquire) {
+            CLOSED => true,
+            // A probe that never reported back (its caller gave up on it)
+            // must not hold the circuit half-open forever, so a stale one is
+            // replaced like an expired open circuit.
+            state if self.cooled_down() => {
+                let probing = self
+                    .state
+                    .compare_exchange(state, HALF_OPEN, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok();
+                if probing {
+                    self.mark_changed();
+                    tracing::info!("LLM circuit half-open; sending a probe request");
+                }
+                probing
+            }
+            _ => false,
+        }
+    }
+
+    fn record_success(&self) {
+        self.failures.store(0, Ordering::Release);
+        if self.state.swap(CLOSED, Ordering::AcqRel) != CLOSED {
+            tracing::info!("LLM circuit closed");
+        }
+    }
+
+    fn record_failure(&self) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+        let failures = self.failures.fetch_add(1, Ordering::AcqRel) + 1;
+        let state = self.state.load(Ordering::Acquire);
+        if state == HALF_OPEN || (state == CLOSED && failures >= self.config.failure_threshold) {
+            self.state.store(OPEN, Ordering::Release);
+            self.mark_changed();
+            tracing::warn!(
+                failures,
+                cooldown_secs = self.config.cooldown_secs,
+                "LLM circuit opened"
+            );
+        }
+    }
+
+    fn cooled_down(&self) -> bool {
+        let since = (self.epoch.elapsed().as_millis() as u64)
+            .saturating_sub(self.changed_at_ms.load(Ordering::Acquire));
+        Duration::from_millis(since) >= Duration::from_secs(self.config.cooldown_secs)
+    }
+
+    fn mark_changed(&self) {
+        self.changed_at_ms
+            .store(self.epoch.elapsed().as_millis() as u64, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(failure_threshold: u32, cooldown_secs: u64) -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold,
+            cooldown_secs,
+        })
+    }
+
+    async fn fail(breaker: &CircuitBreaker) {
+        let outcome: Result<()> = breaker.call(async { Err(anyhow::anyhow!("down")) }).await;
+        assert!(outcome.is_err());
+    }
+
+    #[tokio::test]
+    async fn opens_after_the_failure_threshold() {
+        let breaker = breaker(3, 60);
+        fail(&breaker).await;
+        fail(&breaker).await;
+        assert_eq!(breaker.state_name(), "closed");
+        fail(&breaker).await;
+        assert_eq!(breaker.state_name(), "open");
+    }
+
+    #[tokio::test]
+    async fn success_resets_the_failure_count() {
+        let breaker = breaker(2, 60);
+        fail(&breaker).await;
+        breaker.call(async { Ok(()) }).await.unwrap();
+        fail(&breaker).await;
+        assert_eq!(breaker.state_name(), "closed");
+    }
+
+    #[tokio::test]
+    async fn rejects_calls_while_open() {
+        let breaker = breaker(1, 60);
+        fail(&breaker).await;
+
+        let mut ran = false;
+        let outcome = breaker
+            .call(async {
+                ran = true;
+                Ok(())
+            })
+            .await;
+        assert!(!ran);
+        assert!(matches!(
+            outcome.unwrap_err().downcast_ref::<NexusError>(),
+            Some(NexusError::Llm(_))
+        ));
+        assert_eq!(breaker.state_name(), "open");
+    }
+
+    #[tokio::test]
+    async fn lets_one_probe_through_after_the_cooldown() {
+        let breaker = breaker(1, 0);
+        fail(&breaker).await;
+        assert_eq!(breaker.state_name(), "open");
+
+        assert!(breaker.try_acquire());
+        assert_eq!(breaker.state_name(), "half_open");
+    }
+
+    #[tokio::test]
+    async fn a_successful_probe_closes_the_circuit() {
+        let breaker = breaker(1, 0);
+        fail(&breaker).await;
+
+        breaker.call(async { Ok(()) }).await.unwrap();
+        assert_eq!(breaker.state_name(), "closed");
+    }
+
+    #[tokio::test]
+    async fn a_failed_probe_reopens_the_circuit() {
+        let breaker = breaker(1, 0);
+        fail(&breaker).await;
+
+        fail(&breaker).await;
+        assert_eq!(breaker.state_name(), "open");
+    }
+}
@@ -1,40 +1,136 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
 use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use redis::aio::ConnectionManager;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::config::AppConfig;
+use crate::db::redis::record_backend_error;
+
+/// A text embedding provider.
+pub trait Embedder: Send + Sync {
+    /// Generate an embedding vector for the given text.
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>>>;
+
+    /// Generate embeddings for several texts in one call, in input order.
+    fn embed_batch<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<f32>>>>;
+
+    /// Dimension of the vectors this embedder produces.
+    fn dimension(&self) -> u64;
+
+    /// Load the model ahead of the first request. No-op by default.
+    fn warm_up(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Which embedding provider to use, selected by `EMBED_BACKEND`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbedBackend {
+    Ollama,
+    OpenAi,
+}
+
+impl FromStr for EmbedBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "ollama" => Ok(Self::Ollama),
+            "openai" => Ok(Self::OpenAi),
+            other => anyhow::bail!("Unknown EMBED_BACKEND '{other}' (expected ollama or openai)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenAiConfig {
+    pub api_key: Option<String>,
+    pub base_url: String,
+    pub embed_model: String,
+}
+
+/// Build the embedder selected by the configuration, behind the Redis cache
+/// unless `EMBEDDING_CACHE_ENABLED=false`.
+pub fn from_config(config: &AppConfig, redis: ConnectionManager) -> Arc<dyn Embedder> {
+    let embedder = provider(config);
+    if !config.embedding_cache_enabled {
+        return embedder;
+    }
+    let model = match config.embed_backend {
+        EmbedBackend::Ollama => &config.ollama_embed_model,
+        EmbedBackend::OpenAi => &config.openai.embed_model,
+    };
+    Arc::new(CachedEmbedder::new(
+        embedder,
+        redis,
+        model,
+        config.embedding_cache_ttl_secs,
+    ))
+}
+
+fn provider(config: &AppConfig) -> Arc<dyn Embedder> {
+    match config.embed_backend {
+        EmbedBackend::Ollama => Arc::new(OllamaEmbedder::new(
+            &config.ollama_url,
+            &config.ollama_embed_model,
+            config.embed_dimension.unwrap_or(768),
+            config.ollama_keep_alive.clone(),
+        )),
+        EmbedBackend::OpenAi => Arc::new(OpenAiEmbedder::new(
+            &config.openai.base_url,
+            config.openai.api_key.as_deref().unwrap_or_default(),
+            &config.openai.embed_model,
+            config.embed_dimension,
+        )),
+    }
+}
+
+// ── Ollama ──
 
 /// Embedding service using Ollama's embedding endpoint.
 #[derive(Clone)]
-pub struct EmbeddingService {
+pub struct OllamaEmbedder {
     http: Client,
     base_url: String,
     model: String,
+    dimension: u64,
+    keep_alive: Option<String>,
 }
 
 #[derive(Serialize)]
-struct EmbedRequest<'a> {
+struct OllamaEmbedRequest<'a> {
     model: &'a str,
-    input: &'a str,
+    input: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<&'a str>,
 }
 
 #[derive(Deserialize)]
-struct EmbedResponse {
+struct OllamaEmbedResponse {
     embeddings: Vec<Vec<f32>>,
 }
 
-impl EmbeddingService {
-    pub fn new(base_url: &str, model: &str) -> Self {
+impl OllamaEmbedder {
+    pub fn new(base_url: &str, model: &str, dimension: u64, keep_alive: Option<String>) -> Self {
         Self {
             http: Client::new(),
             base_url: base_url.trim_end_matches('/').to_string(),
             model: model.to_string(),
+            dimension,
+            keep_alive,
         }
     }
 
-    /// Generate an embedding vector for the given text.
-    pub async fn embed(&self, text: &str) -> Result<Vec<f32>> {
-        let req = EmbedRequest {
+    async fn request(&self, input: &[String]) -> Result<Vec<Vec<f32>>> {
+        let req = OllamaEmbedRequest {
             model: &self.model,
-            input: text,
+            input,
+            keep_alive: self.keep_alive.as_deref(),
         };
 
         let resp = self
@@ -46,18 +142,304 @@ impl EmbeddingService {
             .context("Failed to reach Ollama embedding endpoint")?
             .error_for_status()
             .context("Ollama embedding returned error")?
-            .json::<EmbedResponse>()
+            .json::<OllamaEmbedResponse>()
             .await
             .context("Failed to parse embedding response")?;
 
-        resp.embeddings
+        Ok(resp.embeddings)
+    }
+}
+
+impl Embedder for OllamaEmbedder {
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>>> {
+        Box::pin(async move {
+            self.request(&[text.to_string()])
+                .await?
+                .into_iter()
+                .next()
+                .context("No embedding returned")
+        })
+    }
+
+    fn embed_batch<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<f32>>>> {
+        Box::pin(self.request(texts))
+    }
+
+    fn dimension(&self) -> u64 {
+        self.dimension
+    }
+
+    fn warm_up(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.request(&["warm-up".to_string()]).await?;
+            Ok(())
+        })
+    }
+}
+
+// ── OpenAI ──
+
+/// Embedding service using an OpenAI-compatible `/v1/embeddings` endpoint.
+#[derive(Clone)]
+pub struct OpenAiEmbedder {
+    http: Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    /// `EMBED_DIMENSION`, sent as `dimensions` so the model shortens its
+    /// output to match. Unset, nothing is sent: older models reject the field.
+    dimensions: Option<u64>,
+}
+
+/// Output size of OpenAI's embedding models when `dimensions` isn't sent.
+const OPENAI_DEFAULT_DIMENSION: u64 = 1536;
+
+#[derive(Serialize)]
+struct OpenAiEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedResponse {
+    data: Vec<OpenAiEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(base_url: &str, api_key: &str, model: &str, dimensions: Option<u64>) -> Self {
+        Self {
+            http: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            model: model.to_string(),
+            dimensions,
+        }
+    }
+
+    async fn request(&self, input: &[String]) -> Result<Vec<Vec<f32>>> {
+        let req = OpenAiEmbedRequest {
+            model: &self.model,
+            input,
+            dimensions: self.dimensions,
+        };
+
+        let mut resp = self
+            .http
+            .post(format!("{}/v1/embeddings", self.base_url))
+            .bearer_auth(&self.api_key)
+            .json(&req)
+            .send()
+            .await
+            .context("Failed to reach OpenAI embedding endpoint")?
+            .error_for_status()
+            .context("OpenAI embedding returned error")?
+            .json::<OpenAiEmbedResponse>()
+            .await
+            .context("Failed to parse OpenAI embedding response")?;
+
+        resp.data.sort_by_key(|d| d.index);
+        Ok(resp.data.into_iter().map(|d| d.embedding).collect())
+    }
+}
+
+impl Embedder for OpenAiEmbedder {
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>>> {
+        Box::pin(async move {
+            self.request(&[text.to_string()])
+                .await?
+                .into_iter()
+                .next()
+                .context("No embedding returned")
+        })
+    }
+
+    fn embed_batch<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<f32>>>> {
+        Box::pin(self.request(texts))
+    }
+
+    fn dimension(&self) -> u64 {
+        self.dimensions.unwrap_or(OPENAI_DEFAULT_DIMENSION)
+    }
+}
+
+// ── Redis cache ──
+
+/// Caches vectors from another embedder in Redis under
+/// `emb:{model}:{sha256(text)}`, so repeated texts (a message embedded for
+/// both storage and recall, a re-run analysis) skip the model. Cache failures
+/// are logged and fall through to the inner embedder.
+pub struct CachedEmbedder {
+    inner: Arc<dyn Embedder>,
+    redis: ConnectionManager,
+    model: String,
+    ttl_secs: u64,
+}
+
+impl CachedEmbedder {
+    pub fn new(
+        inner: Arc<dyn Embedder>,
+        redis: ConnectionManager,
+        model: &str,
+        ttl_secs: u64,
+    ) -> Self {
+        Self {
+            inner,
+            redis,
+            model: model.to_string(),
+            ttl_secs,
+        }
+    }
+
+    fn key(&self, text: &str) -> String {
+        let digest = Sha256::digest(text.as_bytes());
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        format!("emb:{}:{hex}", self.model)
+    }
+
+    /// Cached vectors for `keys`, `None` for misses. Errors count as misses.
+    async fn lookup(&self, keys: &[String]) -> Vec<Option<Vec<f32>>> {
+        let mut conn = self.redis.clone();
+        let cached: Vec<Option<Vec<u8>>> =
+            match redis::cmd("MGET").arg(keys).query_async(&mut conn).await {
+                Ok(cached) => cached,
+                Err(e) => {
+                    record_backend_error("embedding_cache_get", &e);
+                    return vec![None; keys.len()];
+                }
+            };
+        let dimension = self.inner.dimension() as usize;
+        cached
             .into_iter()
-            .next()
-            .context("No embedding returned")
+            .map(|bytes| bytes.and_then(|b| decode_vector(&b, dimension)))
+            .collect()
+    }
+
+    async fn store(&self, entries: &[(&String, &Vec<f32>)]) {
+        if entries.is_empty() {
+            return;
+        }
+        let mut pipe = redis::pipe();
+        for (key, vector) in entries {
+            pipe.cmd("SET")
+                .arg(*key)
+                .arg(encode_vector(vector))
+                .arg("EX")
+                .arg(self.ttl_secs)
+                .ignore();
+        }
+        let mut conn = self.redis.clone();
+        if let Err(e) = pipe.query_async::<()>(&mut conn).await {
+            record_backend_error("embedding_cache_set", &e);
+        }
+    }
+}
+
+impl Embedder for CachedEmbedder {
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>>> {
+        Box::pin(async move {
+            let key = self.key(text);
+            if let Some(Some(vector)) = self.lookup(std::slice::from_ref(&key)).await.pop() {
+                return Ok(vector);
+            }
+            let vector = self.inner.embed(text).await?;
+            self.store(&[(&key, &vector)]).await;
+            Ok(vector)
+        })
+    }
+
+    fn embed_batch<'a>(&'a self, texts: &'a [String]) -> BoxFuture<'a, Result<Vec<Vec<f32>>>> {
+        Box::pin(async move {
+            if texts.is_empty() {
+                return Ok(Vec::new());
+            }
+            let keys: Vec<String> = texts.iter().map(|t| self.key(t)).collect();
+            let mut vectors = self.lookup(&keys).await;
+
+            let missing: Vec<usize> = (0..texts.len()).filter(|&i| vectors[i].is_none()).collect();
+            if !missing.is_empty() {
+                let inputs: Vec<String> = missing.iter().map(|&i| texts[i].clone()).collect();
+                let embedded = self.inner.embed_batch(&inputs).await?;
+                anyhow::ensure!(
+                    embedded.len() == inputs.len(),
+                    "Expected {} embeddings, got {}",
+                    inputs.len(),
+                    embedded.len()
+                );
+                let fresh: Vec<(&String, &Vec<f32>)> =
+                    missing.iter().map(|&i| &keys[i]).zip(&embedded).collect();
+                self.store(&fresh).await;
+                for (i, vector) in missing.into_iter().zip(embedded) {
+                    vectors[i] = Some(vector);
+                }
+            }
+
+            Ok(vectors.into_iter().flatten().collect())
+        })
+    }
+
+    fn dimension(&self) -> u64 {
+        self.inner.dimension()
+    }
+
+    fn warm_up(&self) -> BoxFuture<'_, Result<()>> {
+        self.inner.warm_up()
+    }
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+/// Decode a cached vector, rejecting one whose size no longer matches.
+fn decode_vector(bytes: &[u8], dimension: usize) -> Option<Vec<f32>> {
+    if bytes.len() != dimension * 4 {
+        return None;
+    }
+    Some(
+        bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn openai_request_sends_configured_dimensions() {
+        let input = ["hello".to_string()];
+        let body = serde_json::to_value(OpenAiEmbedRequest {
+            model: "text-embedding-3-small",
+            input: &input,
+            dimensions: Some(384),
+        })
+        .unwrap();
+        assert_eq!(body["dimensions"], 384);
+
+        let body = serde_json::to_value(OpenAiEmbedRequest {
+            model: "text-embedding-ada-002",
+            input: &input,
+            dimensions: None,
+        })
+        .unwrap();
+        assert!(body.get("dimensions").is_none());
     }
 
-    /// Get the embedding dimension (nomic-embed-text = 768).
-    pub fn dimension(&self) -> u64 {
-        768
+    #[test]
+    fn openai_dimension_defaults_when_unset() {
+        let embedder = OpenAiEmbedder::new("http://localhost", "key", "model", None);
+        assert_eq!(embedder.dimension(), OPENAI_DEFAULT_DIMENSION);
+        let embedder = OpenAiEmbedder::new("http://localhost", "key", "model", Some(384));
+        assert_eq!(embedder.dimension(), 384);
     }
 }
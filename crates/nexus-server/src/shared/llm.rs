@@ -0,0 +1,655 @@
+use std::cell::Cell;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::shared::embeddings::EmbedBackend;
+use crate::shared::mock_llm::MockLlm;
+use crate::shared::ollama::{ChatMessage, OllamaClient};
+
+tokio::task_local! {
+    /// Token usage accumulated by all LLM calls in the current request.
+    static REQUEST_USAGE: Mutex<TokenUsage>;
+
+    /// Set when a JSON response inside [`track_truncation`] was still cut off
+    /// after its retries.
+    static TRUNCATED: Cell<bool>;
+}
+
+/// Token counts and timings reported by the backend for a completion.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct TokenUsage {
+    #[serde(default, rename = "prompt_eval_count")]
+    pub prompt_tokens: u64,
+    #[serde(default, rename = "eval_count")]
+    pub completion_tokens: u64,
+    #[serde(default)]
+    pub prompt_eval_duration: u64,
+    #[serde(default)]
+    pub eval_duration: u64,
+    #[serde(default)]
+    pub total_duration: u64,
+}
+
+impl TokenUsage {
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+
+    fn add(&mut self, other: &TokenUsage) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.prompt_eval_duration += other.prompt_eval_duration;
+        self.eval_duration += other.eval_duration;
+        self.total_duration += other.total_duration;
+    }
+}
+
+/// Generated text plus the usage it cost.
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub text: String,
+    pub usage: TokenUsage,
+    /// Why generation stopped, as reported by the backend: `"length"` when it
+    /// ran out of tokens. `None` if the backend doesn't say.
+    pub done_reason: Option<String>,
+}
+
+/// Sampling parameters for a single completion.
+#[derive(Debug, Clone, Copy)]
+pub struct GenerateParams {
+    /// Constrain the output to valid JSON.
+    pub json: bool,
+    pub temperature: f32,
+    /// Most tokens to generate; `None` uses the configured budget for text or
+    /// JSON output.
+    pub num_predict: Option<i32>,
+    /// Fixed sampling seed. With the same seed, prompt and temperature the
+    /// output repeats, as far as the backend honours the seed.
+    pub seed: Option<i64>,
+}
+
+impl GenerateParams {
+    /// Free-form prose.
+    pub const TEXT: Self = Self {
+        json: false,
+        temperature: 0.7,
+        num_predict: None,
+        seed: None,
+    };
+
+    /// Structured JSON output.
+    pub const JSON: Self = Self {
+        json: true,
+        temperature: 0.3,
+        num_predict: None,
+        seed: None,
+    };
+}
+
+/// Token budgets per kind of output.
+#[derive(Debug, Clone)]
+pub struct NumPredictConfig {
+    /// Prose replies.
+    pub text: i32,
+    /// JSON output.
+    pub json: i32,
+    /// Analysis layers, which each return several arrays of findings.
+    pub analysis: i32,
+    /// Ceiling for retrying a JSON response that ran out of tokens; retries
+    /// double the budget up to this.
+    pub max: i32,
+}
+
+/// A text generation provider.
+pub trait LlmBackend: Send + Sync {
+    /// Single-prompt completion with an optional system prompt.
+    fn generate<'a>(
+        &'a self,
+        prompt: &'a str,
+        system: Option<&'a str>,
+        params: GenerateParams,
+    ) -> BoxFuture<'a, Result<Completion>>;
+
+    /// Multi-turn chat completion.
+    fn chat<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+        params: GenerateParams,
+    ) -> BoxFuture<'a, Result<Completion>>;
+
+    /// Whether the backend is reachable and ready.
+    fn health(&self) -> BoxFuture<'_, Result<bool>>;
+
+    /// Name of the model serving completions.
+    fn model(&self) -> &str;
+
+    /// State of the backend's circuit breaker, if it has one.
+    fn circuit_state(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Load the model ahead of the first request. No-op by default.
+    fn warm_up(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+/// Which LLM provider to use, selected by `LLM_BACKEND`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmBackendKind {
+    Ollama,
+    /// Canned responses from `LLM_FIXTURES_DIR`, for reproducible tests.
+    Mock,
+}
+
+impl FromStr for LlmBackendKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "ollama" => Ok(Self::Ollama),
+            "mock" => Ok(Self::Mock),
+            other => anyhow::bail!("Unknown LLM_BACKEND '{other}' (expected ollama or mock)"),
+        }
+    }
+}
+
+/// Build the configured LLM client.
+pub fn from_config(config: &AppConfig) -> Result<LlmClient> {
+    let backend: Arc<dyn LlmBackend> = match config.llm_backend {
+        LlmBackendKind::Ollama => Arc::new(OllamaClient::new(
+            &config.ollama_url,
+            &config.ollama_model,
+            (config.embed_backend == EmbedBackend::Ollama)
+                .then_some(config.ollama_embed_model.as_str()),
+            config.ollama_keep_alive.clone(),
+            config.llm_io_log.clone(),
+            config.llm_circuit.clone(),
+        )),
+        LlmBackendKind::Mock => Arc::new(MockLlm::load(&config.llm_fixtures_dir)?),
+    };
+    Ok(LlmClient {
+        backend,
+        num_predict: config.llm_num_predict.clone(),
+    })
+}
+
+/// Typed front end over an [`LlmBackend`]: JSON parsing, token budgets and
+/// usage tracking.
+#[derive(Clone)]
+pub struct LlmClient {
+    backend: Arc<dyn LlmBackend>,
+    num_predict: NumPredictConfig,
+}
+
+impl LlmClient {
+    /// Generate a completion with an optional system prompt. Returns raw text.
+    pub async fn generate(&self, prompt: &str, system: Option<&str>) -> Result<String> {
+        Ok(self.generate_completion(prompt, system).await?.text)
+    }
+
+    /// Generate a completion, returning the text with its token usage.
+    pub async fn generate_completion(
+        &self,
+        prompt: &str,
+        system: Option<&str>,
+    ) -> Result<Completion> {
+        let completion = self
+            .backend
+            .generate(prompt, system, self.budgeted(GenerateParams::TEXT))
+            .await?;
+        record_usage(&completion.usage);
+        Ok(completion)
+    }
+
+    /// Generate a completion and parse the response as JSON.
+    pub async fn generate_json<T: serde::de::DeserializeOwned>(
+        &self,
+        prompt: &str,
+        system: Option<&str>,
+    ) -> Result<T> {
+        self.generate_json_with(prompt, system, GenerateParams::JSON)
+            .await
+    }
+
+    /// Generate JSON with the larger budget for analysis layers.
+    pub async fn generate_analysis_json<T: serde::de::DeserializeOwned>(
+        &self,
+        prompt: &str,
+        system: Option<&str>,
+    ) -> Result<T> {
+        let params = GenerateParams {
+            num_predict: Some(self.num_predict.analysis),
+            ..GenerateParams::JSON
+        };
+        self.generate_json_with(prompt, system, params).await
+    }
+
+    /// Generate JSON with explicit sampling parameters. A response cut off
+    /// mid-object is retried with twice the budget, up to the configured
+    /// maximum.
+    pub async fn generate_json_with<T: serde::de::DeserializeOwned>(
+        &self,
+        prompt: &str,
+        system: Option<&str>,
+        params: GenerateParams,
+    ) -> Result<T> {
+        let mut params = self.budgeted(GenerateParams {
+            json: true,
+            ..params
+        });
+        loop {
+            let completion = self.backend.generate(prompt, system, params).await?;
+            record_usage(&completion.usage);
+
+            match self.retry_budget(&completion, params) {
+                Some(larger) => params = larger,
+                None => {
+                    return parse_or_salvage(&completion.text)
+                        .context("Failed to parse JSON from LLM response");
+                }
+            }
+        }
+    }
+
+    /// Multi-turn chat completion.
+    pub async fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+        Ok(self.chat_completion(messages).await?.text)
+    }
+
+    /// Multi-turn chat completion, returning the text with its token usage.
+    pub async fn chat_completion(&self, messages: &[ChatMessage]) -> Result<Completion> {
+        self.chat_completion_with(messages, GenerateParams::TEXT)
+            .await
+    }
+
+    /// Multi-turn chat completion with explicit sampling parameters.
+    pub async fn chat_with(
+        &self,
+        messages: &[ChatMessage],
+        params: GenerateParams,
+    ) -> Result<String> {
+        Ok(self.chat_completion_with(messages, params).await?.text)
+    }
+
+    async fn chat_completion_with(
+        &self,
+        messages: &[ChatMessage],
+        params: GenerateParams,
+    ) -> Result<Completion> {
+        let completion = self.backend.chat(messages, self.budgeted(params)).await?;
+        record_usage(&completion.usage);
+        Ok(completion)
+    }
+
+    /// Multi-turn chat with JSON output parsing.
+    pub async fn chat_json<T: serde::de::DeserializeOwned>(
+        &self,
+        messages: &[ChatMessage],
+    ) -> Result<T> {
+        self.chat_json_with(messages, GenerateParams::JSON).await
+    }
+
+    /// Multi-turn chat with JSON output parsing and explicit sampling
+    /// parameters. Output is constrained to JSON regardless of `params.json`,
+    /// and truncated responses are retried as in [`Self::generate_json_with`].
+    pub async fn chat_json_with<T: serde::de::DeserializeOwned>(
+        &self,
+        messages: &[ChatMessage],
+        params: GenerateParams,
+    ) -> Result<T> {
+        let mut params = self.budgeted(GenerateParams {
+            json: true,
+            ..params
+        });
+        loop {
+            let completion = self.backend.chat(messages, params).await?;
+            record_usage(&completion.usage);
+
+            match self.retry_budget(&completion, params) {
+                Some(larger) => params = larger,
+                None => {
+                    return parse_or_salvage(&completion.text)
+                        .context("Failed to parse JSON from LLM chat response");
+                }
+            }
+        }
+    }
+
+    /// `params` with the configured budget filled in if it has none.
+    fn budgeted(&self, params: GenerateParams) -> GenerateParams {
+        let default = if params.json {
+            self.num_predict.json
+        } else {
+            self.num_predict.text
+        };
+        GenerateParams {
+            num_predict: Some(params.num_predict.unwrap_or(default)),
+            ..params
+        }
+    }
+
+    /// Parameters for another attempt when the backend stopped `completion`
+    /// at its token limit mid-JSON and the budget can still grow. JSON that is
+    /// merely malformed is not retried.
+    fn retry_budget(
+        &self,
+        completion: &Completion,
+        params: GenerateParams,
+    ) -> Option<GenerateParams> {
+        let budget = params.num_predict?;
+        if budget >= self.num_predict.max
+            || completion.done_reason.as_deref() != Some("length")
+            || !is_truncated_json(&completion.text)
+        {
+            return None;
+        }
+        let larger = budget.saturating_mul(2).min(self.num_predict.max);
+        tracing::warn!(
+            budget,
+            larger,
+            "LLM JSON response was cut off, retrying with a larger budget"
+        );
+        Some(GenerateParams {
+            num_predict: Some(larger),
+            ..params
+        })
+    }
+
+    /// Health check: verify the backend is reachable.
+    pub async fn health(&self) -> Result<bool> {
+        self.backend.health().await
+    }
+
+    /// Name of the model serving completions.
+    pub fn model(&self) -> &str {
+        self.backend.model()
+    }
+
+    /// State of the backend's circuit breaker, if it has one.
+    pub fn circuit_state(&self) -> Option<&'static str> {
+        self.backend.circuit_state()
+    }
+
+    /// Load the model so the first request does not pay for a cold start.
+    pub async fn warm_up(&self) -> Result<()> {
+        self.backend.warm_up().await
+    }
+}
+
+/// Parse a JSON response, tolerating what models add despite `format: "json"`:
+/// markdown fences and prose around the object. The strict parse error is
+/// returned if the tolerant path fails too.
+fn parse_json<T: serde::de::DeserializeOwned>(text: &str) -> serde_json::Result<T> {
+    let strict_err = match serde_json::from_str(text) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    let Some(object) = first_json_object(strip_fences(text)) else {
+        return Err(strict_err);
+    };
+    match serde_json::from_str(object) {
+        Ok(value) => {
+            tracing::warn!(
+                error = %strict_err,
+                "Recovered JSON from a malformed LLM response"
+            );
+            Ok(value)
+        }
+        Err(_) => Err(strict_err),
+    }
+}
+
+/// Parse a JSON response. One still cut off after any retries is reported to
+/// an enclosing [`track_truncation`] and, if it can't be parsed as is, closed
+/// after its last complete element and parsed from that.
+fn parse_or_salvage<T: serde::de::DeserializeOwned>(text: &str) -> serde_json::Result<T> {
+    if !is_truncated_json(text) {
+        return parse_json(text);
+    }
+    let _ = TRUNCATED.try_with(|t| t.set(true));
+
+    let err = match parse_json(text) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+    match repair_truncated_json(strip_fences(text)).map(|json| serde_json::from_str(&json)) {
+        Some(Ok(value)) => {
+            tracing::warn!(error = %err, "Salvaged complete entries from a truncated LLM response");
+            Ok(value)
+        }
+        _ => Err(err),
+    }
+}
+
+/// `text` cut after the last nested object or array that closed, with the
+/// containers still open at that point closed. `None` if nothing closed or
+/// the text isn't truncated JSON.
+fn repair_truncated_json(text: &str) -> Option<String> {
+    let body = &text[text.find('{')?..];
+    // Closers for the containers open at each point.
+    let mut open: Vec<char> = Vec::new();
+    let mut cut: Option<(usize, Vec<char>)> = None;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in body.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => open.push('}'),
+            '[' => open.push(']'),
+            '}' | ']' => {
+                if open.pop() != Some(c) || open.is_empty() {
+                    // Mismatched, or the outer object closed: not truncated.
+                    return None;
+                }
+                cut = Some((i + 1, open.clone()));
+            }
+            _ => {}
+        }
+    }
+
+    let (end, open) = cut?;
+    let mut repaired = body[..end].to_string();
+    repaired.extend(open.iter().rev());
+    Some(repaired)
+}
+
+/// Whether `text` opens a JSON object it never closes, as happens when the
+/// model hits its token limit.
+fn is_truncated_json(text: &str) -> bool {
+    let body = strip_fences(text);
+    body.contains('{') && first_json_object(body).is_none()
+}
+
+/// The contents of the first ``` fenced block, or `text` if there is none.
+fn strip_fences(text: &str) -> &str {
+    let Some(start) = text.find("```") else {
+        return text;
+    };
+    let after = &text[start + 3..];
+    // Skip the language tag line, e.g. "json", unless the JSON starts on it.
+    let body = match after.split_once('\n') {
+        Some((tag, rest)) if !tag.contains('{') => rest,
+        _ => after,
+    };
+    body.find("```").map_or(body, |end| &body[..end])
+}
+
+/// The first balanced `{...}` in `text`, skipping braces inside strings.
+fn first_json_object(text: &str) -> Option<&str> {
+    let start = text.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[start..=start + i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Run `fut`, also returning whether any JSON response it received was still
+/// truncated by the token limit after its retries, even if it was salvaged.
+/// A response that came back complete on a retry lost nothing and isn't
+/// reported.
+pub async fn track_truncation<F: Future>(fut: F) -> (F::Output, bool) {
+    TRUNCATED
+        .scope(Cell::new(false), async {
+            let output = fut.await;
+            (output, TRUNCATED.with(Cell::get))
+        })
+        .await
+}
+
+/// Run `fut`, returning its output along with the token usage of every
+/// LLM call made while it ran (on the same task). Calls are still counted
+/// towards any enclosing `track_usage`.
+pub async fn track_usage<F: Future>(fut: F) -> (F::Output, TokenUsage) {
+    let (output, usage) = REQUEST_USAGE
+        .scope(Mutex::new(TokenUsage::default()), async {
+            let output = fut.await;
+            let usage = REQUEST_USAGE.with(|u| *u.lock().unwrap_or_else(|e| e.into_inner()));
+            (output, usage)
+        })
+        .await;
+    record_usage(&usage);
+    (output, usage)
+}
+
+/// Add usage to the current request's total, if it is being tracked.
+fn record_usage(usage: &TokenUsage) {
+    let _ = REQUEST_USAGE.try_with(|u| u.lock().unwrap_or_else(|e| e.into_inner()).add(usage));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Value, json};
+
+    #[test]
+    fn fences_are_stripped() {
+        assert_eq!(strip_fences("```json\n{\"a\": 1}\n```"), "{\"a\": 1}\n");
+        assert_eq!(strip_fences("```{\"a\": 1}```"), "{\"a\": 1}");
+        assert_eq!(strip_fences("{\"a\": 1}"), "{\"a\": 1}");
+    }
+
+    #[test]
+    fn first_object_skips_prose_and_braces_in_strings() {
+        assert_eq!(
+            first_json_object("Sure! {\"a\": {\"b\": \"}\"}} Hope that helps."),
+            Some("{\"a\": {\"b\": \"}\"}}")
+        );
+        assert_eq!(first_json_object("no JSON here"), None);
+        assert_eq!(first_json_object("{\"a\": [1, 2"), None);
+    }
+
+    #[test]
+    fn unclosed_objects_are_truncated() {
+        assert!(is_truncated_json("{\"items\": [1, 2"));
+        assert!(is_truncated_json("```json\n{\"a\": {\"b\": 1}"));
+        assert!(!is_truncated_json("{\"a\": 1}"));
+        assert!(!is_truncated_json("```json\n{\"a\": 1}\n```"));
+        assert!(!is_truncated_json("no JSON here"));
+    }
+
+    #[test]
+    fn truncated_arrays_keep_their_complete_entries() {
+        assert_eq!(
+            repair_truncated_json(r#"{"items": [{"x": 1}, {"x": 2}, {"x": "#).as_deref(),
+            Some(r#"{"items": [{"x": 1}, {"x": 2}]}"#)
+        );
+    }
+
+    #[test]
+    fn truncated_objects_keep_their_complete_members() {
+        assert_eq!(
+            repair_truncated_json(r#"{"a": {"b": 1}, "c": {"d": "#).as_deref(),
+            Some(r#"{"a": {"b": 1}}"#)
+        );
+    }
+
+    #[test]
+    fn unrepairable_text_is_left_alone() {
+        // Nothing closed before the cut.
+        assert_eq!(repair_truncated_json(r#"{"a": "b"#), None);
+        // A brace inside a string doesn't count.
+        assert_eq!(repair_truncated_json(r#"{"a": "}"#), None);
+        // Complete, so not truncated.
+        assert_eq!(repair_truncated_json(r#"{"a": {"b": 1}}"#), None);
+        assert_eq!(repair_truncated_json("no JSON here"), None);
+    }
+
+    #[test]
+    fn fenced_output_with_trailing_prose_parses() {
+        let text = "Here you go:\n```json\n{\"a\": 1}\n```\nLet me know if you need more.";
+        assert_eq!(parse_or_salvage::<Value>(text).unwrap(), json!({"a": 1}));
+        let text = "{\"a\": 1}\nThe object above lists everything.";
+        assert_eq!(parse_or_salvage::<Value>(text).unwrap(), json!({"a": 1}));
+    }
+
+    #[test]
+    fn truncated_output_is_salvaged() {
+        let text = "```json\n{\"items\": [{\"x\": 1}, {\"x\": 2";
+        assert_eq!(
+            parse_or_salvage::<Value>(text).unwrap(),
+            json!({"items": [{"x": 1}]})
+        );
+    }
+
+    #[test]
+    fn unrecoverable_output_is_an_error() {
+        assert!(parse_or_salvage::<Value>("I can't help with that.").is_err());
+        assert!(parse_or_salvage::<Value>(r#"{"a": "unterminated"#).is_err());
+    }
+
+    #[tokio::test]
+    async fn salvaged_output_is_reported_as_truncated() {
+        let (out, truncated) =
+            track_truncation(async { parse_or_salvage::<Value>(r#"{"a": [1], "b": [2"#) }).await;
+        assert_eq!(out.unwrap(), json!({"a": [1]}));
+        assert!(truncated);
+    }
+
+    #[tokio::test]
+    async fn complete_output_is_not_reported_as_truncated() {
+        let (out, truncated) =
+            track_truncation(async { parse_or_salvage::<Value>(r#"{"a": [1]}"#) }).await;
+        assert_eq!(out.unwrap(), json!({"a": [1]}));
+        assert!(!truncated);
+    }
+}
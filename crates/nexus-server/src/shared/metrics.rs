@@ -0,0 +1,53 @@
+use std::sync::LazyLock;
+
+use prometheus::{Encoder, IntCounter, Registry, TextEncoder};
+
+/// Process-wide registry served at `/metrics`.
+static REGISTRY: LazyLock<Registry> = LazyLock::new(|| {
+    Registry::new_custom(Some("nexus".into()), None).expect("valid metric prefix")
+});
+
+/// Redis commands that failed because the backend was unreachable or errored,
+/// as opposed to a plain cache miss.
+pub static REDIS_BACKEND_ERRORS: LazyLock<IntCounter> = LazyLock::new(|| {
+    register(IntCounter::new(
+        "redis_backend_errors_total",
+        "Redis commands that failed because the backend was unreachable or errored",
+    ))
+});
+
+fn register<M>(metric: prometheus::Result<M>) -> M
+where
+    M: prometheus::core::Collector + Clone + 'static,
+{
+    let metric = metric.expect("valid metric definition");
+    REGISTRY
+        .register(Box::new(metric.clone()))
+        .expect("metric registered once");
+    metric
+}
+
+/// Every registered metric in the Prometheus text exposition format.
+pub fn render() -> String {
+    // Touch each metric so it is exported from startup, not only once it moves.
+    LazyLock::force(&REDIS_BACKEND_ERRORS);
+
+    let mut out = Vec::new();
+    if let Err(e) = TextEncoder::new().encode(&REGISTRY.gather(), &mut out) {
+        tracing::error!("Failed to encode metrics: {e}");
+    }
+    String::from_utf8(out).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redis_backend_errors_are_exported() {
+        REDIS_BACKEND_ERRORS.inc();
+        let text = render();
+        assert!(text.contains("# TYPE nexus_redis_backend_errors_total counter"));
+        assert!(text.contains("nexus_redis_backend_errors_total "));
+    }
+}
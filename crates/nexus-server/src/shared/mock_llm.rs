@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use sha2::{Digest, Sha256};
+
+use crate::shared::llm::{Completion, GenerateParams, LlmBackend, TokenUsage};
+use crate::shared::ollama::ChatMessage;
+
+/// Deterministic LLM backend that replays canned responses.
+///
+/// Each response lives in `<dir>/<key>.txt` (or `.json`), where the key is the
+/// SHA-256 of the request's prompt, system prompt and output format, so keys
+/// stay the same across toolchains. A request
+/// with no fixture fails with an error naming the file it expected, so new
+/// fixtures can be recorded as tests are written.
+pub struct MockLlm {
+    dir: PathBuf,
+    fixtures: HashMap<String, String>,
+}
+
+impl MockLlm {
+    /// Load every fixture in `dir`.
+    pub fn load(dir: &str) -> Result<Self> {
+        let dir = PathBuf::from(dir);
+        let mut fixtures = HashMap::new();
+
+        let entries = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read LLM fixtures from {}", dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            let is_fixture = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("txt" | "json")
+            );
+            let Some(key) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if is_fixture {
+                let body = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+                fixtures.insert(key.to_string(), body);
+            }
+        }
+
+        tracing::info!(
+            count = fixtures.len(),
+            dir = %dir.display(),
+            "Loaded mock LLM fixtures"
+        );
+        Ok(Self { dir, fixtures })
+    }
+
+    fn respond(&self, key: String) -> Result<Completion> {
+        let text = self.fixtures.get(&key).cloned().with_context(|| {
+            format!(
+                "No mock LLM fixture for this request (expected {})",
+                fixture_path(&self.dir, &key).display()
+            )
+        })?;
+
+        Ok(Completion {
+            text,
+            usage: TokenUsage::default(),
+            done_reason: None,
+        })
+    }
+}
+
+/// Fixture key for a single-prompt completion.
+pub fn generate_key(prompt: &str, system: Option<&str>, params: GenerateParams) -> String {
+    let mut hasher = Sha256::new();
+    hash_field(&mut hasher, "generate");
+    hash_field(&mut hasher, prompt);
+    match system {
+        Some(system) => {
+            hash_field(&mut hasher, "system");
+            hash_field(&mut hasher, system);
+        }
+        None => hash_field(&mut hasher, "no-system"),
+    }
+    hash_field(&mut hasher, format_name(params));
+    hex(hasher)
+}
+
+/// Fixture key for a chat completion.
+pub fn chat_key(messages: &[ChatMessage], params: GenerateParams) -> String {
+    let mut hasher = Sha256::new();
+    hash_field(&mut hasher, "chat");
+    for m in messages {
+        hash_field(&mut hasher, &m.role);
+        hash_field(&mut hasher, &m.content);
+    }
+    hash_field(&mut hasher, format_name(params));
+    hex(hasher)
+}
+
+/// Hash `field` with its length first, so adjacent fields can't run together.
+fn hash_field(hasher: &mut Sha256, field: &str) {
+    hasher.update((field.len() as u64).to_le_bytes());
+    hasher.update(field.as_bytes());
+}
+
+fn format_name(params: GenerateParams) -> &'static str {
+    if params.json { "json" } else { "text" }
+}
+
+fn hex(hasher: Sha256) -> String {
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn fixture_path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{key}.txt"))
+}
+
+impl LlmBackend for MockLlm {
+    fn generate<'a>(
+        &'a self,
+        prompt: &'a str,
+        system: Option<&'a str>,
+        params: GenerateParams,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move { self.respond(generate_key(prompt, system, params)) })
+    }
+
+    fn chat<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+        params: GenerateParams,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(async move { self.respond(chat_key(messages, params)) })
+    }
+
+    fn health(&self) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(async { Ok(true) })
+    }
+
+    fn model(&self) -> &str {
+        "mock"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_stable_sha256() {
+        assert_eq!(
+            generate_key("Say hi.", None, GenerateParams::TEXT),
+            "7f1fd2f73454f76ddd3a0e4f1259abfcb0745bf0e52400284f133c72fac5e881"
+        );
+        let messages = [ChatMessage {
+            role: "user".into(),
+            content: "Say hi.".into(),
+        }];
+        assert_eq!(
+            chat_key(&messages, GenerateParams::JSON),
+            "12ede38b6ab168ccc457006ef11f80618203e3a5ce2b85a8971003e84287b0d2"
+        );
+    }
+
+    #[test]
+    fn keys_separate_fields() {
+        assert_ne!(
+            generate_key("ab", Some("c"), GenerateParams::TEXT),
+            generate_key("a", Some("bc"), GenerateParams::TEXT)
+        );
+        assert_ne!(
+            generate_key("a", None, GenerateParams::TEXT),
+            generate_key("a", None, GenerateParams::JSON)
+        );
+    }
+
+    #[test]
+    fn replays_shipped_fixtures() {
+        let llm = MockLlm::load(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/llm")).unwrap();
+        let completion = llm
+            .respond(generate_key("Say hi.", None, GenerateParams::TEXT))
+            .unwrap();
+        assert_eq!(completion.text.trim(), "Hi.");
+        assert!(llm.respond("missing".into()).is_err());
+    }
+}
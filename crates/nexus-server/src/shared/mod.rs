@@ -1,2 +1,11 @@
+pub mod chat_engine;
+pub mod circuit_breaker;
 pub mod embeddings;
+pub mod llm;
+pub mod metrics;
+pub mod mock_llm;
+pub mod moderation;
 pub mod ollama;
+pub mod redaction;
+pub mod text_util;
+pub mod tokens;
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Input moderation settings. Off unless `ENABLE_MODERATION` is set.
+#[derive(Debug, Clone, Default)]
+pub struct ModerationConfig {
+    pub enabled: bool,
+    /// OpenAI-compatible moderation endpoint. Without one, or if it fails,
+    /// the local filter is used.
+    pub url: Option<String>,
+    pub api_key: Option<String>,
+    /// Longest wait for the endpoint before falling back to the local filter.
+    pub timeout_secs: u64,
+    /// Words and phrases rejected by the local filter.
+    pub blocked_terms: Vec<String>,
+}
+
+/// Outcome of a moderation check.
+#[derive(Debug, Clone, Default)]
+pub struct ModerationResult {
+    pub flagged: bool,
+    /// Why the text was flagged, e.g. a provider category or "blocked_term".
+    pub categories: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ModerationRequest<'a> {
+    input: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ModerationResponse {
+    results: Vec<ProviderResult>,
+}
+
+#[derive(Deserialize)]
+struct ProviderResult {
+    flagged: bool,
+    #[serde(default)]
+    categories: HashMap<String, bool>,
+}
+
+/// Checks user text before it is stored or sent to the LLM.
+#[derive(Clone)]
+pub struct Moderator {
+    http: Client,
+    config: ModerationConfig,
+    /// `blocked_terms`, normalized the same way as checked text.
+    blocked: Vec<String>,
+}
+
+impl Moderator {
+    pub fn new(config: ModerationConfig) -> Self {
+        if config.enabled && config.url.is_none() && config.blocked_terms.is_empty() {
+            tracing::warn!(
+                "ENABLE_MODERATION is set but neither MODERATION_URL nor MODERATION_BLOCKLIST is; nothing will be flagged"
+            );
+        }
+        let blocked = config
+            .blocked_terms
+            .iter()
+            .map(|t| normalize(t))
+            .filter(|t| !t.is_empty())
+            .collect();
+        let http = Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .expect("Failed to build HTTP client");
+        Self {
+            http,
+            config,
+            blocked,
+        }
+    }
+
+    /// Check `text`. Always passes when moderation is disabled.
+    pub async fn check(&self, text: &str) -> ModerationResult {
+        if !self.config.enabled {
+            return ModerationResult::default();
+        }
+
+        if let Some(url) = &self.config.url {
+            match self.check_remote(url, text).await {
+                Ok(result) => return result,
+                Err(e) => tracing::warn!("Moderation endpoint failed, using local filter: {e:#}"),
+            }
+        }
+
+        self.check_local(text)
+    }
+
+    async fn check_remote(&self, url: &str, text: &str) -> Result<ModerationResult> {
+        let mut req = self.http.post(url).json(&ModerationRequest { input: text });
+        if let Some(key) = &self.config.api_key {
+            req = req.bearer_auth(key);
+        }
+
+        let resp = req
+            .send()
+            .await
+            .context("Failed to reach moderation endpoint")?
+            .error_for_status()
+            .context("Moderation endpoint returned error")?
+            .json::<ModerationResponse>()
+            .await
+            .context("Failed to parse moderation response")?;
+
+        let mut result = ModerationResult::default();
+        for r in resp.results {
+            result.flagged |= r.flagged;
+            result.categories.extend(
+                r.categories
+                    .into_iter()
+                    .filter_map(|(name, hit)| hit.then_some(name)),
+            );
+        }
+        Ok(result)
+    }
+
+    /// Whole-word match against the blocklist, after undoing common
+    /// obfuscations (case, leetspeak, punctuation between letters).
+    fn check_local(&self, text: &str) -> ModerationResult {
+        let normalized = format!(" {} ", normalize(text));
+        let flagged = self
+            .blocked
+            .iter()
+            .any(|term| normalized.contains(&format!(" {term} ")));
+
+        ModerationResult {
+            flagged,
+            categories: if flagged {
+                vec!["blocked_term".into()]
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+/// Lowercase, map leetspeak digits and symbols to letters, drop punctuation
+/// inside words ("b.a.d" → "bad") and collapse everything else to single spaces.
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for word in text.split_whitespace() {
+        let word: String = word
+            .chars()
+            .map(|c| match c {
+                '0' => 'o',
+                '1' => 'i',
+                '3' => 'e',
+                '4' | '@' => 'a',
+                '5' | '$' => 's',
+                '7' => 't',
+                c => c,
+            })
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect();
+        if !word.is_empty() {
+            if !out.is_empty() {
+                out.push(' ');
+            }
+            out.push_str(&word);
+        }
+    }
+    out
+}
@@ -1,6 +1,11 @@
 use anyhow::{Context, Result};
+use futures::future::BoxFuture;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::shared::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
+use crate::shared::llm::{Completion, GenerateParams, LlmBackend, TokenUsage};
 
 /// Client for the Ollama HTTP API.
 #[derive(Clone)]
@@ -8,6 +13,42 @@ pub struct OllamaClient {
     http: Client,
     base_url: String,
     model: String,
+    /// Embedding model served by the same host, checked alongside `model`.
+    embed_model: Option<String>,
+    /// How long Ollama keeps the model loaded after a call, e.g. "30m".
+    keep_alive: Option<String>,
+    io_log: LlmIoLogConfig,
+    /// Fails generate and chat calls fast while Ollama keeps failing.
+    breaker: Arc<CircuitBreaker>,
+}
+
+/// Debug logging of raw prompts and responses. Prompts carry user text, which
+/// may be personal data, so this is off unless `LOG_LLM_IO` is set.
+#[derive(Debug, Clone, Default)]
+pub struct LlmIoLogConfig {
+    pub enabled: bool,
+    /// Longest prompt or response logged, in characters; the rest is elided.
+    pub max_chars: usize,
+    /// Replace every logged prompt, message and response with a length
+    /// placeholder. Not just user turns: system prompts carry the user's
+    /// memories and beliefs, and responses quote them.
+    pub redact: bool,
+}
+
+/// A configured model that the Ollama host hasn't pulled.
+#[derive(Debug, thiserror::Error)]
+#[error("model {0} not found on Ollama host; run `ollama pull {0}`")]
+pub struct ModelNotFound(pub String);
+
+#[derive(Deserialize)]
+struct TagsResponse {
+    #[serde(default)]
+    models: Vec<TagEntry>,
+}
+
+#[derive(Deserialize)]
+struct TagEntry {
+    name: String,
 }
 
 #[derive(Serialize)]
@@ -18,17 +59,36 @@ struct GenerateRequest<'a> {
     stream: bool,
     format: Option<&'a str>,
     options: Option<GenerateOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<&'a str>,
 }
 
 #[derive(Serialize)]
 struct GenerateOptions {
     temperature: f32,
-    num_predict: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<i64>,
+}
+
+impl From<GenerateParams> for GenerateOptions {
+    fn from(params: GenerateParams) -> Self {
+        Self {
+            temperature: params.temperature,
+            num_predict: params.num_predict,
+            seed: params.seed,
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct GenerateResponse {
     response: String,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(flatten)]
+    usage: TokenUsage,
 }
 
 #[derive(Serialize)]
@@ -38,6 +98,8 @@ struct ChatRequest<'a> {
     stream: bool,
     format: Option<&'a str>,
     options: Option<GenerateOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<&'a str>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -49,67 +111,86 @@ pub struct ChatMessage {
 #[derive(Deserialize)]
 struct ChatResponse {
     message: ChatMessage,
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(flatten)]
+    usage: TokenUsage,
 }
 
 impl OllamaClient {
-    pub fn new(base_url: &str, model: &str) -> Self {
+    pub fn new(
+        base_url: &str,
+        model: &str,
+        embed_model: Option<&str>,
+        keep_alive: Option<String>,
+        io_log: LlmIoLogConfig,
+        circuit: CircuitBreakerConfig,
+    ) -> Self {
         let http = Client::builder()
             .timeout(std::time::Duration::from_secs(300))
             .build()
             .expect("Failed to build HTTP client");
+        if io_log.enabled {
+            tracing::warn!(
+                redact = io_log.redact,
+                "LOG_LLM_IO is enabled: prompts and responses will be logged at debug level"
+            );
+        }
         Self {
             http,
             base_url: base_url.trim_end_matches('/').to_string(),
             model: model.to_string(),
+            embed_model: embed_model.map(str::to_string),
+            keep_alive,
+            io_log,
+            breaker: Arc::new(CircuitBreaker::new(circuit)),
         }
     }
 
-    /// Generate a completion with an optional system prompt. Returns raw text.
-    pub async fn generate(&self, prompt: &str, system: Option<&str>) -> Result<String> {
-        let req = GenerateRequest {
-            model: &self.model,
-            prompt,
-            system,
-            stream: false,
-            format: None,
-            options: Some(GenerateOptions {
-                temperature: 0.7,
-                num_predict: 2048,
-            }),
-        };
-
-        let resp = self
-            .http
-            .post(format!("{}/api/generate", self.base_url))
-            .json(&req)
-            .send()
-            .await
-            .context("Failed to reach Ollama")?
-            .error_for_status()
-            .context("Ollama returned error")?
-            .json::<GenerateResponse>()
-            .await
-            .context("Failed to parse Ollama response")?;
+    /// Clip `text` to the configured size for logging.
+    fn clip(&self, text: &str) -> String {
+        let max = self.io_log.max_chars;
+        match text.char_indices().nth(max) {
+            Some((cut, _)) => {
+                let elided = text[cut..].chars().count();
+                format!("{}… [{elided} more chars]", &text[..cut])
+            }
+            None => text.to_string(),
+        }
+    }
 
-        Ok(resp.response)
+    /// Prepare prompt or response text for logging.
+    fn logged(&self, text: &str) -> String {
+        if self.io_log.redact {
+            format!("[redacted {} chars]", text.chars().count())
+        } else {
+            self.clip(text)
+        }
     }
 
-    /// Generate a completion and parse the response as JSON.
-    pub async fn generate_json<T: serde::de::DeserializeOwned>(
+    async fn generate_inner(
         &self,
         prompt: &str,
         system: Option<&str>,
-    ) -> Result<T> {
+        params: GenerateParams,
+    ) -> Result<Completion> {
+        if self.io_log.enabled {
+            tracing::debug!(
+                model = %self.model,
+                system = %system.map(|s| self.logged(s)).unwrap_or_default(),
+                prompt = %self.logged(prompt),
+                "LLM generate request"
+            );
+        }
+
         let req = GenerateRequest {
             model: &self.model,
             prompt,
             system,
             stream: false,
-            format: Some("json"),
-            options: Some(GenerateOptions {
-                temperature: 0.3,
-                num_predict: 4096,
-            }),
+            format: params.json.then_some("json"),
+            options: Some(params.into()),
+            keep_alive: self.keep_alive.as_deref(),
         };
 
         let resp = self
@@ -125,23 +206,45 @@ impl OllamaClient {
             .await
             .context("Failed to parse Ollama response")?;
 
-        let parsed: T = serde_json::from_str(&resp.response)
-            .context("Failed to parse JSON from LLM response")?;
+        if self.io_log.enabled {
+            tracing::debug!(
+                model = %self.model,
+                response = %self.logged(&resp.response),
+                "LLM generate response"
+            );
+        }
 
-        Ok(parsed)
+        Ok(Completion {
+            text: resp.response,
+            usage: resp.usage,
+            done_reason: resp.done_reason,
+        })
     }
 
-    /// Multi-turn chat completion.
-    pub async fn chat(&self, messages: &[ChatMessage]) -> Result<String> {
+    async fn chat_inner(
+        &self,
+        messages: &[ChatMessage],
+        params: GenerateParams,
+    ) -> Result<Completion> {
+        if self.io_log.enabled {
+            let transcript: Vec<String> = messages
+                .iter()
+                .map(|m| format!("{}: {}", m.role, self.logged(&m.content)))
+                .collect();
+            tracing::debug!(
+                model = %self.model,
+                messages = %transcript.join("\n"),
+                "LLM chat request"
+            );
+        }
+
         let req = ChatRequest {
             model: &self.model,
             messages,
             stream: false,
-            format: None,
-            options: Some(GenerateOptions {
-                temperature: 0.7,
-                num_predict: 2048,
-            }),
+            format: params.json.then_some("json"),
+            options: Some(params.into()),
+            keep_alive: self.keep_alive.as_deref(),
         };
 
         let resp = self
@@ -157,52 +260,116 @@ impl OllamaClient {
             .await
             .context("Failed to parse Ollama chat response")?;
 
-        Ok(resp.message.content)
+        if self.io_log.enabled {
+            tracing::debug!(
+                model = %self.model,
+                response = %self.logged(&resp.message.content),
+                "LLM chat response"
+            );
+        }
+
+        Ok(Completion {
+            text: resp.message.content,
+            usage: resp.usage,
+            done_reason: resp.done_reason,
+        })
     }
 
-    /// Multi-turn chat with JSON output parsing.
-    pub async fn chat_json<T: serde::de::DeserializeOwned>(
-        &self,
-        messages: &[ChatMessage],
-    ) -> Result<T> {
-        let req = ChatRequest {
+    /// A generate call with an empty prompt loads the model without producing output.
+    async fn warm_up_inner(&self) -> Result<()> {
+        let req = GenerateRequest {
             model: &self.model,
-            messages,
+            prompt: "",
+            system: None,
             stream: false,
-            format: Some("json"),
-            options: Some(GenerateOptions {
-                temperature: 0.3,
-                num_predict: 4096,
-            }),
+            format: None,
+            options: None,
+            keep_alive: self.keep_alive.as_deref(),
         };
 
-        let resp = self
-            .http
-            .post(format!("{}/api/chat", self.base_url))
+        self.http
+            .post(format!("{}/api/generate", self.base_url))
             .json(&req)
             .send()
             .await
             .context("Failed to reach Ollama")?
             .error_for_status()
-            .context("Ollama returned error")?
-            .json::<ChatResponse>()
-            .await
-            .context("Failed to parse Ollama chat response")?;
-
-        let parsed: T = serde_json::from_str(&resp.message.content)
-            .context("Failed to parse JSON from LLM chat response")?;
-
-        Ok(parsed)
+            .context("Ollama returned error")?;
+        Ok(())
     }
 
-    /// Health check: verify Ollama is reachable and the model is available.
-    pub async fn health(&self) -> Result<bool> {
+    /// Health check: verify Ollama is reachable and the chat and embedding
+    /// models are pulled. A missing model fails with [`ModelNotFound`].
+    async fn health_inner(&self) -> Result<bool> {
         let resp = self
             .http
             .get(format!("{}/api/tags", self.base_url))
             .send()
             .await?;
+        if !resp.status().is_success() {
+            return Ok(false);
+        }
+
+        let tags: TagsResponse = resp
+            .json()
+            .await
+            .context("Failed to parse Ollama tag list")?;
+        let required = std::iter::once(&self.model).chain(&self.embed_model);
+        for model in required {
+            if !tags.models.iter().any(|tag| same_model(&tag.name, model)) {
+                return Err(ModelNotFound(model.clone()).into());
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Whether tag `name` is `model`. Ollama lists untagged models as `:latest`.
+fn same_model(name: &str, model: &str) -> bool {
+    let with_latest = |m: &str| {
+        if m.contains(':') {
+            m.to_string()
+        } else {
+            format!("{m}:latest")
+        }
+    };
+    with_latest(name) == with_latest(model)
+}
+
+impl LlmBackend for OllamaClient {
+    fn generate<'a>(
+        &'a self,
+        prompt: &'a str,
+        system: Option<&'a str>,
+        params: GenerateParams,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(
+            self.breaker
+                .call(self.generate_inner(prompt, system, params)),
+        )
+    }
+
+    fn chat<'a>(
+        &'a self,
+        messages: &'a [ChatMessage],
+        params: GenerateParams,
+    ) -> BoxFuture<'a, Result<Completion>> {
+        Box::pin(self.breaker.call(self.chat_inner(messages, params)))
+    }
+
+    fn health(&self) -> BoxFuture<'_, Result<bool>> {
+        Box::pin(self.health_inner())
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn circuit_state(&self) -> Option<&'static str> {
+        Some(self.breaker.state_name())
+    }
 
-        Ok(resp.status().is_success())
+    fn warm_up(&self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(self.warm_up_inner())
     }
 }
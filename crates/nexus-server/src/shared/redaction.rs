@@ -0,0 +1,130 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::borrow::Cow;
+
+/// Redaction of stored text. Off unless `ENABLE_REDACTION` is set.
+#[derive(Debug, Clone, Default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// Words and phrases replaced with `[REDACTED]`, matched case-insensitively
+    /// as whole words.
+    pub terms: Vec<String>,
+    /// Regular expressions whose matches are replaced with `[REDACTED]`.
+    pub patterns: Vec<String>,
+    /// Also replace email addresses and phone numbers.
+    pub detect_pii: bool,
+}
+
+const REDACTED: &str = "[REDACTED]";
+const EMAIL: &str = "[EMAIL]";
+const PHONE: &str = "[PHONE]";
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+/// `+44 20 7946 0958`, `(555) 123-4567`, `555.123.4567` and the like; bare
+/// digit runs such as dates and amounts are left alone.
+const PHONE_PATTERN: &str =
+    r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{2,4}\)|\b\d{2,4})[\s.-]\d{3,4}[\s.-]\d{3,4}\b";
+
+/// Replaces sensitive spans with placeholders before text is persisted to
+/// Postgres or Qdrant. Only the stored copy is redacted: the engines still
+/// see the original message.
+#[derive(Clone)]
+pub struct Redactor {
+    rules: Vec<(Regex, &'static str)>,
+}
+
+impl Redactor {
+    pub fn new(config: &RedactionConfig) -> Result<Self> {
+        if !config.enabled {
+            return Ok(Self { rules: Vec::new() });
+        }
+
+        let mut rules = Vec::new();
+        let terms: Vec<String> = config
+            .terms
+            .iter()
+            .map(|t| t.trim())
+            .filter(|t| !t.is_empty())
+            .map(regex::escape)
+            .collect();
+        if !terms.is_empty() {
+            let pattern = format!(r"(?i)\b(?:{})\b", terms.join("|"));
+            rules.push((Regex::new(&pattern)?, REDACTED));
+        }
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid redaction pattern '{pattern}'"))?;
+            rules.push((regex, REDACTED));
+        }
+        if config.detect_pii {
+            rules.push((Regex::new(EMAIL_PATTERN)?, EMAIL));
+            rules.push((Regex::new(PHONE_PATTERN)?, PHONE));
+        }
+
+        if rules.is_empty() {
+            tracing::warn!(
+                "ENABLE_REDACTION is set but no terms, patterns or PII detection are configured; nothing will be redacted"
+            );
+        }
+        Ok(Self { rules })
+    }
+
+    /// `text` with every match replaced by its placeholder. Borrowed when
+    /// nothing matched.
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut text = Cow::Borrowed(text);
+        for (regex, placeholder) in &self.rules {
+            if let Cow::Owned(replaced) = regex.replace_all(&text, *placeholder) {
+                text = Cow::Owned(replaced);
+            }
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor(terms: &[&str], detect_pii: bool) -> Redactor {
+        Redactor::new(&RedactionConfig {
+            enabled: true,
+            terms: terms.iter().map(|t| t.to_string()).collect(),
+            patterns: Vec::new(),
+            detect_pii,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn replaces_terms_as_whole_words() {
+        let redactor = redactor(&["Falcon"], false);
+        assert_eq!(
+            redactor.redact("falcon ships, Falconry doesn't"),
+            "[REDACTED] ships, Falconry doesn't"
+        );
+    }
+
+    #[test]
+    fn replaces_emails_and_phone_numbers() {
+        let redactor = redactor(&[], true);
+        assert_eq!(
+            redactor.redact("Mail jo@example.com or call (555) 123-4567 by 2024-06-01"),
+            "Mail [EMAIL] or call [PHONE] by 2024-06-01"
+        );
+    }
+
+    #[test]
+    fn disabled_leaves_text_alone() {
+        let redactor = Redactor::new(&RedactionConfig {
+            terms: vec!["Falcon".into()],
+            detect_pii: true,
+            ..RedactionConfig::default()
+        })
+        .unwrap();
+        assert!(matches!(
+            redactor.redact("Falcon, jo@example.com"),
+            Cow::Borrowed(_)
+        ));
+    }
+}
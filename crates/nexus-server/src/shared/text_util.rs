@@ -0,0 +1,329 @@
+use std::ops::Range;
+
+/// Abbreviations (lowercase, without the final period) that never end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "rev", "gen", "col", "capt", "lt",
+    "sgt", "gov", "sen", "rep", "pres", "vs", "e.g", "i.e", "cf", "al", "approx", "ca", "fig",
+    "figs", "vol", "pp", "ed", "eds", "dept", "est", "inc", "ltd", "co", "corp", "jan", "feb",
+    "mar", "apr", "jun", "jul", "aug", "sep", "sept", "oct", "nov", "dec", "a.m", "p.m",
+];
+
+/// Number of words in `text`: whitespace-separated tokens containing at least
+/// one letter or digit, so stray punctuation doesn't count.
+pub fn word_count(text: &str) -> usize {
+    text.split_whitespace()
+        .filter(|token| token.chars().any(char::is_alphanumeric))
+        .count()
+}
+
+/// Whether `text` has enough words to be worth sending to the LLM. At least
+/// one word is always required.
+pub fn has_min_words(text: &str, min_words: usize) -> bool {
+    word_count(text) >= min_words.max(1)
+}
+
+/// Split text into trimmed, non-empty sentences.
+pub fn split_sentences(text: &str) -> Vec<String> {
+    sentence_spans(text)
+        .into_iter()
+        .map(|r| text[r].to_string())
+        .collect()
+}
+
+/// Convert a byte range of `text` into character offsets.
+pub fn char_range(text: &str, bytes: Range<usize>) -> Range<usize> {
+    let start = text[..bytes.start].chars().count();
+    start..start + text[bytes].chars().count()
+}
+
+/// Character offsets of `needle` in `text`, skipping occurrences already in
+/// `claimed` so that a sentence quoted twice maps to both of its places. The
+/// chosen range is added to `claimed`; once every occurrence is taken, the
+/// first is returned again. An exact match is preferred; failing that, the
+/// match ignores ASCII case, since models often recapitalise a quoted sentence.
+pub fn locate(text: &str, needle: &str, claimed: &mut Vec<Range<usize>>) -> Option<Range<usize>> {
+    let needle = needle.trim();
+    if needle.is_empty() {
+        return None;
+    }
+    let mut found: Vec<Range<usize>> = text
+        .match_indices(needle)
+        .map(|(start, _)| char_range(text, start..start + needle.len()))
+        .collect();
+    if found.is_empty() {
+        let lower = text.to_ascii_lowercase();
+        found = lower
+            .match_indices(&needle.to_ascii_lowercase())
+            .map(|(start, _)| char_range(text, start..start + needle.len()))
+            .collect();
+    }
+    let range = found
+        .iter()
+        .find(|range| !claimed.contains(range))
+        .or(found.first())?
+        .clone();
+    claimed.push(range.clone());
+    Some(range)
+}
+
+/// Split `text` into byte ranges of at most `max_chars` characters, breaking
+/// between sentences. A sentence longer than `max_chars` is split at the last
+/// whitespace that fits, or mid-word if there is none.
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<Range<usize>> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+
+    for span in sentence_spans(text) {
+        if let Some(range) = current.clone() {
+            if text[range.start..span.end].chars().count() <= max_chars {
+                current = Some(range.start..span.end);
+                continue;
+            }
+            chunks.push(range);
+        }
+
+        let mut start = span.start;
+        while text[start..span.end].chars().count() > max_chars {
+            let rest = &text[start..span.end];
+            let limit = rest
+                .char_indices()
+                .nth(max_chars)
+                .map_or(rest.len(), |(idx, _)| idx);
+            let cut = rest[..limit]
+                .rfind(char::is_whitespace)
+                .filter(|&idx| idx > 0)
+                .unwrap_or(limit);
+            chunks.push(start..start + rest[..cut].trim_end().len());
+            let after = &rest[cut..];
+            start += cut + (after.len() - after.trim_start().len());
+        }
+        current = (start < span.end).then_some(start..span.end);
+    }
+
+    if let Some(range) = current {
+        chunks.push(range);
+    }
+    chunks
+}
+
+/// Byte ranges of each sentence in `text`, trimmed of surrounding whitespace.
+///
+/// A sentence ends at a run of `.`, `!` or `?` (plus any closing quotes or
+/// brackets) followed by whitespace or end of text. A period does not end a
+/// sentence when it follows a known abbreviation ("Dr.", "e.g.") or an
+/// initialism ("U.S.A.", "J."), or when the next word starts in lowercase.
+/// Periods inside tokens ("3.14", "U.S.A") are never boundaries.
+pub fn sentence_spans(text: &str) -> Vec<Range<usize>> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        if !is_terminator(chars[i].1) {
+            i += 1;
+            continue;
+        }
+
+        let term_start = i;
+        while i < chars.len() && is_terminator(chars[i].1) {
+            i += 1;
+        }
+        while i < chars.len() && is_closer(chars[i].1) {
+            i += 1;
+        }
+
+        // Terminators inside a token (decimals, dotted initialisms) are not boundaries.
+        if i < chars.len() && !chars[i].1.is_whitespace() {
+            continue;
+        }
+
+        let only_periods = chars[term_start..i]
+            .iter()
+            .all(|&(_, c)| c == '.' || is_closer(c));
+        if only_periods {
+            let word = preceding_word(text, chars[term_start].0);
+            let next = chars[i..].iter().find(|(_, c)| !c.is_whitespace());
+            let next_is_lower = next.is_some_and(|&(_, c)| c.is_lowercase());
+            if is_abbreviation(word) || next_is_lower {
+                continue;
+            }
+        }
+
+        let end = chars.get(i).map_or(text.len(), |&(idx, _)| idx);
+        push_trimmed(text, start..end, &mut spans);
+        start = end;
+    }
+
+    push_trimmed(text, start..text.len(), &mut spans);
+    spans
+}
+
+fn is_terminator(c: char) -> bool {
+    matches!(c, '.' | '!' | '?')
+}
+
+fn is_closer(c: char) -> bool {
+    matches!(c, '"' | '\'' | ')' | ']' | '\u{201D}' | '\u{2019}')
+}
+
+/// The word immediately before byte offset `end`, without leading punctuation.
+fn preceding_word(text: &str, end: usize) -> &str {
+    let before = &text[..end];
+    let word_start = before.rfind(char::is_whitespace).map_or(0, |idx| {
+        idx + before[idx..].chars().next().map_or(1, char::len_utf8)
+    });
+    before[word_start..].trim_start_matches(|c: char| !c.is_alphanumeric())
+}
+
+fn is_abbreviation(word: &str) -> bool {
+    if word.is_empty() || word == "I" {
+        return false;
+    }
+    let lower = word.to_lowercase();
+    if ABBREVIATIONS.contains(&lower.as_str()) {
+        return true;
+    }
+    // Initials and initialisms: "J", "U.S.A".
+    lower
+        .split('.')
+        .all(|part| part.chars().count() == 1 && part.chars().all(char::is_alphabetic))
+}
+
+fn push_trimmed(text: &str, range: Range<usize>, spans: &mut Vec<Range<usize>>) {
+    let slice = &text[range.clone()];
+    let trimmed_start = slice.len() - slice.trim_start().len();
+    let trimmed_end = slice.trim_end().len();
+    if trimmed_start < trimmed_end {
+        spans.push(range.start + trimmed_start..range.start + trimmed_end);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_on_terminators() {
+        assert_eq!(
+            split_sentences("It rained. Was it cold? Yes!  "),
+            ["It rained.", "Was it cold?", "Yes!"]
+        );
+    }
+
+    #[test]
+    fn abbreviations_and_initials_do_not_end_sentences() {
+        assert_eq!(
+            split_sentences("Dr. Smith met J. R. Jones at 9 a.m. Then they left."),
+            ["Dr. Smith met J. R. Jones at 9 a.m. Then they left."]
+        );
+        assert_eq!(
+            split_sentences("Bring fruit, e.g. apples. The U.S.A. is large."),
+            ["Bring fruit, e.g. apples.", "The U.S.A. is large."]
+        );
+    }
+
+    #[test]
+    fn periods_inside_tokens_are_not_boundaries() {
+        assert_eq!(
+            split_sentences("Pi is 3.14 roughly. It costs $2.50."),
+            ["Pi is 3.14 roughly.", "It costs $2.50."]
+        );
+    }
+
+    #[test]
+    fn lowercase_continuation_is_not_a_boundary() {
+        assert_eq!(
+            split_sentences("He said wait... then left."),
+            ["He said wait... then left."]
+        );
+    }
+
+    #[test]
+    fn closing_quotes_stay_with_their_sentence() {
+        assert_eq!(
+            split_sentences("She said \"Stop.\" He did."),
+            ["She said \"Stop.\"", "He did."]
+        );
+    }
+
+    #[test]
+    fn spans_are_trimmed_byte_ranges() {
+        let text = "  Héllo there.  Bye. ";
+        let spans = sentence_spans(text);
+        assert_eq!(
+            spans.iter().map(|r| &text[r.clone()]).collect::<Vec<_>>(),
+            ["Héllo there.", "Bye."]
+        );
+        assert_eq!(spans[0], 2..15);
+    }
+
+    #[test]
+    fn empty_text_has_no_sentences() {
+        assert!(sentence_spans("").is_empty());
+        assert!(sentence_spans("   \n ").is_empty());
+    }
+
+    #[test]
+    fn word_count_ignores_punctuation_only_tokens() {
+        assert_eq!(word_count("Hello, world - it's 3 o'clock!"), 5);
+        assert_eq!(word_count(""), 0);
+        assert_eq!(word_count(" \t\n "), 0);
+        assert_eq!(word_count("... -- !? ,"), 0);
+    }
+
+    #[test]
+    fn has_min_words_always_needs_one_word() {
+        assert!(has_min_words("one two three", 3));
+        assert!(!has_min_words("one two", 3));
+        assert!(!has_min_words("", 0));
+        assert!(!has_min_words("   ", 0));
+        assert!(!has_min_words("?!", 0));
+        assert!(has_min_words("ok", 0));
+    }
+
+    #[test]
+    fn locate_returns_character_offsets() {
+        let text = "Café open. It rained.";
+        let mut claimed = Vec::new();
+        assert_eq!(locate(text, " It rained. ", &mut claimed), Some(11..21));
+        assert_eq!(locate(text, "it RAINED.", &mut Vec::new()), Some(11..21));
+        assert_eq!(locate(text, "Snow.", &mut claimed), None);
+        assert_eq!(locate(text, "  ", &mut claimed), None);
+    }
+
+    #[test]
+    fn locate_moves_on_to_unclaimed_occurrences() {
+        let text = "No. Yes. No.";
+        let mut claimed = Vec::new();
+        assert_eq!(locate(text, "No.", &mut claimed), Some(0..3));
+        assert_eq!(locate(text, "No.", &mut claimed), Some(9..12));
+        assert_eq!(locate(text, "No.", &mut claimed), Some(0..3));
+        assert_eq!(locate(text, "Yes.", &mut claimed), Some(4..8));
+    }
+
+    #[test]
+    fn chunks_are_byte_ranges_of_whole_sentences() {
+        let text = "Één twee. Drie vier. Vijf.";
+        let chunks = chunk_text(text, 20);
+        assert_eq!(
+            chunks.iter().map(|r| &text[r.clone()]).collect::<Vec<_>>(),
+            ["Één twee. Drie vier.", "Vijf."]
+        );
+        assert_eq!(chunks[1], 23..28);
+        assert_eq!(char_range(text, chunks[1].clone()), 21..26);
+    }
+
+    #[test]
+    fn long_sentences_are_split_at_whitespace() {
+        let text = "alpha beta gamma delta.";
+        let chunks = chunk_text(text, 12);
+        assert_eq!(
+            chunks.iter().map(|r| &text[r.clone()]).collect::<Vec<_>>(),
+            ["alpha beta", "gamma delta."]
+        );
+        assert_eq!(chunk_text("abcdef", 4), [0..4, 4..6]);
+        assert!(chunk_text("", 10).is_empty());
+    }
+}
@@ -0,0 +1,20 @@
+/// Rough token estimate for budgeting prompt context (~4 characters per token
+/// for English text). Deliberately conservative; not a real tokenizer.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Keep leading items while they fit in `budget`, deducting the cost of each kept item.
+pub fn take_within_budget(items: Vec<String>, budget: &mut usize) -> Vec<String> {
+    let mut kept = Vec::new();
+    for item in items {
+        // +1 for the joining newline.
+        let cost = estimate_tokens(&item) + 1;
+        if cost > *budget {
+            break;
+        }
+        *budget -= cost;
+        kept.push(item);
+    }
+    kept
+}
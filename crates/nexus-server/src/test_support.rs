@@ -0,0 +1,37 @@
+//! Setup for tests that need the services configured in `.env`. Those tests
+//! are `#[ignore]`d; run them with `cargo test -- --ignored`.
+
+use crate::api::state::AppState;
+use crate::config::AppConfig;
+use crate::db::DatabaseConnections;
+
+/// App state connected to the configured services, with migrations applied
+/// and collections created.
+pub async fn live_state() -> AppState {
+    live_state_with(|_| {}).await
+}
+
+/// [`live_state`] with the configuration adjusted first.
+pub async fn live_state_with(configure: impl FnOnce(&mut AppConfig)) -> AppState {
+    dotenvy::dotenv().ok();
+    let mut config = AppConfig::from_env().expect("configuration should load from the environment");
+    configure(&mut config);
+
+    let db = DatabaseConnections::connect(&config)
+        .await
+        .expect("configured services should be reachable");
+    sqlx::migrate!("../../migrations")
+        .run(&db.pg)
+        .await
+        .expect("migrations should apply");
+
+    let (jobs, _) = crate::api::jobs::channel(config.job_queue_capacity);
+    let state = AppState::new(db, config, jobs).expect("app state should build");
+    crate::river::episodic::ensure_collection(&state)
+        .await
+        .expect("episodic collection should exist");
+    crate::river::beliefs::ensure_collection(&state)
+        .await
+        .expect("belief collection should exist");
+    state
+}
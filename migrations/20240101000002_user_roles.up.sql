@@ -0,0 +1,2 @@
+-- User roles: 'user' or 'admin'
+ALTER TABLE users ADD COLUMN IF NOT EXISTS role VARCHAR(50) NOT NULL DEFAULT 'user';
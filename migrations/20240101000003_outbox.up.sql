@@ -0,0 +1,12 @@
+-- Cross-store writes (Neo4j, Qdrant) recorded in Postgres and applied by a
+-- background processor, so they survive failures and restarts.
+CREATE TABLE IF NOT EXISTS outbox (
+    id UUID PRIMARY KEY,
+    op JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_outbox_pending ON outbox(created_at) WHERE processed_at IS NULL;
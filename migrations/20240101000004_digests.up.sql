@@ -0,0 +1,11 @@
+-- End-of-day digests of a user's new beliefs and contradictions.
+CREATE TABLE IF NOT EXISTS digests (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    digest_date DATE NOT NULL,
+    new_beliefs JSONB NOT NULL DEFAULT '[]',
+    contradictions JSONB NOT NULL DEFAULT '[]',
+    reflection TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, digest_date)
+);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS idx_analyses_search_vector;
+DROP INDEX IF EXISTS idx_analyses_user_id;
+ALTER TABLE analyses DROP COLUMN IF EXISTS search_vector;
+ALTER TABLE analyses DROP COLUMN IF EXISTS user_id;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS belief_audit;
+DROP FUNCTION IF EXISTS belief_audit_append_only();
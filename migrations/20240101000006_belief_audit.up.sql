@@ -0,0 +1,30 @@
+-- Append-only ledger of belief changes. Each row carries a SHA-256 hash over
+-- its contents and the previous row's hash for the same user, so an edited or
+-- removed row breaks the chain.
+CREATE TABLE IF NOT EXISTS belief_audit (
+    id BIGSERIAL PRIMARY KEY,
+    belief_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    action VARCHAR(20) NOT NULL,
+    old_confidence DOUBLE PRECISION,
+    new_confidence DOUBLE PRECISION,
+    source_message_id UUID,
+    -- Who made the change; NULL for the system (e.g. eviction).
+    actor_id UUID,
+    reason TEXT NOT NULL,
+    prev_hash CHAR(64),
+    hash CHAR(64) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_belief_audit_user ON belief_audit(user_id, id);
+
+CREATE OR REPLACE FUNCTION belief_audit_append_only() RETURNS trigger AS $$
+BEGIN
+    RAISE EXCEPTION 'belief_audit is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER belief_audit_append_only
+    BEFORE UPDATE OR DELETE ON belief_audit
+    FOR EACH ROW EXECUTE FUNCTION belief_audit_append_only();
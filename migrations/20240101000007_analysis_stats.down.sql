@@ -0,0 +1,10 @@
+DROP INDEX IF EXISTS idx_analyses_user_presupposition;
+DROP INDEX IF EXISTS idx_analyses_user_nominalisation;
+DROP INDEX IF EXISTS idx_analyses_user_passive;
+ALTER TABLE analyses
+    DROP COLUMN IF EXISTS naturalised_claim_count,
+    DROP COLUMN IF EXISTS framing_count,
+    DROP COLUMN IF EXISTS implicature_count,
+    DROP COLUMN IF EXISTS presupposition_count,
+    DROP COLUMN IF EXISTS nominalisation_count,
+    DROP COLUMN IF EXISTS passive_voice_count;
@@ -0,0 +1,26 @@
+-- Per-analysis feature counts, written alongside the JSONB result so analyses
+-- can be filtered and aggregated without unpacking it.
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS passive_voice_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS nominalisation_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS presupposition_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS implicature_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS framing_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS naturalised_claim_count INTEGER NOT NULL DEFAULT 0;
+
+-- Backfill from the stored results.
+UPDATE analyses SET
+    passive_voice_count = (
+        SELECT COUNT(*) FROM jsonb_array_elements(result->'syntactic'->'voice_analysis') AS v
+        WHERE v->>'voice' = 'passive'
+    ),
+    nominalisation_count = jsonb_array_length(result->'syntactic'->'nominalisations'),
+    presupposition_count = jsonb_array_length(result->'semantic'->'presuppositions'),
+    implicature_count = jsonb_array_length(result->'semantic'->'implicatures'),
+    framing_count = jsonb_array_length(result->'discourse'->'framing'),
+    naturalised_claim_count =
+        jsonb_array_length(result->'critical_synthesis'->'naturalised_claims');
+
+CREATE INDEX IF NOT EXISTS idx_analyses_user_passive ON analyses(user_id, passive_voice_count);
+CREATE INDEX IF NOT EXISTS idx_analyses_user_nominalisation ON analyses(user_id, nominalisation_count);
+CREATE INDEX IF NOT EXISTS idx_analyses_user_presupposition ON analyses(user_id, presupposition_count);
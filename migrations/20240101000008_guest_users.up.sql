@@ -0,0 +1,5 @@
+-- Ephemeral accounts created by POST /api/v1/auth/guest and deleted, with
+-- everything that cascades from them, once past their retention window.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS is_guest BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_users_guest_created_at ON users(created_at) WHERE is_guest;
@@ -0,0 +1,16 @@
+-- Contradictions between beliefs, mirrored from the Neo4j CONTRADICTS edges so
+-- they can be listed and filtered without traversing the graph.
+CREATE TABLE IF NOT EXISTS contradictions (
+    belief_a_id UUID NOT NULL,
+    belief_b_id UUID NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    explanation TEXT NOT NULL,
+    severity DOUBLE PRECISION NOT NULL,
+    detected_at TIMESTAMPTZ NOT NULL,
+    -- Set when the user marks the contradiction resolved.
+    resolved_at TIMESTAMPTZ,
+    PRIMARY KEY (belief_a_id, belief_b_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_contradictions_user ON contradictions(user_id, detected_at DESC);
+CREATE INDEX IF NOT EXISTS idx_contradictions_belief_b ON contradictions(belief_b_id);
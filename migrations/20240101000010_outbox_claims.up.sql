@@ -0,0 +1,5 @@
+-- Outbox entries are claimed for a lease rather than held under a row lock
+-- while their writes run, and deleted once applied.
+ALTER TABLE outbox ADD COLUMN IF NOT EXISTS claimed_until TIMESTAMPTZ;
+
+DELETE FROM outbox WHERE processed_at IS NOT NULL;
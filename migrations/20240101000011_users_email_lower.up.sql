@@ -0,0 +1,3 @@
+-- Emails are stored lowercased and looked up with lower(email); index that
+-- expression so the lookup can use it and mixed-case duplicates are rejected.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_users_email_lower ON users(lower(email));